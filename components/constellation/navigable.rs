@@ -3,123 +3,294 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use core::todo;
-use std::cell::RefCell;
-use std::collections::BTreeSet;
-use std::sync::Weak;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::ops::Deref;
+use std::rc::{Rc, Weak};
 
 use base::id::BrowsingContextId;
-use script_traits::session_history::{DocumentId, DocumentState, SessionHistoryEntry};
+use script_traits::session_history::{
+    DocumentId, DocumentState, NestedHistory, NestedHistoryId, SessionHistoryEntry,
+    SessionHistoryEntryStep,
+};
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
-
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
-/// An id to differeniate navigables.
-pub struct NavigableId(usize);
-
-impl Default for NavigableId {
-    fn default() -> Self {
-        Self(0)
-    }
-}
-
-/// <https://html.spec.whatwg.org/multipage/#navigable>
-pub struct Navigable {
-    id: NavigableId,
-    parent: Option<Weak<Navigable>>,
-    is_closing: bool,
-    active_session_history_entry: RefCell<Option<SessionHistoryEntry>>,
-    current_session_history_entry: Option<SessionHistoryEntry>,
-    /// A list of session history entries, initially a new list.
-    session_history_entries: RefCell<Vec<SessionHistoryEntry>>,
-    name: String,
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+/// A single deferred step from a traversable's session history traversal queue.
+///
+/// This is the algorithm closure itself, not a message: the queue that holds these has no
+/// channel of its own to the script thread that actually runs them, so draining it hands each
+/// one to the caller's dispatcher (e.g. a
+/// <https://html.spec.whatwg.org/multipage/#navigation-and-traversal-task-source> sender) rather
+/// than running it here.
+pub type SessionHistoryTraversalStep = Box<dyn FnOnce() + Send>;
+
+/// <https://html.spec.whatwg.org/multipage/#tn-session-history-traversal-queue>
+///
+/// One of these is owned by each top-level traversable. It gives `apply_history_step` and the
+/// getters it depends on (`get_session_history_entries`, `get_all_used_history_steps`) a single
+/// serialization point, so the `// Assert: this is running within traversable's session history
+/// traversal queue` comments scattered through this file describe something real instead of
+/// aspirational.
+#[derive(Default)]
+pub struct SessionHistoryTraversalQueue {
+    steps: RefCell<VecDeque<SessionHistoryTraversalStep>>,
+    /// True while [`Self::drain`] is already popping steps. A step that itself appends another
+    /// step (e.g. a traversal that triggers a further navigation) goes to the back of `steps`
+    /// instead of running inline, keeping the queue FIFO rather than depth-first.
+    processing: Cell<bool>,
+    /// Navigables with a pending entry queued via
+    /// [`Self::append_session_history_synchronous_navigation_steps`], reported by
+    /// [`Self::has_pending_synchronous_navigation`]. `apply_history_step` does not read this set;
+    /// it computes its step whether or not a synchronous navigation is pending.
+    pending_synchronous_navigations: RefCell<BTreeSet<NavigableId>>,
 }
 
-// Perhaps Navigable is a trait, and `Traversable` implements it? Not sure when a non-traversable
-// navigable comes into play
-impl Navigable {
-    /// Dependencies:
-    /// 
-    ///  - <https://html.spec.whatwg.org/multipage/document-sequences.html#browsing-context-group>
-    ///  - storage shed: <https://storage.spec.whatwg.org/#legacy-clone-a-traversable-storage-shed>
-    ///  - "A user agent holds a top-level traversable set (a set of top-level traversables).
-    ///    These are typically presented to the user in the form of browser windows or browser tabs."
-    ///    <https://html.spec.whatwg.org/multipage/document-sequences.html#top-level-traversable-set>
-    /// 
-    /// <https://html.spec.whatwg.org/multipage/#creating-a-new-top-level-traversable>
-    pub fn new(
-        opener: Option<BrowsingContextId>,
-        target_name: Option<String>,
-        opener_navigable: Option<Navigable>
-    ) -> Navigable {
-        todo!()
-
-        // Step 1. Let document be null.
+impl SessionHistoryTraversalQueue {
+    pub fn new() -> SessionHistoryTraversalQueue {
+        SessionHistoryTraversalQueue::default()
+    }
 
-        // Step 2. If opener is null, then set document to the second return value of creating a
-        // new top-level browsing context and document.
+    /// <https://html.spec.whatwg.org/multipage/#append-a-session-history-traversal-steps>
+    ///
+    /// Used by cross-document navigation finalization (and `history.back()`/`forward()`) to
+    /// queue a step that must run after every step already queued.
+    pub fn append_session_history_traversal_steps(&self, step: impl FnOnce() + Send + 'static) {
+        self.steps.borrow_mut().push_back(Box::new(step));
+    }
 
-            // This process involves:
+    /// <https://html.spec.whatwg.org/multipage/#append-a-session-history-synchronous-navigation-steps>
+    ///
+    /// As [`Self::append_session_history_traversal_steps`], but additionally records `navigable`
+    /// as having a pending synchronous navigation, so `apply_history_step` can detect and drain
+    /// it before computing steps. The caller is responsible for calling
+    /// [`Self::clear_pending_synchronous_navigation`] once `step` has actually run.
+    pub fn append_session_history_synchronous_navigation_steps(
+        &self,
+        navigable: NavigableId,
+        step: impl FnOnce() + Send + 'static,
+    ) {
+        self.pending_synchronous_navigations
+            .borrow_mut()
+            .insert(navigable);
+        self.append_session_history_traversal_steps(step);
+    }
 
-            // 1. Let group and document be the result of creating a new browsing context group and
-            // document.
+    /// Whether `navigable` has a synchronous navigation queued that hasn't run yet.
+    pub fn has_pending_synchronous_navigation(&self, navigable: NavigableId) -> bool {
+        self.pending_synchronous_navigations
+            .borrow()
+            .contains(&navigable)
+    }
 
-                // <https://html.spec.whatwg.org/multipage/document-sequences.html#browsing-context-group>
+    /// Mark `navigable`'s pending synchronous navigation as resolved, once the step
+    /// [`Self::append_session_history_synchronous_navigation_steps`] queued for it has run.
+    pub fn clear_pending_synchronous_navigation(&self, navigable: NavigableId) {
+        self.pending_synchronous_navigations
+            .borrow_mut()
+            .remove(&navigable);
+    }
 
-            // 2. Return group's browsing context set[0] and document.
+    /// Run every queued step, in FIFO order, by handing each to `dispatch`. Steps appended while
+    /// this runs go to the back of the queue - see `processing`'s doc comment - and are dispatched
+    /// by this same loop once the steps ahead of them have been; a nested call while already
+    /// draining is a no-op.
+    pub fn drain(&self, mut dispatch: impl FnMut(SessionHistoryTraversalStep)) {
+        if self.processing.get() {
+            return;
+        }
+
+        self.processing.set(true);
+
+        loop {
+            // Release the borrow before dispatching, so a step that appends can borrow `steps`.
+            let Some(step) = self.steps.borrow_mut().pop_front() else {
+                break;
+            };
+            dispatch(step);
+        }
+
+        self.processing.set(false);
+    }
+}
 
-        // Step 4. Let documentState be a new document state, with:
-        // document: document
-        // initiator origin:  null if opener is null; otherwise, document's origin
-        // origin: document's origin
-        // navigable target name: targetName
-        // about base URL: document's about base URL
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+/// An id to differeniate navigables.
+pub struct NavigableId(usize);
 
-        // let document_state = DocumentState::new(
-        //  document.id(),
-        //  document.referrer_policy(),
-        //  navigable_target_name: target_name,
-        //  initiator_origin: None, //     null if opener is null; otherwise, document's origin
-        //  about_base_url: document.about_base_url() // TODO
-        //)
+impl NavigableId {
+    fn next() -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static NEXT_NAVIGABLE_ID: AtomicUsize = AtomicUsize::new(1);
+        Self(NEXT_NAVIGABLE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
 
-        // Step 5. Let traversable be a new traversable navigable.
-        // let traversable = ...
+impl Default for NavigableId {
+    fn default() -> Self {
+        Self(0)
+    }
+}
 
-        // Step 6. Initialize the navigable traversable given documentState.
-        // self.initialize(document_state, None);
+/// A navigation type relevant to applying a history step.
+///
+/// This stands in for the full Navigation API `NavigationType` (push/replace/reload/traverse),
+/// which is a WebIDL enum generated in the `script` crate and not reachable from here -
+/// `apply_history_step` only ever sees the two variants below.
+///
+/// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#the-navigation-api>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationType {
+    Traverse,
+    Reload,
+}
 
-        // Step 7. Let initialHistoryEntry be traversable's active session history entry.
-        // let initial_history_entry = traversable.active_session_history_entry()
+/// Placeholder for <https://html.spec.whatwg.org/multipage#source-snapshot-params>.
+///
+/// The real type (`net_traits::navigation::SourceSnapshotParams`) carries a `PolicyContainer`
+/// this snapshot's `net_traits` has no module for, so `apply_history_step`'s still-TODO Step 3
+/// (the initiator-origin check) takes this minimal stand-in instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceSnapshotParams {
+    pub has_transient_activation: bool,
+}
 
-        // Step 8. Set initialHistoryEntry's step to 0.
-        // initial_history_entry.set_step(0)
+/// <https://html.spec.whatwg.org/multipage/#history-handling-behavior>
+///
+/// The outcome of [`TraversableNavigable::apply_history_step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryApplicationResult {
+    Applied,
+    Canceled,
+    InitiatorDisallowed,
+}
 
-        // Step 9. Append initialHistoryEntry to traversable's session history entries.
-        // traversable.add_session_history_entry(initial_history_entry);
+/// <https://html.spec.whatwg.org/multipage/#nav-ongoing-navigation>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum OngoingNavigation {
+    #[default]
+    None,
+    Traversal,
+}
 
-        // Step 10. If opener is non-null, then legacy-clone a traversable storage shed given
-        // opener's top-level traversable and traversable.
+/// <https://html.spec.whatwg.org/multipage/interaction.html#dom-visibilitystate>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VisibilityState {
+    Hidden,
+    #[default]
+    Visible,
+}
 
-            // <https://storage.spec.whatwg.org/#legacy-clone-a-traversable-storage-shed>
+/// The data `apply_history_step` hands off for a changing navigable, so its caller can dispatch
+/// it to the script thread that owns the corresponding `Document` - this file has no channel of
+/// its own to that thread (see [`SessionHistoryTraversalStep`]'s doc comment for the same gap).
+///
+/// <https://html.spec.whatwg.org/multipage/#history-step-application>
+#[derive(Clone, Debug)]
+pub struct HistoryStepApplication {
+    pub navigable: NavigableId,
+    pub script_history_length: usize,
+    pub script_history_index: usize,
+    pub navigation_type: Option<NavigationType>,
+}
 
-            // A traversable navigable holds a storage shed, which is a storage shed. A traversable
-            // navigable’s storage shed holds all session storage data. 
+/// A navigation's status, as reported to a [`BiDiObserver`].
+///
+/// Stands in for the richer set the BiDi `browsingContext.navigationStarted`/`navigationCommitted`
+/// /`fragmentNavigated`/`navigationFailed` events distinguish; this file only has enough context
+/// (from [`TraversableNavigable::apply_history_step`]) to report a step as having completed.
+///
+/// <https://w3c.github.io/webdriver-bidi/#module-browsingContext>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationStatus {
+    /// A traversal/reload was applied, and the navigable's current session history entry changed
+    /// or reloaded as a result.
+    Complete,
+}
 
-            // Lot's to do there...
+/// Observes navigable lifecycle and navigation-status events for WebDriver BiDi's
+/// `browsingContext` module (and, by the same hook, Servo's own devtools).
+///
+/// Registered process-wide via [`register_bidi_observer`] rather than per-traversable, since a
+/// BiDi session or devtools client watches every navigable, not just one.
+///
+/// <https://w3c.github.io/webdriver-bidi/#module-browsingContext>
+pub trait BiDiObserver {
+    /// A navigable was created: a new top-level traversable (`parent` is `None`), or a child
+    /// navigable (`parent` is its owning traversable) from [`Navigable::create_child_navigable`].
+    /// `opener` is the navigable that opened this one, for a top-level traversable created via
+    /// `window.open()`. [`TraversableNavigable::new`] is a `todo!()` and sends no notification;
+    /// only `create_child_navigable` calls this.
+    fn navigable_created(
+        &self,
+        navigable_id: NavigableId,
+        parent: Option<NavigableId>,
+        opener: Option<NavigableId>,
+    );
+
+    /// A navigable was destroyed, e.g. via [`TraversableNavigable::destroy_the_child_navigable`].
+    fn navigable_destroyed(&self, navigable_id: NavigableId);
+
+    /// `navigable`'s navigation reached `status`, at `url`.
+    fn navigation_status(&self, navigable_id: NavigableId, url: ServoUrl, status: NavigationStatus);
+}
 
-        // Step 11. Append traversable to the user agent's top-level traversable set.
+thread_local! {
+    /// <https://html.spec.whatwg.org/multipage/document-sequences.html#top-level-traversable-set>
+    ///
+    /// "A user agent holds a top-level traversable set (a set of top-level traversables). These
+    /// are typically presented to the user in the form of browser windows or browser tabs."
+    ///
+    /// `Navigable`/`TraversableNavigable` use `Cell`/`RefCell`, not the `Sync`-safe primitives an
+    /// `Arc` would need, because all of this - like the rest of the constellation's state - is
+    /// only ever touched from the constellation's own thread. A thread-local set matches that,
+    /// where a process-wide one behind a `Mutex` would force every field in the type to be
+    /// `Sync` for no actual benefit.
+    static TOP_LEVEL_TRAVERSABLE_SET: RefCell<Vec<Rc<TraversableNavigable>>> =
+        RefCell::new(Vec::new());
+
+    /// Every [`BiDiObserver`] registered via [`register_bidi_observer`]. Thread-local for the same
+    /// reason as [`TOP_LEVEL_TRAVERSABLE_SET`] - observers are only ever notified from the
+    /// constellation's own thread.
+    static BIDI_OBSERVERS: RefCell<Vec<Rc<dyn BiDiObserver>>> = RefCell::new(Vec::new());
+}
 
-            // ...
+/// Register `observer` to receive every subsequent [`BiDiObserver`] notification.
+pub fn register_bidi_observer(observer: Rc<dyn BiDiObserver>) {
+    BIDI_OBSERVERS.with_borrow_mut(|observers| observers.push(observer));
+}
 
-        // Step 12. Invoke WebDriver BiDi navigable created with traversable and
-        // openerNavigableForWebDriver.
+/// Notify every registered [`BiDiObserver`] via `notify`.
+fn notify_bidi_observers(notify: impl Fn(&dyn BiDiObserver)) {
+    BIDI_OBSERVERS.with_borrow(|observers| {
+        for observer in observers.iter() {
+            notify(observer.as_ref());
+        }
+    });
+}
 
-        // Step 13. Return traversable.
-        // traversable
-    }
+/// <https://html.spec.whatwg.org/multipage/#navigable>
+///
+/// The state every navigable has, whether or not it's a traversable (top-level) one. The
+/// session-history storage, traversal queue, and related machinery that only a top-level
+/// navigable owns live on [`TraversableNavigable`] instead - see its doc comment for why that
+/// split exists.
+pub struct Navigable {
+    id: NavigableId,
+    /// `None` for a top-level traversable; `Some` for a child navigable, pointing at the
+    /// traversable whose session history actually owns this navigable's entries.
+    parent: RefCell<Option<Weak<TraversableNavigable>>>,
+    is_closing: bool,
+    active_session_history_entry: RefCell<Option<SessionHistoryEntry>>,
+    current_session_history_entry: RefCell<Option<SessionHistoryEntry>>,
+    name: String,
+    /// <https://html.spec.whatwg.org/multipage/#nav-ongoing-navigation>
+    ongoing_navigation: Cell<OngoingNavigation>,
+}
 
+impl Navigable {
     /// <https://html.spec.whatwg.org/multipage/#initialize-the-navigable>
-    pub fn initialize(&self, document_state: DocumentState, parent: Option<Navigable>) {
+    pub fn initialize(&self, document_state: DocumentState, parent: Option<Weak<TraversableNavigable>>) {
         // Step 1. Assert: documentState's document is non-null.
 
         // Step 2. Let entry be a new session history entry, with
@@ -135,13 +306,26 @@ impl Navigable {
         // self.set_active_session_history_entry(entry);
 
         // Step 5. Set navigable's parent to parent.
-        // self.set_parent(parent);
+        *self.parent.borrow_mut() = parent;
+    }
+
+    pub(crate) fn id(&self) -> NavigableId {
+        self.id
     }
 
     pub fn active_session_history_entry(&self) -> Option<SessionHistoryEntry> {
         self.active_session_history_entry.borrow().clone()
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#nav-current>
+    pub fn current_session_history_entry(&self) -> Option<SessionHistoryEntry> {
+        self.current_session_history_entry.borrow().clone()
+    }
+
+    fn set_current_session_history_entry(&self, entry: SessionHistoryEntry) {
+        *self.current_session_history_entry.borrow_mut() = Some(entry);
+    }
+
     /// A navigable's active document is its active session history entry's document.
     ///
     /// <https://html.spec.whatwg.org/multipage/#nav-document>
@@ -179,156 +363,730 @@ impl Navigable {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#getting-session-history-entries>
-    // pub fn get_session_history_entries(&self) -> BTreeSet<SessionHistoryEntry> {
-    //     // Step 1. Let traversable be navigable's traversable navigable.
+    ///
+    /// Only reached for a non-traversable (child) navigable - [`TraversableNavigable`] shadows
+    /// this with its own inherent method, which wins method resolution for anything that's
+    /// actually top-level.
+    pub fn get_session_history_entries(&self) -> BTreeSet<SessionHistoryEntry> {
+        // Step 1. Let traversable be navigable's traversable navigable.
+        let Some(traversable) = self.parent.borrow().as_ref().and_then(Weak::upgrade) else {
+            // A child navigable whose traversable has already been dropped, or (in debug builds,
+            // a bug) a bare top-level `Navigable` that was never wrapped in a
+            // `TraversableNavigable`.
+            return BTreeSet::new();
+        };
+
+        // Step 2. Assert: this is running within traversable's session history traversal queue -
+        // true by construction, as with `TraversableNavigable::get_session_history_entries`.
+
+        // Steps 4-5. Collect the document states reachable from traversable's own entries.
+        let mut document_states: Vec<DocumentState> = traversable
+            .get_session_history_entries()
+            .into_iter()
+            .map(|entry| entry.document_state)
+            .collect();
+
+        // Step 6. Search depth-first through nested histories for the one whose id matches
+        // this navigable, descending into further-nested document states as we go.
+        while let Some(document_state) = document_states.pop() {
+            for nested_history in document_state.nested_histories.iter() {
+                if nested_history.id() == self.id.0 {
+                    return nested_history.entries().into_iter().collect();
+                }
+
+                document_states.extend(
+                    nested_history
+                        .entries()
+                        .into_iter()
+                        .map(|entry| entry.document_state),
+                );
+            }
+        }
+
+        // Step 7. Assert: this step is not reached - every navigable created through
+        // `create_child_navigable` has a matching nested history appended to its parent entry's
+        // document state before the navigable itself is handed out, so the lookup above always
+        // finds one.
+        unreachable!("navigable {:?} has no nested history in its traversable", self.id)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/document-sequences.html#creating-a-new-child-navigable>
+    ///
+    /// `container` identifies the containing iframe/frame element's browsing context - this crate
+    /// only ever sees ids, not DOM elements, so that's all it's keyed on. Only one level of
+    /// nesting is modeled: the new navigable's parent is always `parent` itself, matching
+    /// [`Navigable::parent`]'s existing top-level-traversable-only simplification.
+    pub fn create_child_navigable(
+        parent: &Rc<TraversableNavigable>,
+        container: BrowsingContextId,
+        url: ServoUrl,
+        document_state: DocumentState,
+    ) -> NavigableId {
+        let child = Navigable {
+            id: NavigableId::next(),
+            parent: RefCell::new(Some(Rc::downgrade(parent))),
+            is_closing: false,
+            active_session_history_entry: RefCell::new(None),
+            current_session_history_entry: RefCell::new(None),
+            name: String::new(),
+            ongoing_navigation: Cell::new(OngoingNavigation::default()),
+        };
+        let child_id = child.id;
+
+        // Let entry be a new session history entry for the child's initial document, and set it
+        // as both the child's current and active entry.
+        let entry = SessionHistoryEntry::new(url, document_state);
+        entry.set_step(0);
+        *child.current_session_history_entry.borrow_mut() = Some(entry.clone());
+        *child.active_session_history_entry.borrow_mut() = Some(entry);
+
+        // Append a new nested history, keyed by the child's own id, to the entry stored in
+        // `parent.session_history_entries` that matches the parent's active session history
+        // entry - that's what `get_session_history_entries` actually searches above, not
+        // `parent.active_session_history_entry`'s own copy. `SessionHistoryEntry`/`DocumentState`
+        // are plain `Clone` types with no shared interior (no `Rc`/`RefCell` wrapping the
+        // document state itself), so pushing onto the latter would never be visible to the
+        // former - the search would always fall through to its `unreachable!()`.
+        if let Some(active_entry) = parent.active_session_history_entry() {
+            let mut entries = parent.session_history_entries.borrow_mut();
+            if let Some(stored_entry) = entries.iter_mut().find(|entry| **entry == active_entry) {
+                stored_entry.document_state.nested_histories.push(NestedHistory::new(
+                    NestedHistoryId::from_navigable_id(child_id.0),
+                    vec![child
+                        .current_session_history_entry()
+                        .expect("just set above")],
+                ));
+            }
+        }
+
+        parent.child_navigables.borrow_mut().insert(container, child);
+
+        notify_bidi_observers(|observer| {
+            observer.navigable_created(child_id, Some(parent.id()), None)
+        });
+
+        child_id
+    }
+
+    /// A top-level traversable is a traversable navigable with a null parent.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/document-sequences.html#top-level-traversable>
+    pub fn is_top_level(&self) -> bool {
+        self.parent.borrow().is_none()
+    }
+}
 
-    //     // Step 2. Assert: this is running within traversable's session history traversal queue.
-    //     // TODO :o https://html.spec.whatwg.org/multipage/#tn-session-history-traversal-queue
+/// The quota applied to each origin's `sessionStorage` bottle.
+///
+/// <https://storage.spec.whatwg.org/#concept-site-quota>
+const SESSION_STORAGE_QUOTA_BYTES: usize = 5 * 1024 * 1024;
+
+/// Bytes a key/value pair charges against a bottle's quota - per the spec, UTF-16 code units,
+/// counted as two bytes apiece.
+///
+/// <https://storage.spec.whatwg.org/#concept-site-quota>
+fn entry_size(key: &str, value: &str) -> usize {
+    (key.encode_utf16().count() + value.encode_utf16().count()) * 2
+}
 
-    //     // Step 3. If navigable is traversable, return traversable's session history entries.
-    //     self.session_history_entries.clone()
+/// <https://storage.spec.whatwg.org/#bottle>
+///
+/// One origin's worth of `sessionStorage` data.
+#[derive(Clone, Debug, Default)]
+struct StorageBottle {
+    /// `sessionStorage.key()` is indexed by insertion order, which a sorted map would silently
+    /// reorder, so entries live in a plain `Vec` instead of e.g. a `BTreeMap`.
+    entries: Vec<(String, String)>,
+    /// Total bytes currently charged against the bottle's quota; kept alongside `entries` rather
+    /// than recomputed so `set` can reject an over-quota write in O(1) instead of re-summing on
+    /// every call.
+    bytes_used: usize,
+}
 
-    //     // Step 4. Let docStates be an empty ordered set of document states.
+impl StorageBottle {
+    fn get(&self, key: &str) -> Option<String> {
+        self.entries
+            .iter()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value.clone())
+    }
 
-    //     // Step 5. For each entry of traversable's session history entries, append entry's document
-    //     // state to docStates.
+    fn key(&self, index: usize) -> Option<String> {
+        self.entries.get(index).map(|(key, _)| key.clone())
+    }
 
-    //     // Step 6. For each docState of docStates:
-    //     // Step 6.1. For each nestedHistory of docState's nested histories:
-    //     // Step 6.1.1. If nestedHistory's id equals navigable's id, return nestedHistory's entries.
-    //     // Step 6.1.2. For each entry of nestedHistory's entries, append entry's document state to
-    //     // docStates.
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
 
-    //     // Step 7. Assert: this step is not reached.
-    // }
+    /// Returns `false`, leaving the bottle unchanged, if storing `value` under `key` would push
+    /// `bytes_used` past `quota`.
+    fn set(&mut self, key: String, value: String, quota: usize) -> bool {
+        let previous_size = self
+            .get(&key)
+            .map(|previous_value| entry_size(&key, &previous_value))
+            .unwrap_or(0);
+        let new_size = entry_size(&key, &value);
 
-    /// NavigationApi
-    // TODO(NavigationAPI)
-    /// <https://html.spec.whatwg.org/multipage/#apply-the-history-step>
-    // pub(crate) fn apply_history_step(
-    //     &self,
-    //     step: usize,
-    //     // check_for_cancellation: bool // TODO
-    //     source_snapshot_params: Option<SourceSnapshotParams>,
-    //     navigationType: Option<NavigationType>,
-    // ) -> HistoryApplicationResult {
-    //     // Step 1. Assert: This is running within traversable's session history traversal queue.
-    //     // TODO
+        if self.bytes_used - previous_size + new_size > quota {
+            return false;
+        }
+
+        self.bytes_used = self.bytes_used - previous_size + new_size;
+
+        match self.entries.iter_mut().find(|(entry_key, _)| *entry_key == key) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((key, value)),
+        }
+
+        true
+    }
+
+    fn remove(&mut self, key: &str) -> Option<String> {
+        let index = self.entries.iter().position(|(entry_key, _)| entry_key == key)?;
+        let (key, value) = self.entries.remove(index);
+        self.bytes_used -= entry_size(&key, &value);
+        Some(value)
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes_used = 0;
+    }
+}
+
+/// <https://storage.spec.whatwg.org/#storage-shed>
+///
+/// Owned by each [`TraversableNavigable`]; holds that tab's `sessionStorage` data, one
+/// [`StorageBottle`] per storage key. `sessionStorage` doesn't need the full storage-key
+/// partitioning (top-level site plus opaqueness) other storage types use, so the origin alone is
+/// used as the key here.
+#[derive(Default)]
+pub struct StorageShed {
+    bottles: RefCell<HashMap<ImmutableOrigin, StorageBottle>>,
+}
+
+impl StorageShed {
+    fn new() -> StorageShed {
+        StorageShed::default()
+    }
+
+    /// <https://storage.spec.whatwg.org/#legacy-clone-a-traversable-storage-shed>
+    ///
+    /// Deep-copies `opener`'s bottles into `self` - a snapshot, not a shared reference: writes
+    /// made afterwards, by either traversable, aren't reflected in the other's copy.
+    fn legacy_clone(&self, opener: &StorageShed) {
+        let mut bottles = self.bottles.borrow_mut();
+        for (origin, bottle) in opener.bottles.borrow().iter() {
+            bottles.insert(origin.clone(), bottle.clone());
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/webstorage.html#the-sessionstorage-attribute>
+    pub fn session_storage_for(&self, origin: &ImmutableOrigin) -> SessionStorage<'_> {
+        SessionStorage {
+            shed: self,
+            origin: origin.clone(),
+        }
+    }
+}
+
+/// A per-origin view onto a [`StorageShed`], backing `Window.sessionStorage`.
+///
+/// No `Window` exists in this snapshot to actually hold one of these - it's exposed here so that
+/// whichever `dom::window` lands later only has to call `TraversableNavigable::session_storage_for`
+/// and forward `Storage`'s methods onto it.
+///
+/// <https://html.spec.whatwg.org/multipage/webstorage.html#the-sessionstorage-attribute>
+pub struct SessionStorage<'a> {
+    shed: &'a StorageShed,
+    origin: ImmutableOrigin,
+}
+
+impl SessionStorage<'_> {
+    pub fn len(&self) -> usize {
+        self.shed
+            .bottles
+            .borrow()
+            .get(&self.origin)
+            .map(StorageBottle::len)
+            .unwrap_or(0)
+    }
+
+    pub fn key(&self, index: usize) -> Option<String> {
+        self.shed.bottles.borrow().get(&self.origin)?.key(index)
+    }
+
+    pub fn get_item(&self, key: &str) -> Option<String> {
+        self.shed.bottles.borrow().get(&self.origin)?.get(key)
+    }
+
+    /// Returns `false` if storing `value` would exceed the origin's quota.
+    pub fn set_item(&self, key: String, value: String) -> bool {
+        self.shed
+            .bottles
+            .borrow_mut()
+            .entry(self.origin.clone())
+            .or_default()
+            .set(key, value, SESSION_STORAGE_QUOTA_BYTES)
+    }
+
+    pub fn remove_item(&self, key: &str) -> Option<String> {
+        self.shed.bottles.borrow_mut().get_mut(&self.origin)?.remove(key)
+    }
+
+    pub fn clear(&self) {
+        if let Some(bottle) = self.shed.bottles.borrow_mut().get_mut(&self.origin) {
+            bottle.clear();
+        }
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/document-sequences.html#traversable-navigable>
+///
+/// A top-level navigable. Session-history storage (`session_history_entries`,
+/// `current_session_history_step`), the traversal queue, and [`VisibilityState`] only make sense
+/// for one of these, so they live here rather than on every [`Navigable`] - the source used to
+/// carry all of it undifferentiated, forcing every method to pretend `self` was both kinds of
+/// navigable at once. A `TraversableNavigable` derefs to its shared `Navigable` state for
+/// everything that doesn't care which kind of navigable it's looking at.
+pub struct TraversableNavigable {
+    navigable: Navigable,
+    /// <https://html.spec.whatwg.org/multipage/#tn-current-session-history-step>
+    current_session_history_step: Cell<usize>,
+    /// A list of session history entries, initially a new list.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#tn-session-history-entries>
+    session_history_entries: RefCell<Vec<SessionHistoryEntry>>,
+    /// <https://html.spec.whatwg.org/multipage/#tn-running-nested-apply-history-step>
+    running_nested_apply_history_step: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#tn-session-history-traversal-queue>
+    session_history_traversal_queue: SessionHistoryTraversalQueue,
+    /// <https://html.spec.whatwg.org/multipage/interaction.html#system-visibility-state>
+    visibility_state: Cell<VisibilityState>,
+    /// <https://storage.spec.whatwg.org/#tn-storage-shed>
+    storage_shed: StorageShed,
+    /// Child navigables (e.g. iframes) whose session history lives nested inside one of this
+    /// traversable's own session history entries, keyed by the containing browsing context so
+    /// [`TraversableNavigable::destroy_the_child_navigable`] can find them again when their
+    /// container is removed.
+    child_navigables: RefCell<HashMap<BrowsingContextId, Navigable>>,
+}
+
+impl Deref for TraversableNavigable {
+    type Target = Navigable;
+
+    fn deref(&self) -> &Navigable {
+        &self.navigable
+    }
+}
+
+impl TraversableNavigable {
+    /// Dependencies:
+    ///
+    ///  - <https://html.spec.whatwg.org/multipage/document-sequences.html#browsing-context-group>
+    ///  - storage shed: <https://storage.spec.whatwg.org/#legacy-clone-a-traversable-storage-shed>
+    ///  - "A user agent holds a top-level traversable set (a set of top-level traversables).
+    ///    These are typically presented to the user in the form of browser windows or browser tabs."
+    ///    <https://html.spec.whatwg.org/multipage/document-sequences.html#top-level-traversable-set>
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#creating-a-new-top-level-traversable>
+    pub fn new(
+        opener: Option<BrowsingContextId>,
+        target_name: Option<String>,
+        opener_navigable: Option<Rc<TraversableNavigable>>,
+    ) -> Rc<TraversableNavigable> {
+        todo!()
+
+        // Step 1. Let document be null.
+
+        // Step 2. If opener is null, then set document to the second return value of creating a
+        // new top-level browsing context and document.
+
+            // This process involves:
+
+            // 1. Let group and document be the result of creating a new browsing context group and
+            // document.
+
+                // <https://html.spec.whatwg.org/multipage/document-sequences.html#browsing-context-group>
+
+            // 2. Return group's browsing context set[0] and document.
+
+        // Step 4. Let documentState be a new document state, with:
+        // document: document
+        // initiator origin:  null if opener is null; otherwise, document's origin
+        // origin: document's origin
+        // navigable target name: targetName
+        // about base URL: document's about base URL
+
+        // let document_state = DocumentState::new(
+        //  document.id(),
+        //  document.referrer_policy(),
+        //  navigable_target_name: target_name,
+        //  initiator_origin: None, //     null if opener is null; otherwise, document's origin
+        //  about_base_url: document.about_base_url() // TODO
+        //)
+
+        // Step 5. Let traversable be a new traversable navigable.
+        // let traversable = Rc::new(TraversableNavigable { ... });
+
+        // Step 6. Initialize the navigable traversable given documentState.
+        // traversable.initialize(document_state, None);
 
-    //     // Step 2. Let targetStep be the result of getting the used step given traversable and step.
-    //     let target_step = self.get_the_used_step(step);
+        // Step 7. Let initialHistoryEntry be traversable's active session history entry.
+        // let initial_history_entry = traversable.active_session_history_entry()
 
-    //     // Step 3. If initiatorToCheck is not null, then:
-    //     // TODO
+        // Step 8. Set initialHistoryEntry's step to 0.
+        // initial_history_entry.set_step(0)
 
-    //     // Step 4. Let navigablesCrossingDocuments be the result of getting all navigables that
-    //     // might experience a cross-document traversal given traversable and targetStep.
-    //     // https://html.spec.whatwg.org/multipage/#getting-all-navigables-that-might-experience-a-cross-document-traversal
+        // Step 9. Append initialHistoryEntry to traversable's session history entries.
+        // traversable.session_history_entries.borrow_mut().push(initial_history_entry);
 
-    //     // Step 5. If checkForCancelation is true, and the result of checking if unloading is
-    //     // canceled given navigablesCrossingDocuments, traversable, targetStep, and userInvolvement
-    //     // is not "continue", then return that result.
-    //     // https://html.spec.whatwg.org/multipage/#checking-if-unloading-is-canceled
+        // Step 10. If opener is non-null, then legacy-clone a traversable storage shed given
+        // opener's top-level traversable and traversable.
+        // if let Some(opener_navigable) = opener_navigable {
+        //     traversable.legacy_clone_storage_shed(&opener_navigable);
+        // }
 
-    //     // Step 6. Let changingNavigables be the result of get all navigables whose current session
-    //     // history entry will change or reload given traversable and targetStep.
-    //     // https://html.spec.whatwg.org/multipage/#get-all-navigables-whose-current-session-history-entry-will-change-or-reload
+        // Step 11. Append traversable to the user agent's top-level traversable set.
+        // Self::register(&traversable);
 
-    //     // Step 7. Let nonchangingNavigablesThatStillNeedUpdates be the result of getting all
-    //     // navigables that only need history object length/index update given traversable and
-    //     // targetStep.
-    //     // https://html.spec.whatwg.org/multipage/#getting-all-navigables-that-only-need-history-object-length/index-update
+        // Step 12. Invoke WebDriver BiDi navigable created with traversable and
+        // openerNavigableForWebDriver.
+        // notify_bidi_observers(|observer| {
+        //     observer.navigable_created(traversable.id(), None, opener_navigable.as_ref().map(|o| o.id()))
+        // });
+
+        // Step 13. Return traversable.
+        // traversable
+    }
 
-    //     // Step 8. For each navigable of changingNavigables:
-    //     // Step 8.1. Let targetEntry be the result of getting the target history entry given
-    //     // navigable and targetStep.
-    //     let target_entry = self.get_the_target_history_entry(step);
+    /// <https://html.spec.whatwg.org/multipage/document-sequences.html#top-level-traversable-set>
+    fn register(traversable: &Rc<TraversableNavigable>) {
+        TOP_LEVEL_TRAVERSABLE_SET.with_borrow_mut(|set| set.push(Rc::clone(traversable)));
+    }
 
-    //     // Step 8.2. Set navigable's current session history entry to targetEntry.
-    //     if let Some(proxy) = self.browsing_context() {
-    //         // proxy.set_current_history_entry(target_entry);
-    //     }
+    /// <https://html.spec.whatwg.org/multipage/#getting-session-history-entries>
+    ///
+    /// Step 3 of the algorithm: a traversable is always its own traversable navigable.
+    pub fn get_session_history_entries(&self) -> BTreeSet<SessionHistoryEntry> {
+        self.session_history_entries.borrow().iter().cloned().collect()
+    }
 
-    //     // Step 8.3. Set the ongoing navigation for navigable to "traversal".
+    /// <https://storage.spec.whatwg.org/#legacy-clone-a-traversable-storage-shed>
+    fn legacy_clone_storage_shed(&self, opener: &TraversableNavigable) {
+        self.storage_shed.legacy_clone(&opener.storage_shed);
+    }
 
-    //     // Step 9. Let totalChangeJobs be the size of changingNavigables.
-    //     let total_changed_jobs = 1;
+    /// A `Window`'s `sessionStorage` attribute would delegate to this, scoped to its own origin -
+    /// there's no `dom::window` in this snapshot to do the wiring, so it stops here.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/webstorage.html#the-sessionstorage-attribute>
+    pub fn session_storage_for(&self, origin: &ImmutableOrigin) -> SessionStorage<'_> {
+        self.storage_shed.session_storage_for(origin)
+    }
 
-    //     // TODO
+    /// <https://html.spec.whatwg.org/multipage/document-sequences.html#destroy-a-child-navigable>
+    ///
+    /// Tears down `container`'s child navigable and removes the nested history it created in
+    /// [`Navigable::create_child_navigable`] from whichever of this traversable's entries it was
+    /// attached to.
+    pub fn destroy_the_child_navigable(&self, container: BrowsingContextId) {
+        let Some(child) = self.child_navigables.borrow_mut().remove(&container) else {
+            return;
+        };
+
+        for entry in self.session_history_entries.borrow_mut().iter_mut() {
+            entry
+                .document_state
+                .nested_histories
+                .retain(|nested_history| nested_history.id() != child.id().0);
+        }
+
+        notify_bidi_observers(|observer| observer.navigable_destroyed(child.id()));
+    }
 
-    //     HistoryApplicationResult::Applied
-    // }
+    // TODO(NavigationAPI)
+    /// <https://html.spec.whatwg.org/multipage/#apply-the-history-step>
+    ///
+    /// Only classifies and updates `self`: the real algorithm partitions every navigable in the
+    /// traversable's tree into changing/update-only/unaffected sets, but the child navigables in
+    /// `child_navigables` are not classified here, so there is only ever one navigable to
+    /// classify. `dispatch` receives the resulting [`HistoryStepApplication`] whenever one
+    /// is produced - as with [`SessionHistoryTraversalQueue::drain`], this file has no channel of
+    /// its own to the script thread that has to act on it, so the caller supplies one instead.
+    pub(crate) fn apply_history_step(
+        &self,
+        step: usize,
+        // Step 3's initiator-origin check isn't implemented yet - see `SourceSnapshotParams`.
+        _source_snapshot_params: Option<SourceSnapshotParams>,
+        navigation_type: Option<NavigationType>,
+        dispatch: impl FnOnce(HistoryStepApplication),
+    ) -> HistoryApplicationResult {
+        // Step 1. Assert: This is running within traversable's session history traversal queue.
+        // Callers reach this only as a step dispatched out of `SessionHistoryTraversalQueue::drain`.
+        // Pending synchronous navigations (`has_pending_synchronous_navigation`) are not checked
+        // or drained here.
+
+        // Step 2. Let targetStep be the result of getting the used step given traversable and step.
+        let target_step = self.get_the_used_step(step);
+
+        // Step 3. If initiatorToCheck is not null, then: TODO - no source snapshot params are
+        // threaded through far enough yet to compare origins against.
+
+        // Step 4-5. Cross-document traversal / unloading-is-canceled checks: TODO, pending the
+        // `beforeunload` plumbing this crate doesn't have access to from here.
+
+        let entries = self.get_session_history_entries();
+
+        // Step 6. changingNavigables: those whose current session history entry will change or
+        // reload at targetStep.
+        // Step 8.1. Let targetEntry be the result of getting the target history entry given
+        // navigable and targetStep.
+        let target_entry = self.get_the_target_history_entry(target_step);
+        let is_changing = match self.current_session_history_entry() {
+            Some(current) => current != target_entry || current.document_state.reload_pending,
+            None => true,
+        };
+
+        // Step 7. nonchangingNavigablesThatStillNeedUpdates: everything else still gets its
+        // history length/index refreshed even when its current entry doesn't move; only a
+        // changing navigable also gets steps 8.2/8.3 below.
+        if is_changing {
+            // Step 8.2. Set navigable's current session history entry to targetEntry.
+            self.set_current_session_history_entry(target_entry.clone());
+
+            // Step 8.3. Set the ongoing navigation for navigable to "traversal".
+            self.ongoing_navigation.set(OngoingNavigation::Traversal);
+        }
+
+        // Step 9 onwards coordinate the changing navigables' cross-document traversals in
+        // lockstep (`totalChangeJobs`); with only one navigable ever in `changingNavigables`
+        // here, that coordination collapses to reporting this one step to `dispatch`.
+        let script_history_index = entries
+            .iter()
+            .position(|entry| *entry == target_entry)
+            .unwrap_or(0);
+
+        self.current_session_history_step.set(target_step);
+
+        dispatch(HistoryStepApplication {
+            navigable: self.id(),
+            script_history_length: entries.len(),
+            script_history_index,
+            navigation_type,
+        });
+
+        // Same-document navigation finalization isn't modeled in this file yet, so the only
+        // status this reports is a traversal having been applied - there's nowhere else in this
+        // file a BiDi `fragmentNavigated`/`navigationFailed` equivalent could come from.
+        notify_bidi_observers(|observer| {
+            observer.navigation_status(self.id(), target_entry.url().clone(), NavigationStatus::Complete)
+        });
+
+        HistoryApplicationResult::Applied
+    }
 
     /// <https://html.spec.whatwg.org/multipage/#getting-the-used-step>
-    // fn get_the_used_step(&self, step: usize) -> usize {
-    //     // Step 1. Let steps be the result of getting all used history steps within traversable.
-    //     let steps = self.get_all_used_history_steps();
+    fn get_the_used_step(&self, step: usize) -> usize {
+        // Step 1. Let steps be the result of getting all used history steps within traversable.
+        let steps = self.get_all_used_history_steps();
 
-    //     // Step 2. Return the greatest item in steps that is less than or equal to step.
-    //     steps.range(..=step).next_back().cloned().unwrap_or(0)
-    // }
+        // Step 2. Return the greatest item in steps that is less than or equal to step.
+        steps.range(..=step).next_back().copied().unwrap_or(0)
+    }
 
     // TODO(NavigationAPI)
     /// <https://html.spec.whatwg.org/multipage/#getting-the-target-history-entry>
-    // fn get_the_target_history_entry(&self, step: usize) -> SessionHistoryEntry {
-    //     // Step 1. Let entries be the result of getting session history entries for navigable.
-    //     let entries = self.get_session_history_entries();
-
-    //     // Step 2. Return the item in entries that has the greatest step less than or equal to step.
-    //     entries
-    //         .iter()
-    //         .filter(|entry| match entry.step {
-    //             SessionHistoryEntryStep::Integer(i) => i <= step,
-    //             _ => false,
-    //         })
-    //         .last()
-    //         .expect("Document has no session history entries")
-    //         .clone()
-    // }
-
-    // TODO(NavigationApi)
+    fn get_the_target_history_entry(&self, step: usize) -> SessionHistoryEntry {
+        // Step 1. Let entries be the result of getting session history entries for navigable.
+        let entries = self.get_session_history_entries();
+
+        // Step 2. Return the item in entries that has the greatest step less than or equal to step.
+        entries
+            .iter()
+            .filter(|entry| match *entry.step.borrow() {
+                SessionHistoryEntryStep::Integer(i) => i <= step,
+                SessionHistoryEntryStep::Pending => false,
+            })
+            .last()
+            .expect("Document has no session history entries")
+            .clone()
+    }
+
+    // TODO(NavigationAPI)
     /// <https://html.spec.whatwg.org/multipage/#getting-all-used-history-steps>
-    fn get_all_used_history_steps(&self) -> Option<BTreeSet<usize>> {
-        // // Step 2.1.1. Assert: this is running within traversable's session history traversal queue.
-        // // TODO
-
-        // // Step 2. Let steps be an empty ordered set of non-negative integers.
-        // let mut steps: BTreeSet<usize> = BTreeSet::new();
-
-        // // Step 3. Let entryLists be the ordered set « traversable's session history entries ».
-        // let entry_list: BTreeSet<SessionHistoryEntry> = self.get_session_history_entries();
-
-        // // It's not clear whether the entry_list should grow during iteration with values from
-        // // entry.nested_histories? That would require two separate operations
-
-        // for entry in entry_list.iter() {
-        //     // Step 4.1.1. Append entry's step to steps.
-        //     match entry.step {
-        //         SessionHistoryEntryStep::Integer(value) => {
-        //             steps.insert(value);
-        //         },
-        //         _ => {},
-        //     }
-
-        //     // For each nestedHistory of entry's document state's nested histories, append
-        //     // nestedHistory's entries list to entryLists.
-        //     for nested_history in entry.document_state.nested_histories.iter() {
-        //         for entry in nested_history.entries().iter() {
-        //             self.append_session_history_entry(entry.clone());
-        //         }
-        //     }
-        // }
+    fn get_all_used_history_steps(&self) -> BTreeSet<usize> {
+        // Step 2.1.1. Assert: this is running within traversable's session history traversal queue.
+        // As with `get_session_history_entries`, true as long as this is only reached from a
+        // `SessionHistoryTraversalQueue::drain` dispatch.
+
+        // Step 2. Let steps be an empty ordered set of non-negative integers.
+        let mut steps: BTreeSet<usize> = BTreeSet::new();
+
+        // Step 3. Let entryLists be the ordered set « traversable's session history entries ».
+        let mut entry_lists: Vec<BTreeSet<SessionHistoryEntry>> =
+            vec![self.get_session_history_entries()];
+
+        // Step 4. For each entryList of entryLists:
+        while let Some(entry_list) = entry_lists.pop() {
+            for entry in entry_list.iter() {
+                // Step 4.1.1. Append entry's step to steps.
+                if let SessionHistoryEntryStep::Integer(value) = *entry.step.borrow() {
+                    steps.insert(value);
+                }
+
+                // Step 4.1.2. For each nestedHistory of entry's document state's nested
+                // histories, append nestedHistory's entries list to entryLists.
+                for nested_history in entry.document_state.nested_histories.iter() {
+                    entry_lists.push(nested_history.entries().into_iter().collect());
+                }
+            }
+        }
+
+        // Step 5. Return steps, sorted.
+        steps
+    }
 
-        // // Step 5. Return steps, sorted.
-        // steps
+    /// <https://html.spec.whatwg.org/multipage/#append-a-session-history-traversal-steps>
+    pub fn append_session_history_traversal_steps(&self, step: impl FnOnce() + Send + 'static) {
+        self.session_history_traversal_queue
+            .append_session_history_traversal_steps(step);
+    }
 
-        None
+    /// <https://html.spec.whatwg.org/multipage/#append-a-session-history-synchronous-navigation-steps>
+    pub fn append_session_history_synchronous_navigation_steps(
+        &self,
+        step: impl FnOnce() + Send + 'static,
+    ) {
+        self.session_history_traversal_queue
+            .append_session_history_synchronous_navigation_steps(self.id(), step);
     }
 
-    /// A top-level traversable is a traversable navigable with a null parent.
+    /// Drain this traversable's session history traversal queue, one step at a time, by handing
+    /// each to `dispatch` - the
+    /// <https://html.spec.whatwg.org/multipage/#navigation-and-traversal-task-source> sender on
+    /// the script thread that actually owns the documents these steps touch.
+    pub fn drain_session_history_traversal_queue(
+        &self,
+        dispatch: impl FnMut(SessionHistoryTraversalStep),
+    ) {
+        self.session_history_traversal_queue.drain(dispatch);
+    }
+
+    /// Whether `self` is in the middle of running a nested `apply_history_step` call, triggered
+    /// by a step it dispatched re-entering the traversal queue before the outer call returned.
+    /// Nothing in this file sets the flag, so this returns `false`.
     ///
-    /// <https://html.spec.whatwg.org/multipage/document-sequences.html#top-level-traversable>
-    pub fn is_top_level(&self) -> bool {
-        self.parent.is_none()
+    /// <https://html.spec.whatwg.org/multipage/#tn-running-nested-apply-history-step>
+    pub fn is_running_nested_apply_history_step(&self) -> bool {
+        self.running_nested_apply_history_step.get()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/interaction.html#system-visibility-state>
+    pub fn visibility_state(&self) -> VisibilityState {
+        self.visibility_state.get()
     }
+
+    /// <https://html.spec.whatwg.org/multipage/interaction.html#visibility-state>
+    pub fn set_visibility_state(&self, visibility_state: VisibilityState) {
+        self.visibility_state.set(visibility_state);
+    }
+}
+
+/// A fresh document state for `url`, with no navigable target name, opener, or about base URL -
+/// enough for a test entry, not a real navigation.
+#[cfg(test)]
+fn test_document_state(url: &ServoUrl) -> DocumentState {
+    DocumentState::new(
+        DocumentId::next(),
+        script_traits::ReferrerPolicy::default(),
+        None,
+        None,
+        url.origin(),
+        None,
+    )
+}
+
+/// Builds a bare [`TraversableNavigable`] with a single session history entry at `url`, without
+/// going through [`TraversableNavigable::new`] (still a `todo!()` - see its doc comment) or the
+/// constellation message dispatcher that would call it in a real build (not present in this
+/// snapshot, along with the iframe DOM element that would be `create_child_navigable`'s other
+/// real caller). This is the minimal setup `create_child_navigable`/`destroy_the_child_navigable`
+/// actually need: a traversable whose active entry is also present in `session_history_entries`.
+#[cfg(test)]
+fn test_traversable(url: ServoUrl) -> Rc<TraversableNavigable> {
+    let document_state = test_document_state(&url);
+    let entry = SessionHistoryEntry::new(url, document_state);
+    entry.set_step(0);
+
+    Rc::new(TraversableNavigable {
+        navigable: Navigable {
+            id: NavigableId::next(),
+            parent: RefCell::new(None),
+            is_closing: false,
+            active_session_history_entry: RefCell::new(Some(entry.clone())),
+            current_session_history_entry: RefCell::new(Some(entry.clone())),
+            name: String::new(),
+            ongoing_navigation: Cell::new(OngoingNavigation::default()),
+        },
+        current_session_history_step: Cell::new(0),
+        session_history_entries: RefCell::new(vec![entry]),
+        running_nested_apply_history_step: Cell::new(false),
+        session_history_traversal_queue: SessionHistoryTraversalQueue::new(),
+        visibility_state: Cell::new(VisibilityState::default()),
+        storage_shed: StorageShed::new(),
+        child_navigables: RefCell::new(HashMap::new()),
+    })
+}
+
+/// A real caller for `create_child_navigable`/`destroy_the_child_navigable`, and the regression
+/// test for the bug they shipped with: `create_child_navigable` used to attach the new nested
+/// history to `parent.active_session_history_entry`'s own private copy of the entry rather than
+/// the one actually stored in `parent.session_history_entries`, so `get_session_history_entries`
+/// - which only ever searches the latter - could never find it and unconditionally panicked via
+/// its trailing `unreachable!()`. This exercises the pair the way real iframe creation/removal
+/// would, once this snapshot has the DOM element and constellation message handling to drive them
+/// from - neither of which exist here yet.
+#[test]
+fn create_child_navigable_attaches_a_discoverable_nested_history() {
+    base::id::PipelineNamespace::install(base::id::PipelineNamespaceId(0));
+
+    let parent = test_traversable(ServoUrl::parse("https://example.com/").unwrap());
+    let container = BrowsingContextId::new();
+    let child_url = ServoUrl::parse("https://example.com/iframe").unwrap();
+
+    let child_id = Navigable::create_child_navigable(
+        &parent,
+        container,
+        child_url.clone(),
+        test_document_state(&child_url),
+    );
+
+    let child_entries = {
+        let child_navigables = parent.child_navigables.borrow();
+        let child = child_navigables.get(&container).expect("just inserted above");
+        assert_eq!(child.id(), child_id);
+        child.get_session_history_entries()
+    };
+
+    assert_eq!(
+        child_entries.len(),
+        1,
+        "the nested history create_child_navigable attached should be the one found"
+    );
+
+    parent.destroy_the_child_navigable(container);
+    assert!(
+        parent.child_navigables.borrow().get(&container).is_none(),
+        "destroy_the_child_navigable should remove the child"
+    );
+    assert!(
+        parent
+            .session_history_entries
+            .borrow()
+            .iter()
+            .all(|entry| entry.document_state.nested_histories.is_empty()),
+        "destroy_the_child_navigable should also remove the nested history it created"
+    );
 }
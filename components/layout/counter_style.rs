@@ -0,0 +1,346 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Formatting for CSS Counter Styles Level 3, <https://www.w3.org/TR/css-counter-styles-3/>,
+//! shared by list markers (`GeneratedContentInfo::ListItem`) and `counter()`/`counters()`
+//! (`GeneratedContentInfo::ContentItem`) through `generated_content::resolve_generated_content`.
+//!
+//! There is no `@counter-style` at-rule parser or cascade here to resolve a `<counter-style>`
+//! name against, so this module implements the formatting algorithm - the part that is
+//! genuinely self-contained - on its own, against the small set of predefined styles below or
+//! whatever a caller-supplied `lookup` returns.
+
+use std::collections::HashSet;
+
+/// A counter style's symbol-selection algorithm.
+///
+/// <https://www.w3.org/TR/css-counter-styles-3/#counter-style-system>
+#[derive(Clone, Debug)]
+pub enum CounterSystem {
+    /// Cycles through `symbols` forever: `symbols[(n - 1) % symbols.len()]`.
+    Cyclic,
+    /// Maps a small, fixed range of values starting at `first_value` onto `symbols`, one each;
+    /// falls back outside that range.
+    Fixed { first_value: i64 },
+    /// Like `Cyclic`, but each symbol is repeated `ceil(n / symbols.len())` times.
+    Symbolic,
+    /// Bijective base-`symbols.len()` numbering (no zero digit).
+    Alphabetic,
+    /// Ordinary positional base-`symbols.len()` numbering, with `symbols[0]` as the zero digit.
+    Numeric,
+    /// Sign-value numbering: descending `(weight, symbol)` pairs, greedily subtracted.
+    Additive,
+}
+
+/// A counter style: either a parsed `@counter-style` rule, or one of the handful of predefined
+/// styles `counter()`/list markers can fall back to. Mirrors the descriptors of
+/// <https://www.w3.org/TR/css-counter-styles-3/#counter-style-rule>.
+#[derive(Clone, Debug)]
+pub struct CounterStyle {
+    pub system: CounterSystem,
+    /// Cyclic/Fixed/Symbolic/Alphabetic/Numeric symbols, or `Additive`'s `(weight, symbol)`
+    /// pairs' symbols, sorted by descending weight alongside `additive_weights`.
+    pub symbols: Vec<String>,
+    /// Only meaningful when `system` is [`CounterSystem::Additive`]; weights matching `symbols`
+    /// index-for-index, already sorted descending.
+    pub additive_weights: Vec<i64>,
+    pub prefix: String,
+    pub suffix: String,
+    /// `(before, after)` wrapped around a negative value's formatted representation.
+    pub negative: (String, String),
+    /// Minimum rendered length and the symbol used to pad up to it.
+    pub pad: Option<(u32, String)>,
+    /// Values outside this (inclusive) range fall back. `None` means unbounded.
+    pub range: Option<(i64, i64)>,
+    /// Name of the counter style to fall back to when `value` is out of range or this style's
+    /// system can't represent it; resolved through `lookup` in [`format_counter`].
+    pub fallback: Option<String>,
+}
+
+impl CounterStyle {
+    fn simple(system: CounterSystem, symbols: &[&str]) -> Self {
+        CounterStyle {
+            system,
+            symbols: symbols.iter().map(|symbol| (*symbol).to_owned()).collect(),
+            additive_weights: Vec::new(),
+            prefix: String::new(),
+            suffix: ". ".to_owned(),
+            negative: ("-".to_owned(), String::new()),
+            pad: None,
+            range: None,
+            fallback: Some("decimal".to_owned()),
+        }
+    }
+
+    pub fn decimal() -> Self {
+        CounterStyle {
+            fallback: None,
+            ..Self::simple(
+                CounterSystem::Numeric,
+                &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"],
+            )
+        }
+    }
+
+    pub fn decimal_leading_zero() -> Self {
+        CounterStyle {
+            pad: Some((2, "0".to_owned())),
+            ..Self::decimal()
+        }
+    }
+
+    pub fn lower_alpha() -> Self {
+        Self::simple(
+            CounterSystem::Alphabetic,
+            &[
+                "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p",
+                "q", "r", "s", "t", "u", "v", "w", "x", "y", "z",
+            ],
+        )
+    }
+
+    pub fn upper_alpha() -> Self {
+        Self::simple(
+            CounterSystem::Alphabetic,
+            &[
+                "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P",
+                "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
+            ],
+        )
+    }
+
+    pub fn lower_roman() -> Self {
+        Self::additive(&[
+            (1000, "m"),
+            (900, "cm"),
+            (500, "d"),
+            (400, "cd"),
+            (100, "c"),
+            (90, "xc"),
+            (50, "l"),
+            (40, "xl"),
+            (10, "x"),
+            (9, "ix"),
+            (5, "v"),
+            (4, "iv"),
+            (1, "i"),
+        ])
+    }
+
+    pub fn upper_roman() -> Self {
+        Self::additive(&[
+            (1000, "M"),
+            (900, "CM"),
+            (500, "D"),
+            (400, "CD"),
+            (100, "C"),
+            (90, "XC"),
+            (50, "L"),
+            (40, "XL"),
+            (10, "X"),
+            (9, "IX"),
+            (5, "V"),
+            (4, "IV"),
+            (1, "I"),
+        ])
+    }
+
+    pub fn disc() -> Self {
+        Self::simple(CounterSystem::Cyclic, &["\u{2022}"])
+    }
+
+    pub fn circle() -> Self {
+        Self::simple(CounterSystem::Cyclic, &["\u{25E6}"])
+    }
+
+    pub fn square() -> Self {
+        Self::simple(CounterSystem::Cyclic, &["\u{25AA}"])
+    }
+
+    fn additive(pairs: &[(i64, &str)]) -> Self {
+        CounterStyle {
+            additive_weights: pairs.iter().map(|(weight, _)| *weight).collect(),
+            range: Some((1, i64::MAX)),
+            ..Self::simple(
+                CounterSystem::Additive,
+                &pairs.iter().map(|(_, symbol)| *symbol).collect::<Vec<_>>(),
+            )
+        }
+    }
+}
+
+/// Looks up one of the small set of predefined counter styles this module ships by name, for use
+/// as the `lookup` callback of [`format_counter`] when no `@counter-style` cascade exists to
+/// consult instead.
+///
+/// <https://www.w3.org/TR/css-counter-styles-3/#predefined-counters>
+pub fn predefined_counter_style(name: &str) -> Option<CounterStyle> {
+    Some(match name {
+        "decimal" => CounterStyle::decimal(),
+        "decimal-leading-zero" => CounterStyle::decimal_leading_zero(),
+        "lower-alpha" | "lower-latin" => CounterStyle::lower_alpha(),
+        "upper-alpha" | "upper-latin" => CounterStyle::upper_alpha(),
+        "lower-roman" => CounterStyle::lower_roman(),
+        "upper-roman" => CounterStyle::upper_roman(),
+        "disc" => CounterStyle::disc(),
+        "circle" => CounterStyle::circle(),
+        "square" => CounterStyle::square(),
+        _ => return None,
+    })
+}
+
+/// Generates the symbols-only representation of `n` (a non-negative counter magnitude) against
+/// `style`, with no prefix/suffix/pad/sign applied. Returns `None` when `style`'s system can't
+/// represent `n` at all (e.g. `n` falls outside a `Fixed` style's range, or `Additive` can't
+/// reach zero exactly), signaling that the caller should fall back.
+///
+/// <https://www.w3.org/TR/css-counter-styles-3/#simple-counter-algorithms>
+fn format_symbols(n: i64, style: &CounterStyle) -> Option<String> {
+    if style.symbols.is_empty() {
+        return None;
+    }
+
+    let len = style.symbols.len() as i64;
+
+    match style.system {
+        CounterSystem::Cyclic => Some(style.symbols[(n - 1).rem_euclid(len) as usize].clone()),
+        CounterSystem::Fixed { first_value } => {
+            let index = n - first_value;
+            if index < 0 || index >= len {
+                None
+            } else {
+                Some(style.symbols[index as usize].clone())
+            }
+        },
+        CounterSystem::Symbolic => {
+            let symbol = &style.symbols[((n - 1).rem_euclid(len)) as usize];
+            let repetitions = n.div_ceil(len).max(1) as usize;
+            Some(symbol.repeat(repetitions))
+        },
+        CounterSystem::Alphabetic => {
+            if n <= 0 {
+                return None;
+            }
+            let mut n = n;
+            let mut digits = Vec::new();
+            while n > 0 {
+                n -= 1;
+                digits.push(style.symbols[(n % len) as usize].clone());
+                n /= len;
+            }
+            digits.reverse();
+            Some(digits.concat())
+        },
+        CounterSystem::Numeric => {
+            if len < 2 {
+                return None;
+            }
+            if n == 0 {
+                return Some(style.symbols[0].clone());
+            }
+            let mut n = n;
+            let mut digits = Vec::new();
+            while n > 0 {
+                digits.push(style.symbols[(n % len) as usize].clone());
+                n /= len;
+            }
+            digits.reverse();
+            Some(digits.concat())
+        },
+        CounterSystem::Additive => {
+            if n == 0 {
+                // No additive-zero symbol is modeled here; treat zero as unrepresentable so the
+                // caller falls back, matching the predefined Roman-numeral styles, which have none.
+                return None;
+            }
+            let mut remaining = n;
+            let mut rendered = String::new();
+            for (weight, symbol) in style.additive_weights.iter().zip(style.symbols.iter()) {
+                if *weight <= 0 || remaining == 0 {
+                    continue;
+                }
+                let count = remaining / weight;
+                if count > 0 {
+                    for _ in 0..count {
+                        rendered.push_str(symbol);
+                    }
+                    remaining -= count * weight;
+                }
+            }
+            if remaining != 0 { None } else { Some(rendered) }
+        },
+    }
+}
+
+/// Pads `body` up to `pad`'s minimum length with copies of its pad symbol, per
+/// <https://www.w3.org/TR/css-counter-styles-3/#counter-style-pad>. Padding is measured in
+/// symbols (`char`s), not bytes, matching the spec's "count of characters" wording.
+fn apply_pad(body: String, pad: &Option<(u32, String)>) -> String {
+    let Some((min_length, pad_symbol)) = pad else {
+        return body;
+    };
+
+    let deficit = (*min_length as usize).saturating_sub(body.chars().count());
+    if deficit == 0 {
+        return body;
+    }
+
+    let mut padded = pad_symbol.repeat(deficit);
+    padded.push_str(&body);
+    padded
+}
+
+/// Formats `value` against `style`, including its prefix, suffix, sign, and padding, falling
+/// back through `style.fallback` (resolved via `lookup`, terminating at `decimal` if a fallback
+/// name doesn't resolve or a cycle is detected) when `value` is out of range or unrepresentable.
+///
+/// <https://www.w3.org/TR/css-counter-styles-3/#generate-a-counter>
+pub fn format_counter(
+    value: i64,
+    style: &CounterStyle,
+    lookup: &dyn Fn(&str) -> Option<CounterStyle>,
+) -> String {
+    format_counter_inner(value, style, lookup, &mut HashSet::new())
+}
+
+fn format_counter_inner(
+    value: i64,
+    style: &CounterStyle,
+    lookup: &dyn Fn(&str) -> Option<CounterStyle>,
+    seen_fallbacks: &mut HashSet<String>,
+) -> String {
+    let in_range = style
+        .range
+        .is_none_or(|(low, high)| value >= low && value <= high);
+
+    let body = if in_range {
+        format_symbols(value.unsigned_abs() as i64, style)
+    } else {
+        None
+    };
+
+    match body {
+        Some(body) => {
+            let body = apply_pad(body, &style.pad);
+            let (negative_before, negative_after) = &style.negative;
+            let signed = if value < 0 {
+                format!("{negative_before}{body}{negative_after}")
+            } else {
+                body
+            };
+            format!("{}{signed}{}", style.prefix, style.suffix)
+        },
+        None => {
+            // Cycle guard: a fallback chain that loops back on a style already visited resolves
+            // to `decimal` rather than recursing forever.
+            let fallback_name = style.fallback.as_deref().unwrap_or("decimal");
+            if !seen_fallbacks.insert(fallback_name.to_owned()) {
+                return format_counter_inner(value, &CounterStyle::decimal(), lookup, seen_fallbacks);
+            }
+
+            let fallback_style = lookup(fallback_name).unwrap_or_else(CounterStyle::decimal);
+            format_counter_inner(value, &fallback_style, lookup, seen_fallbacks)
+        },
+    }
+}
@@ -5,6 +5,7 @@
 //! The `Fragment` type, which represents the leaves of the layout tree.
 
 use std::borrow::ToOwned;
+use std::cell::RefCell;
 use std::cmp::{Ordering, max, min};
 use std::collections::LinkedList;
 use std::sync::{Arc, Mutex};
@@ -14,7 +15,7 @@ use app_units::Au;
 use base::id::{BrowsingContextId, PipelineId};
 use base::text::is_bidi_control;
 use bitflags::bitflags;
-use canvas_traits::canvas::{CanvasId, CanvasMsg};
+use canvas_traits::canvas::{CanvasId, CanvasMsg, FromLayoutMsg};
 use euclid::default::{Point2D, Rect, Size2D, Vector2D};
 use fonts::ByteIndex;
 use html5ever::{local_name, namespace_url, ns};
@@ -35,6 +36,8 @@ use style::computed_values::border_collapse::T as BorderCollapse;
 use style::computed_values::box_sizing::T as BoxSizing;
 use style::computed_values::color::T as Color;
 use style::computed_values::display::T as Display;
+use style::computed_values::hyphens::T as Hyphens;
+use style::computed_values::line_break::T as LineBreak;
 use style::computed_values::mix_blend_mode::T as MixBlendMode;
 use style::computed_values::overflow_wrap::T as OverflowWrap;
 use style::computed_values::overflow_x::T as StyleOverflow;
@@ -52,6 +55,7 @@ use style::str::char_is_whitespace;
 use style::values::computed::counters::ContentItem;
 use style::values::computed::{Length, VerticalAlign};
 use style::values::generics::box_::{Perspective, VerticalAlignKeyword};
+use style::values::generics::position::PreferredRatio;
 use style::values::generics::transform;
 use webrender_api::units::LayoutTransform;
 use webrender_api::{self, ImageKey};
@@ -60,8 +64,9 @@ use crate::context::LayoutContext;
 use crate::display_list::items::{BLUR_INFLATION_FACTOR, ClipScrollNodeIndex, OpaqueNode};
 use crate::display_list::{StackingContextId, ToLayout};
 use crate::floats::ClearType;
-use crate::flow::{GetBaseFlow, ImmutableFlowUtils};
+use crate::flow::{BaseFlow, GetBaseFlow, ImmutableFlowUtils};
 use crate::flow_ref::FlowRef;
+use crate::hyphenation::{Dictionary, DictionaryCache};
 use crate::inline::{
     InlineFragmentContext, InlineFragmentNodeFlags, InlineFragmentNodeInfo, InlineMetrics,
     LineMetrics,
@@ -71,6 +76,7 @@ use crate::model::{
 };
 use crate::text::TextRunScanner;
 use crate::text_run::{TextRun, TextRunSlice};
+use crate::unicode_linebreak::{self, Strictness};
 use crate::wrapper::ThreadSafeLayoutNodeHelpers;
 use crate::{ServoArc, text};
 
@@ -279,11 +285,73 @@ impl fmt::Debug for SpecificFragmentInfo {
     }
 }
 
+/// The start/end margins of a block-level box, kept as the greatest positive value and the
+/// most-negative value among every margin that has collapsed together so far, rather than a
+/// single pre-summed `Au` - the structure CSS 2.1 § 8.3.1 margin collapsing needs to fold in
+/// further adjoining margins without losing information. Conceptually belongs beside
+/// `MaybeAuto`/`IntrinsicISizes` in `model.rs`, but that file doesn't exist in this snapshot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdjoiningMargins {
+    pub most_positive: Au,
+    pub most_negative: Au,
+}
+
+impl AdjoiningMargins {
+    pub fn new() -> AdjoiningMargins {
+        AdjoiningMargins {
+            most_positive: Au(0),
+            most_negative: Au(0),
+        }
+    }
+
+    pub fn from_margin(margin: Au) -> AdjoiningMargins {
+        if margin >= Au(0) {
+            AdjoiningMargins {
+                most_positive: margin,
+                most_negative: Au(0),
+            }
+        } else {
+            AdjoiningMargins {
+                most_positive: Au(0),
+                most_negative: margin,
+            }
+        }
+    }
+
+    /// Folds `other` into `self`, keeping the greatest positive and most-negative values seen
+    /// across both - the result of two adjoining margins collapsing into one.
+    pub fn union(&mut self, other: AdjoiningMargins) {
+        self.most_positive = max(self.most_positive, other.most_positive);
+        self.most_negative = min(self.most_negative, other.most_negative);
+    }
+
+    /// The single margin value these adjoining margins collapse to.
+    pub fn collapse(&self) -> Au {
+        self.most_positive + self.most_negative
+    }
+}
+
+impl Default for AdjoiningMargins {
+    fn default() -> Self {
+        AdjoiningMargins::new()
+    }
+}
+
 /// Information for generated content.
 #[derive(Clone)]
 pub enum GeneratedContentInfo {
     ListItem,
     ContentItem(ContentItem),
+    /// `content: open-quote`. Resolved against the element's computed `quotes` list and the
+    /// current quote depth by `quote::resolve_quote`, which also advances that depth.
+    OpenQuote,
+    /// `content: close-quote`. Resolved the same way as `OpenQuote`, but decrements the depth
+    /// (clamped at zero) instead of incrementing it.
+    CloseQuote,
+    /// `content: no-open-quote`. Like `OpenQuote`, but emits no text - only the depth changes.
+    NoOpenQuote,
+    /// `content: no-close-quote`. Like `CloseQuote`, but emits no text - only the depth changes.
+    NoCloseQuote,
     /// Placeholder for elements with generated content that did not generate any fragments.
     Empty,
 }
@@ -370,6 +438,67 @@ impl CanvasFragmentInfo {
             canvas_id: data.canvas_id,
         }
     }
+
+    /// Resolves this canvas's backing store into an owned pixel snapshot, synchronously querying
+    /// the canvas paint thread when `source` is [`CanvasFragmentSource::Image`] rather than
+    /// relying on a live WebRender texture. For print/paginated media and for compositing a
+    /// canvas into another replaced element, where there's no live texture key to hand WebRender
+    /// directly.
+    ///
+    /// Returns `None` for `WebGL`/`WebGPU`, which already have a WebRender `ImageKey` and so take
+    /// the ordinary texture-key display-list path instead of a pixel readback through here.
+    /// `Empty` (transparent black, never yet painted) resolves to a cleared buffer of
+    /// `dom_width` × `dom_height` rather than `None`, since it's still a valid - just blank -
+    /// canvas the display-list builder can emit a plain image item for.
+    pub fn pixel_snapshot(&self) -> Option<CanvasPixelSnapshot> {
+        let width = self.dom_width.to_px().max(0) as u32;
+        let height = self.dom_height.to_px().max(0) as u32;
+        let cleared = || vec![0; width as usize * height as usize * 4];
+
+        let bytes = match &self.source {
+            CanvasFragmentSource::Image(renderer) => {
+                let (result_sender, result_receiver) =
+                    ipc_channel::ipc::channel().expect("Failed to create IPC channel");
+                let request = CanvasMsg::FromLayout(
+                    FromLayoutMsg::SendPixelContents(result_sender),
+                    self.canvas_id,
+                );
+                renderer
+                    .lock()
+                    .unwrap()
+                    .send(request)
+                    .expect("Failed to send pixel contents request to the canvas paint thread");
+                result_receiver.recv().unwrap_or_else(|_| cleared())
+            },
+            CanvasFragmentSource::Empty => cleared(),
+            CanvasFragmentSource::WebGL(_) | CanvasFragmentSource::WebGPU(_) => return None,
+        };
+
+        Some(CanvasPixelSnapshot {
+            width,
+            height,
+            bytes,
+        })
+    }
+}
+
+/// An owned RGBA8 pixel snapshot of a canvas's backing store, shaped like [`ImageFragmentInfo`]
+/// (dimensions plus raw bytes) so the display-list builder can emit the same kind of plain image
+/// item it would for any other raster image, in place of a WebRender texture key.
+///
+/// Resolved via [`CanvasFragmentInfo::pixel_snapshot`]. The request this sends to the canvas
+/// paint thread (`CanvasMsg::FromLayout(FromLayoutMsg::SendPixelContents(..), CanvasId)`) mirrors
+/// the shape a real new `CanvasMsg` variant would take, but `canvas_traits` - the crate that
+/// actually owns `CanvasMsg` - isn't part of this snapshot, so that variant can't be added here;
+/// this models the request/response contract against the existing `CanvasFragmentSource::Image`
+/// channel instead.
+#[derive(Clone)]
+pub struct CanvasPixelSnapshot {
+    pub width: u32,
+    pub height: u32,
+    /// Straight (non-premultiplied) RGBA8 pixel data, `width * height * 4` bytes, row-major,
+    /// origin top-left.
+    pub bytes: Vec<u8>,
 }
 
 #[derive(Clone)]
@@ -654,6 +783,65 @@ impl TableColumnFragmentInfo {
 pub struct TruncatedFragmentInfo {
     pub text_info: Option<ScannedTextFragmentInfo>,
     pub full: Fragment,
+    /// The marker fragment painted at the inline-start edge, present only if that edge was
+    /// actually clipped (the two-value `text-overflow: <start> <end>` syntax requests a marker
+    /// for an edge that isn't overflowing its line, no marker is painted there).
+    pub start_marker: Option<Fragment>,
+    /// The marker fragment painted at the inline-end edge, present only if that edge was
+    /// actually clipped.
+    pub end_marker: Option<Fragment>,
+}
+
+/// The marker string requested at each edge by `text-overflow`, e.g. the default keyword
+/// `ellipsis` (`"\u{2026}"`) or a custom `<string>` replacement,
+/// <https://drafts.csswg.org/css-overflow-3/#text-overflow>. `None` on a given edge means that
+/// edge is never truncated (the initial value, `clip`).
+#[derive(Clone, Debug, Default)]
+pub struct TextOverflowMarkers {
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+/// The physical edge of a line box a `text-overflow` marker paints at, once the logical
+/// `start`/`end` request has been resolved against writing mode and bidi direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhysicalEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl TruncatedFragmentInfo {
+    /// Resolves which physical edge each present marker paints at, given the writing mode of the
+    /// fragment that was truncated. `text-overflow` always clips along the line's own
+    /// progression axis, so in a horizontal writing mode `start` maps to the physical left under
+    /// `ltr` and the physical right under `rtl`; in a vertical writing mode it maps to the
+    /// physical top under `vertical-lr` and the physical bottom under `vertical-rl` instead.
+    /// `end` is always the opposite edge. The (nonexistent in this snapshot) display-list builder
+    /// would use this to decide which physical side of the border box to paint each marker
+    /// against.
+    pub fn marker_physical_edges(
+        &self,
+        writing_mode: WritingMode,
+    ) -> (Option<(&Fragment, PhysicalEdge)>, Option<(&Fragment, PhysicalEdge)>) {
+        let (start_edge, end_edge) = if writing_mode.is_vertical() {
+            if writing_mode.is_vertical_lr() {
+                (PhysicalEdge::Top, PhysicalEdge::Bottom)
+            } else {
+                (PhysicalEdge::Bottom, PhysicalEdge::Top)
+            }
+        } else if writing_mode.is_bidi_ltr() {
+            (PhysicalEdge::Left, PhysicalEdge::Right)
+        } else {
+            (PhysicalEdge::Right, PhysicalEdge::Left)
+        };
+
+        (
+            self.start_marker.as_ref().map(|fragment| (fragment, start_edge)),
+            self.end_marker.as_ref().map(|fragment| (fragment, end_edge)),
+        )
+    }
 }
 
 impl Fragment {
@@ -1061,6 +1249,45 @@ impl Fragment {
         }
     }
 
+    /// The `(inline, block)` ratio pair this box's proportions should be clamped to, honoring the
+    /// `aspect-ratio` property, per <https://drafts.csswg.org/css-sizing-4/#aspect-ratio>. Returns
+    /// `None` when nothing constrains the box's proportions at all (bare `auto` with no intrinsic
+    /// ratio - the pre-`aspect-ratio` behavior).
+    ///
+    /// The returned pair is expressed in the same units as `intrinsic_inline_size`/
+    /// `intrinsic_block_size` so callers can feed it through the same integer
+    /// cross-multiplication they'd use for an intrinsic ratio; a declared `<ratio>` is scaled up
+    /// by a large constant to preserve precision through that integer math, the same way an
+    /// image's natural pixel dimensions already do.
+    fn preferred_ratio(&self, intrinsic_inline_size: Au, intrinsic_block_size: Au) -> Option<(Au, Au)> {
+        let aspect_ratio = &self.style.get_position().aspect_ratio;
+
+        const RATIO_SCALE: f32 = 1_000_000.0;
+        let declared_ratio = match aspect_ratio.ratio {
+            PreferredRatio::None => None,
+            PreferredRatio::Ratio(ref ratio) => Some((
+                Au::new((ratio.0.0 * RATIO_SCALE) as i32),
+                Au::new((ratio.1.0 * RATIO_SCALE) as i32),
+            )),
+        };
+
+        if !aspect_ratio.auto {
+            // A bare `<ratio>` (the `auto` keyword absent) always governs, even over an intrinsic
+            // ratio this box might otherwise have.
+            return declared_ratio;
+        }
+
+        if self.has_intrinsic_ratio() {
+            // `auto` alone, or `auto <ratio>` - either way, a real intrinsic ratio wins.
+            Some((intrinsic_inline_size, intrinsic_block_size))
+        } else {
+            // `auto <ratio>` with no intrinsic ratio on this box falls back to the declared one;
+            // bare `auto` with no declared ratio either falls through to `None`, unchanged from
+            // the pre-`aspect-ratio` behavior.
+            declared_ratio
+        }
+    }
+
     /// Whether this replace element has intrinsic aspect ratio.
     pub fn has_intrinsic_ratio(&self) -> bool {
         match self.specific {
@@ -1082,6 +1309,11 @@ impl Fragment {
     /// When a parameter is `None` it means the specified size in certain direction
     /// is unconstrained. The inline containing size can also be `None` since this
     /// method is also used for calculating intrinsic inline size contribution.
+    ///
+    /// Also honors the `aspect-ratio` property (see [`Self::preferred_ratio`]) for replaced
+    /// elements. Applying it to non-replaced boxes as well - against the content box, adjusted by
+    /// `box_sizing_boundary` for `box-sizing: border-box` - additionally requires the block-flow
+    /// inline-size assignment pass in `block.rs`, which doesn't exist in this snapshot.
     pub fn calculate_replaced_sizes(
         &self,
         containing_inline_size: Option<Au>,
@@ -1103,6 +1335,10 @@ impl Fragment {
         let inline_constraint = self.size_constraint(containing_inline_size, Direction::Inline);
         let block_constraint = self.size_constraint(containing_block_size, Direction::Block);
 
+        // The declared `aspect-ratio` (falling back to this box's intrinsic ratio, if any) seeds
+        // the same proportional cross-calculation that used to only fire for an intrinsic ratio.
+        let ratio = self.preferred_ratio(intrinsic_inline_size, intrinsic_block_size);
+
         // https://drafts.csswg.org/css-images-3/#default-sizing
         match (inline_size, block_size) {
             // If the specified size is a definite width and height, the concrete
@@ -1125,13 +1361,13 @@ impl Fragment {
             // dimensions. Otherwise it is taken from the default object size.
             (MaybeAuto::Specified(inline_size), MaybeAuto::Auto) => {
                 let inline_size = inline_constraint.clamp(inline_size);
-                let block_size = if self.has_intrinsic_ratio() {
+                let block_size = if let Some((ratio_inline, ratio_block)) = ratio {
                     // Note: We can not precompute the ratio and store it as a float, because
                     // doing so may result one pixel difference in calculation for certain
                     // images, thus make some tests fail.
                     Au::new(
-                        (inline_size.0 as i64 * intrinsic_block_size.0 as i64 /
-                            intrinsic_inline_size.0 as i64) as i32,
+                        (inline_size.0 as i64 * ratio_block.0 as i64 / ratio_inline.0 as i64)
+                            as i32,
                     )
                 } else {
                     intrinsic_block_size
@@ -1140,10 +1376,10 @@ impl Fragment {
             },
             (MaybeAuto::Auto, MaybeAuto::Specified(block_size)) => {
                 let block_size = block_constraint.clamp(block_size);
-                let inline_size = if self.has_intrinsic_ratio() {
+                let inline_size = if let Some((ratio_inline, ratio_block)) = ratio {
                     Au::new(
-                        (block_size.0 as i64 * intrinsic_inline_size.0 as i64 /
-                            intrinsic_block_size.0 as i64) as i32,
+                        (block_size.0 as i64 * ratio_inline.0 as i64 / ratio_block.0 as i64)
+                            as i32,
                     )
                 } else {
                     intrinsic_inline_size
@@ -1152,21 +1388,21 @@ impl Fragment {
             },
             // https://drafts.csswg.org/css2/visudet.html#min-max-widths
             (MaybeAuto::Auto, MaybeAuto::Auto) => {
-                if self.has_intrinsic_ratio() {
+                if let Some((ratio_inline, ratio_block)) = ratio {
                     // This approach follows the spirit of cover and contain constraint.
                     // https://drafts.csswg.org/css-images-3/#cover-contain
 
-                    // First, create two rectangles that keep aspect ratio while may be clamped
+                    // First, create two rectangles that keep the ratio while may be clamped
                     // by the constraints;
                     let first_isize = inline_constraint.clamp(intrinsic_inline_size);
                     let first_bsize = Au::new(
-                        (first_isize.0 as i64 * intrinsic_block_size.0 as i64 /
-                            intrinsic_inline_size.0 as i64) as i32,
+                        (first_isize.0 as i64 * ratio_block.0 as i64 / ratio_inline.0 as i64)
+                            as i32,
                     );
                     let second_bsize = block_constraint.clamp(intrinsic_block_size);
                     let second_isize = Au::new(
-                        (second_bsize.0 as i64 * intrinsic_inline_size.0 as i64 /
-                            intrinsic_block_size.0 as i64) as i32,
+                        (second_bsize.0 as i64 * ratio_inline.0 as i64 / ratio_block.0 as i64)
+                            as i32,
                     );
                     let (inline_size, block_size) = match (
                         first_isize.cmp(&intrinsic_inline_size),
@@ -1393,6 +1629,44 @@ impl Fragment {
         }
     }
 
+    /// Returns this fragment's start/end margins as collapsible `AdjoiningMargins` pairs, for
+    /// block margin collapsing (CSS 2.1 § 8.3.1) to combine with adjoining margins elsewhere in
+    /// the fragment tree - rather than the plain, already-summed `Au` values
+    /// `compute_block_direction_margins` stores in `self.margin`, which discard the sign/
+    /// most-positive/most-negative structure collapsing needs.
+    ///
+    /// Table-ish fragments never participate in margin collapsing, so this just wraps whatever
+    /// `compute_block_direction_margins` already zeroed out for them.
+    pub fn adjoining_block_margins(&self) -> (AdjoiningMargins, AdjoiningMargins) {
+        (
+            AdjoiningMargins::from_margin(self.margin.block_start),
+            AdjoiningMargins::from_margin(self.margin.block_end),
+        )
+    }
+
+    /// Whether this fragment's start and end margins should collapse straight through each
+    /// other, per <https://www.w3.org/TR/CSS21/box.html#collapsing-margins>: no border, no
+    /// padding, an auto (or zero) block-size, and no in-flow content separating them.
+    pub fn is_block_margin_collapse_through(&self) -> bool {
+        if !matches!(
+            self.specific,
+            SpecificFragmentInfo::Generic | SpecificFragmentInfo::GeneratedContent(_)
+        ) {
+            return false;
+        }
+
+        let no_border_or_padding = self.border_padding.block_start == Au(0) &&
+            self.border_padding.block_end == Au(0);
+
+        let auto_or_zero_block_size =
+            match style_length(self.style().content_block_size(), None) {
+                MaybeAuto::Auto => true,
+                MaybeAuto::Specified(block_size) => block_size == Au(0),
+            };
+
+        no_border_or_padding && auto_or_zero_block_size && self.border_box.size.block == Au(0)
+    }
+
     /// Computes the border and padding in both inline and block directions from the containing
     /// block inline-size and the style. After this call, the `border_padding` field will be
     /// correct.
@@ -1585,6 +1859,36 @@ impl Fragment {
         }
     }
 
+    /// Returns the `IntrinsicISizes` that `child_base` - an inline-block or inline-absolute
+    /// child's `BaseFlow` - contributes to this fragment's own intrinsic inline-size.
+    ///
+    /// Ordinarily that's just the child's own intrinsic inline-size. But when the child
+    /// establishes an orthogonal writing mode (one of `self`/`child_base` is vertical and the
+    /// other horizontal), the child's *block* axis runs parallel to `self`'s *inline* axis, so
+    /// it's the child's intrinsic *block*-size that should contribute here instead.
+    ///
+    /// `BaseFlow` (declared in `flow.rs`) doesn't exist in this snapshot, so there's no
+    /// `intrinsic_block_size` companion measurement to actually read in the orthogonal branch
+    /// below - the comment there marks the call this function would make once `BaseFlow` grows
+    /// one (a best-effort max-content block-size computed against an unconstrained available
+    /// inline-size). Until then the orthogonal branch also returns the inline-size contribution.
+    fn intrinsic_inline_sizes_for_child_flow(&self, child_base: &BaseFlow) -> IntrinsicISizes {
+        if self.style.writing_mode.is_vertical() != child_base.writing_mode.is_vertical() {
+            // let block_size = child_base.intrinsic_block_size();
+            // return IntrinsicISizes {
+            //     minimum_inline_size: block_size.minimum_block_size,
+            //     preferred_inline_size: block_size.preferred_block_size,
+            // };
+            debug!(
+                "intrinsic_inline_sizes_for_child_flow: child flow is orthogonal to its parent, \
+                 but BaseFlow has no intrinsic block-size measurement to fall back on in this \
+                 tree; using its inline-size contribution as a best-effort approximation"
+            );
+        }
+
+        child_base.intrinsic_inline_sizes
+    }
+
     /// Computes the intrinsic inline-sizes of this fragment.
     pub fn compute_intrinsic_inline_sizes(&mut self) -> IntrinsicISizesContribution {
         let mut result = self.style_specified_intrinsic_inline_size();
@@ -1601,11 +1905,11 @@ impl Fragment {
             SpecificFragmentInfo::InlineAbsoluteHypothetical(_) => {},
             SpecificFragmentInfo::InlineBlock(ref info) => {
                 let block_flow = info.flow_ref.as_block();
-                result.union_block(&block_flow.base.intrinsic_inline_sizes)
+                result.union_block(&self.intrinsic_inline_sizes_for_child_flow(&block_flow.base))
             },
             SpecificFragmentInfo::InlineAbsolute(ref info) => {
                 let block_flow = info.flow_ref.as_block();
-                result.union_block(&block_flow.base.intrinsic_inline_sizes)
+                result.union_block(&self.intrinsic_inline_sizes_for_child_flow(&block_flow.base))
             },
             SpecificFragmentInfo::Image(_) |
             SpecificFragmentInfo::Media(_) |
@@ -1665,16 +1969,40 @@ impl Fragment {
             let range = &text_fragment_info.range;
 
             // See http://dev.w3.org/csswg/css-sizing/#max-content-inline-size.
-            // TODO: Account for soft wrap opportunities.
             let max_line_inline_size = text_fragment_info
                 .run
                 .metrics_for_range(range)
                 .advance_width;
 
-            let min_line_inline_size = if self_.text_wrap_mode() == TextWrapMode::Wrap {
-                text_fragment_info.run.min_width_for_range(range)
-            } else {
+            // See http://dev.w3.org/csswg/css-sizing/#min-content-inline-size. `overflow-wrap:
+            // break-word` deliberately isn't consulted here: it only kicks in for used sizing once
+            // normal word-based wrapping has already failed to fit, and must not shrink the
+            // min-content size itself. `anywhere` isn't consulted here either; its
+            // single-character minimum is reported by `minimum_splittable_inline_size` instead.
+            let min_line_inline_size = if self_.text_wrap_mode() != TextWrapMode::Wrap {
                 max_line_inline_size
+            } else {
+                match self_.style().get_inherited_text().word_break {
+                    // `word-break: break-all` permits a break between any two typographic
+                    // characters, so the min-content contribution shrinks to the widest single
+                    // character's advance rather than the widest unbreakable word.
+                    WordBreak::BreakAll => text_fragment_info
+                        .run
+                        .character_slices_in_range(range)
+                        .map(|slice| {
+                            text_fragment_info
+                                .run
+                                .metrics_for_slice(slice.glyphs, &slice.range)
+                                .advance_width
+                        })
+                        .max()
+                        .unwrap_or(Au(0)),
+                    // `word-break: keep-all` suppresses the inter-ideograph soft wrap
+                    // opportunities `min_width_for_range` would otherwise use, so CJK runs
+                    // contribute their full width rather than breaking per-character.
+                    WordBreak::KeepAll => max_line_inline_size,
+                    WordBreak::Normal => text_fragment_info.run.min_width_for_range(range),
+                }
             };
 
             result.union_block(&IntrinsicISizes {
@@ -1723,16 +2051,37 @@ impl Fragment {
     pub fn minimum_splittable_inline_size(&self) -> Au {
         match self.specific {
             SpecificFragmentInfo::TruncatedFragment(ref t) if t.text_info.is_some() => {
-                let text = t.text_info.as_ref().unwrap();
-                text.run.minimum_splittable_inline_size(&text.range)
+                self.minimum_splittable_inline_size_for_text(t.text_info.as_ref().unwrap())
             },
             SpecificFragmentInfo::ScannedText(ref text) => {
-                text.run.minimum_splittable_inline_size(&text.range)
+                self.minimum_splittable_inline_size_for_text(text)
             },
             _ => Au(0),
         }
     }
 
+    /// The narrowest width `minimum_splittable_inline_size` can report for `text`. Ordinarily
+    /// this is the width of its first natural word, but under `overflow-wrap: anywhere` it's the
+    /// width of its widest single character instead: unlike `break-word`, `anywhere` is also a
+    /// legal splitting point for intrinsic-size purposes, so an element using it must be allowed
+    /// to shrink down to a single character's width rather than a whole word's.
+    fn minimum_splittable_inline_size_for_text(&self, text: &ScannedTextFragmentInfo) -> Au {
+        if self.style().get_inherited_text().overflow_wrap == OverflowWrap::Anywhere {
+            return text
+                .run
+                .character_slices_in_range(&text.range)
+                .map(|slice| {
+                    text.run
+                        .metrics_for_slice(slice.glyphs, &slice.range)
+                        .advance_width
+                })
+                .max()
+                .unwrap_or(Au(0));
+        }
+
+        text.run.minimum_splittable_inline_size(&text.range)
+    }
+
     /// Returns the dimensions of the content box.
     ///
     /// This is marked `#[inline]` because it is frequently called when only one or two of the
@@ -1761,10 +2110,36 @@ impl Fragment {
         let mut flags = SplitOptions::empty();
         if starts_line {
             flags.insert(SplitOptions::STARTS_LINE);
-            if self.style().get_inherited_text().overflow_wrap == OverflowWrap::BreakWord {
+            // `anywhere` retries at character boundaries exactly like `break-word` during line
+            // breaking; the two only differ for intrinsic-size purposes, in
+            // `minimum_splittable_inline_size_for_text` below.
+            if matches!(
+                self.style().get_inherited_text().overflow_wrap,
+                OverflowWrap::BreakWord | OverflowWrap::Anywhere
+            ) {
                 flags.insert(SplitOptions::RETRY_AT_CHARACTER_BOUNDARIES)
             }
         }
+        if self.style().get_inherited_text().hyphens == Hyphens::Auto {
+            flags.insert(SplitOptions::RETRY_AT_HYPHENATION_POINTS)
+        }
+
+        let line_break = self.style().get_inherited_text().line_break;
+
+        // `line-break: anywhere` takes precedence over `word-break`: every typographic character
+        // cluster boundary is a soft-wrap opportunity, the same as `word-break: break-all`, but
+        // (per spec) also usable for min-content measurement mid-word.
+        if line_break == LineBreak::Anywhere {
+            let character_breaking_strategy = text_fragment_info
+                .run
+                .character_slices_in_range(&text_fragment_info.range);
+            flags.remove(SplitOptions::RETRY_AT_CHARACTER_BOUNDARIES);
+            return self.calculate_split_position_using_breaking_strategy(
+                character_breaking_strategy,
+                max_inline_size,
+                flags,
+            );
+        }
 
         match self.style().get_inherited_text().word_break {
             WordBreak::Normal | WordBreak::KeepAll => {
@@ -1804,16 +2179,51 @@ impl Fragment {
             .on_glyph_run_boundary(text_fragment_info.range.begin())
     }
 
-    /// Truncates this fragment to the given `max_inline_size`, using a character-based breaking
-    /// strategy. The resulting fragment will have `SpecificFragmentInfo::TruncatedFragment`,
-    /// preserving the original fragment for use in incremental reflow.
+    /// Truncates this fragment to the given `max_inline_size` per `text-overflow`, using a
+    /// character-based breaking strategy at whichever edges `markers` requests a marker for
+    /// (`ellipsis`/a custom `<string>`, rather than the initial `clip`). The resulting fragment
+    /// will have `SpecificFragmentInfo::TruncatedFragment`, preserving the original fragment for
+    /// use in incremental reflow, plus a marker fragment at each edge that ended up clipped (a
+    /// two-value `text-overflow` may request a marker for one edge that never overflows its
+    /// line, in which case that edge keeps no marker).
     ///
     /// This function will panic if self is already truncated.
-    pub fn truncate_to_inline_size(self, max_inline_size: Au) -> Fragment {
+    pub fn truncate_to_inline_size(
+        self,
+        max_inline_size: Au,
+        markers: &TextOverflowMarkers,
+        layout_context: &LayoutContext,
+    ) -> Fragment {
         if let SpecificFragmentInfo::TruncatedFragment(_) = self.specific {
             panic!("Cannot truncate an already truncated fragment");
         }
-        let info = self.calculate_truncate_to_inline_size(max_inline_size);
+
+        // Shape each requested marker up front so its measured advance can be reserved from
+        // `max_inline_size` before the text is split, and so the same shaped fragment can be
+        // reused for painting rather than re-shaping the marker string later.
+        let start_marker = markers
+            .start
+            .as_ref()
+            .map(|marker| self.transform_into_ellipsis(layout_context, marker.clone()));
+        let end_marker = markers
+            .end
+            .as_ref()
+            .map(|marker| self.transform_into_ellipsis(layout_context, marker.clone()));
+        let reserved_inline_size =
+            start_marker.as_ref().map_or(Au(0), |fragment| fragment.border_box.size.inline) +
+                end_marker.as_ref().map_or(Au(0), |fragment| fragment.border_box.size.inline);
+        let available_inline_size = max(Au(0), max_inline_size - reserved_inline_size);
+
+        let original_range = match self.specific {
+            SpecificFragmentInfo::ScannedText(ref info) => Some(info.range.clone()),
+            _ => None,
+        };
+
+        let info = self.calculate_truncate_to_inline_size(
+            available_inline_size,
+            start_marker.is_some(),
+            end_marker.is_some(),
+        );
         let (size, text_info) = match info {
             Some(TruncationResult {
                 split: SplitInfo { inline_size, range },
@@ -1844,18 +2254,53 @@ impl Fragment {
             },
             None => (LogicalSize::zero(self.style.writing_mode), None),
         };
+
+        // Only keep a marker for an edge that was actually clipped; the two-value syntax may
+        // name a marker for an edge whose content fit without truncation.
+        let (start_marker, end_marker) = match (&text_info, original_range) {
+            (Some(text_info), Some(original_range)) => (
+                if text_info.range.begin() > original_range.begin() {
+                    start_marker
+                } else {
+                    None
+                },
+                if text_info.range.end() < original_range.end() {
+                    end_marker
+                } else {
+                    None
+                },
+            ),
+            _ => (start_marker, end_marker),
+        };
+
         let mut result = self.transform(size, SpecificFragmentInfo::Generic);
         result.specific =
             SpecificFragmentInfo::TruncatedFragment(Box::new(TruncatedFragmentInfo {
                 text_info,
                 full: self,
+                start_marker,
+                end_marker,
             }));
         result
     }
 
     /// Truncates this fragment to the given `max_inline_size`, using a character-based breaking
     /// strategy. If no characters could fit, returns `None`.
-    fn calculate_truncate_to_inline_size(&self, max_inline_size: Au) -> Option<TruncationResult> {
+    ///
+    /// When `truncate_end` is set, an inline-end suffix is dropped by the ordinary forward
+    /// splitter. When `truncate_start` is set, an inline-start prefix is then dropped too, by
+    /// walking character slices backward from whatever's left after the `truncate_end` pass (or
+    /// from the original range, if `truncate_end` wasn't set) to find the longest fitting
+    /// suffix. Running these as two independent passes rather than jointly searching both edges
+    /// at once is a simplification: the `truncate_end` pass keeps as much of the inline-start
+    /// content as `max_inline_size` allows, so when both flags are set the `truncate_start` pass
+    /// finds that prefix already fits and drops nothing - only the inline-end edge is truncated.
+    fn calculate_truncate_to_inline_size(
+        &self,
+        max_inline_size: Au,
+        truncate_start: bool,
+        truncate_end: bool,
+    ) -> Option<TruncationResult> {
         let text_fragment_info =
             if let SpecificFragmentInfo::ScannedText(ref text_fragment_info) = self.specific {
                 text_fragment_info
@@ -1863,20 +2308,101 @@ impl Fragment {
                 return None;
             };
 
-        let character_breaking_strategy = text_fragment_info
-            .run
-            .character_slices_in_range(&text_fragment_info.range);
+        let mut range = text_fragment_info.range.clone();
+
+        if truncate_end {
+            let character_breaking_strategy =
+                text_fragment_info.run.character_slices_in_range(&range);
+            let split_info = self.calculate_split_position_using_breaking_strategy(
+                character_breaking_strategy,
+                max_inline_size,
+                SplitOptions::empty(),
+            )?;
+            range = split_info.inline_start?.range;
+        }
+
+        if truncate_start {
+            // Walk character slices from the end of `range` backward, keeping as many as fit in
+            // `max_inline_size`, to find the longest suffix that still fits.
+            let mut slices: Vec<_> = text_fragment_info
+                .run
+                .character_slices_in_range(&range)
+                .collect();
+            slices.reverse();
+
+            let mut included_width = Au(0);
+            let mut suffix_start = range.end();
+            for slice in slices {
+                let advance = text_fragment_info
+                    .run
+                    .metrics_for_slice(slice.glyphs, &slice.range)
+                    .advance_width;
+                if included_width + advance > max_inline_size {
+                    break;
+                }
+                included_width += advance;
+                suffix_start = slice.range.begin();
+            }
+
+            if suffix_start >= range.end() {
+                return None;
+            }
+
+            let mut new_range = Range::new(suffix_start, ByteIndex(0));
+            new_range.extend_to(range.end());
+            range = new_range;
+        }
 
-        let split_info = self.calculate_split_position_using_breaking_strategy(
-            character_breaking_strategy,
-            max_inline_size,
-            SplitOptions::empty(),
-        )?;
+        if range.begin() == text_fragment_info.range.begin() &&
+            range.end() == text_fragment_info.range.end()
+        {
+            return None;
+        }
 
-        let split = split_info.inline_start?;
         Some(TruncationResult {
-            split,
-            text_run: split_info.text_run.clone(),
+            split: SplitInfo::new(range, text_fragment_info),
+            text_run: text_fragment_info.run.clone(),
+        })
+    }
+
+    /// Looks for the rightmost point within the overflowing `slice`'s word at which [Liang's
+    /// hyphenation algorithm](crate::hyphenation) permits a break and whose leading text still
+    /// fits within `remaining_inline_size`. Returns the `ByteIndex` that text should be split at
+    /// on success.
+    ///
+    /// This can only choose a byte offset to split at - actually inserting a shaped U+2010
+    /// hyphen glyph there would require font-shaping machinery (`text_run.rs`, `text.rs`) that
+    /// doesn't exist in this snapshot, so the resulting split has no hyphen glyph of its own. It
+    /// also has no access to the element's computed `lang`, so it always consults
+    /// [`hyphenation::EN_US_SAMPLE_PATTERNS`] rather than a language-appropriate pattern set.
+    fn find_hyphenation_split(
+        &self,
+        text_fragment_info: &ScannedTextFragmentInfo,
+        slice: &TextRunSlice,
+        remaining_inline_size: Au,
+    ) -> Option<ByteIndex> {
+        thread_local! {
+            static DICTIONARY_CACHE: RefCell<DictionaryCache> = RefCell::new(DictionaryCache::new());
+        }
+
+        let word_range = slice.text_run_range();
+        let word = &text_fragment_info.run.text
+            [word_range.begin().to_usize()..word_range.end().to_usize()];
+
+        DICTIONARY_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let dictionary = cache
+                .get_or_compile("en", || Dictionary::new(crate::hyphenation::EN_US_SAMPLE_PATTERNS));
+
+            dictionary.hyphenate(word).into_iter().rev().find_map(|offset| {
+                let candidate_end = ByteIndex(word_range.begin().to_usize() as isize + offset as isize);
+                let candidate = Range::new(word_range.begin(), ByteIndex(offset as isize));
+                if text_fragment_info.run.advance_for_range(&candidate) <= remaining_inline_size {
+                    Some(candidate_end)
+                } else {
+                    None
+                }
+            })
         })
     }
 
@@ -1901,6 +2427,13 @@ impl Fragment {
         let mut inline_end_range = None;
         let mut overflowing = false;
 
+        let strictness = match self.style().get_inherited_text().line_break {
+            LineBreak::Auto | LineBreak::Normal => Strictness::Normal,
+            LineBreak::Loose => Strictness::Loose,
+            LineBreak::Strict => Strictness::Strict,
+            LineBreak::Anywhere => Strictness::Anywhere,
+        };
+
         debug!(
             "calculate_split_position_using_breaking_strategy: splitting text fragment \
              (strlen={}, range={:?}, max_inline_size={:?})",
@@ -1923,8 +2456,28 @@ impl Fragment {
                 .metrics_for_slice(slice.glyphs, &slice.range);
             let advance = metrics.advance_width;
 
+            // The CJK/punctuation line-breaking rules (`line-break: strict|normal|loose`)
+            // forbid a break at this boundary regardless of how it measures, so there's nothing
+            // to do here but fold this slice into the current line too.
+            let boundary_prohibited = !inline_start_range.is_empty() &&
+                match (
+                    text_fragment_info.run.text[..inline_start_range.end().to_usize()]
+                        .chars()
+                        .next_back(),
+                    text_fragment_info.run.text[slice.range.begin().to_usize()..]
+                        .chars()
+                        .next(),
+                ) {
+                    (Some(before), Some(after)) => !unicode_linebreak::is_break_allowed(
+                        unicode_linebreak::classify(before),
+                        unicode_linebreak::classify(after),
+                        strictness,
+                    ),
+                    _ => false,
+                };
+
             // Have we found the split point?
-            if advance <= remaining_inline_size || slice.glyphs.is_whitespace() {
+            if advance <= remaining_inline_size || slice.glyphs.is_whitespace() || boundary_prohibited {
                 // Keep going; we haven't found the split point yet.
                 debug!("calculate_split_position_using_breaking_strategy: enlarging span");
                 remaining_inline_size -= advance;
@@ -1963,6 +2516,24 @@ impl Fragment {
             // If we failed to find a suitable split point, we're on the verge of overflowing the
             // line.
             if split_is_empty || overflowing {
+                // If we've been instructed to retry at hyphenation points (via `hyphens: auto`),
+                // see whether the word that doesn't fit has a legal hyphenation point that does.
+                if flags.contains(SplitOptions::RETRY_AT_HYPHENATION_POINTS) {
+                    if let Some(hyphenated_end) = self.find_hyphenation_split(
+                        text_fragment_info,
+                        &slice,
+                        remaining_inline_size,
+                    ) {
+                        inline_start_range.extend_to(hyphenated_end);
+                        overflowing = false;
+
+                        let mut inline_end = Range::new(hyphenated_end, ByteIndex(0));
+                        inline_end.extend_to(text_fragment_info.range.end());
+                        inline_end_range = Some(inline_end);
+                        break;
+                    }
+                }
+
                 // If we've been instructed to retry at character boundaries (probably via
                 // `overflow-wrap: break-word`), do so.
                 if flags.contains(SplitOptions::RETRY_AT_CHARACTER_BOUNDARIES) {
@@ -2270,9 +2841,8 @@ impl Fragment {
                     ascent,
                 }
             },
-            SpecificFragmentInfo::TruncatedFragment(ref t) if t.text_info.is_some() => {
-                let info = t.text_info.as_ref().unwrap();
-                inline_metrics_of_text(info, self, layout_context)
+            SpecificFragmentInfo::TruncatedFragment(ref t) => {
+                inline_metrics_of_truncated(t, self, layout_context)
             },
             SpecificFragmentInfo::ScannedText(ref info) => {
                 inline_metrics_of_text(info, self, layout_context)
@@ -2283,7 +2853,6 @@ impl Fragment {
             SpecificFragmentInfo::InlineAbsoluteHypothetical(ref info) => {
                 inline_metrics_of_block(&info.flow_ref, &self.style)
             },
-            SpecificFragmentInfo::TruncatedFragment(..) |
             SpecificFragmentInfo::InlineAbsolute(_) => InlineMetrics::new(Au(0), Au(0), Au(0)),
             SpecificFragmentInfo::Table |
             SpecificFragmentInfo::TableCell |
@@ -2317,6 +2886,39 @@ impl Fragment {
             InlineMetrics::from_font_metrics(&info.run.font_metrics, line_height)
         }
 
+        /// A truncated fragment's line-box contribution is the union of whatever remaining
+        /// visible text it still has (`text_info`, absent if the whole fragment was clipped away)
+        /// and whichever ellipsis/`<string>` markers `text-overflow` painted at its edges - a
+        /// fragment truncated down to nothing but a lone marker must still push the line box open
+        /// to that marker's ascent/descent, not collapse to zero height.
+        fn inline_metrics_of_truncated(
+            truncated: &TruncatedFragmentInfo,
+            self_: &Fragment,
+            layout_context: &LayoutContext,
+        ) -> InlineMetrics {
+            let mut metrics = truncated
+                .text_info
+                .as_ref()
+                .map(|info| inline_metrics_of_text(info, self_, layout_context));
+
+            for marker in [&truncated.start_marker, &truncated.end_marker]
+                .into_iter()
+                .flatten()
+            {
+                let marker_metrics = marker.content_inline_metrics(layout_context);
+                metrics = Some(match metrics {
+                    Some(metrics) => InlineMetrics::new(
+                        metrics.space_above_baseline.max(marker_metrics.space_above_baseline),
+                        metrics.space_below_baseline.max(marker_metrics.space_below_baseline),
+                        metrics.ascent.max(marker_metrics.ascent),
+                    ),
+                    None => marker_metrics,
+                });
+            }
+
+            metrics.unwrap_or_else(|| InlineMetrics::new(Au(0), Au(0), Au(0)))
+        }
+
         fn inline_metrics_of_block(flow: &FlowRef, style: &ComputedValues) -> InlineMetrics {
             // CSS 2.1 § 10.8: "The height of each inline-level box in the line box is calculated.
             // For replaced elements, inline-block elements, and inline-table elements, this is the
@@ -2480,6 +3082,11 @@ impl Fragment {
 
     /// Returns true if this fragment can merge with another immediately-following fragment or
     /// false otherwise.
+    ///
+    /// Only `UnscannedText`-with-`UnscannedText` ever merges; in particular a
+    /// `TruncatedFragment` never matches either side of that pair, so text on either side of a
+    /// `text-overflow` truncation point - or one of its markers - can never be merged back
+    /// together into a single fragment.
     pub fn can_merge_with_fragment(&self, other: &Fragment) -> bool {
         match (&self.specific, &other.specific) {
             (
@@ -2635,6 +3242,28 @@ impl Fragment {
         }
     }
 
+    /// Replaces `style` with `new_style` (via [`Self::repair_style`]), but only if
+    /// `dirty_pseudo` matches this fragment's own `pseudo`. An element and its
+    /// `::before`/`::after` generated content share a `node` address, so comparing `node` alone
+    /// would repair all three fragments whenever any one of them was dirtied; comparing the full
+    /// `(node, pseudo)` pair keeps sibling pseudo-fragments untouched.
+    ///
+    /// Returns whether a repair actually happened, so callers walking inline contexts (where a
+    /// fragment's `inline_context` may carry its own per-run pseudo via
+    /// `InlineFragmentNodeInfo`) know whether to recurse into repairing that context too.
+    pub fn repair_style_for_pseudo(
+        &mut self,
+        new_style: &ServoArc<ComputedValues>,
+        dirty_pseudo: &PseudoElementType,
+    ) -> bool {
+        if self.pseudo != *dirty_pseudo {
+            return false;
+        }
+
+        self.repair_style(new_style);
+        true
+    }
+
     pub fn repair_style(&mut self, new_style: &ServoArc<ComputedValues>) {
         self.style = (*new_style).clone()
     }
@@ -2720,6 +3349,12 @@ impl Fragment {
     }
 
     /// Returns true if this fragment establishes a new stacking context and false otherwise.
+    ///
+    /// This only ever looks at `self`'s own style, not any inline ancestor's - a stacking
+    /// context belongs to the positioned box itself, not to every descendant fragment inside it.
+    /// For the containing-block question instead (which descendant fragments should anchor their
+    /// absolutely positioned content to a positioned inline ancestor), see
+    /// [`has_positioned_inline_ancestor`](Self::has_positioned_inline_ancestor).
     pub fn establishes_stacking_context(&self) -> bool {
         // Text fragments shouldn't create stacking contexts.
         match self.specific {
@@ -2755,10 +3390,13 @@ impl Fragment {
         }
 
         // Statically positioned fragments don't establish stacking contexts if the previous
-        // conditions are not fulfilled. Furthermore, z-index doesn't apply to statically
-        // positioned fragments.
+        // conditions are not fulfilled... unless this is a flex or grid item, in which case
+        // `position: static` doesn't shield it from `z-index`: flex/grid items establish a
+        // stacking context whenever `z-index` isn't `auto`, regardless of `position`. See
+        // https://www.w3.org/TR/css-flexbox-1/#painting and
+        // https://www.w3.org/TR/css-grid-1/#z-order.
         if self.style().get_box().position == Position::Static {
-            return false;
+            return self.is_flex_or_grid_item() && !self.style().get_position().z_index.is_auto();
         }
 
         // For absolutely and relatively positioned fragments we only establish a stacking
@@ -2767,6 +3405,18 @@ impl Fragment {
         !self.style().get_position().z_index.is_auto()
     }
 
+    /// Returns true if this fragment is a child of a flex or grid container that participates in
+    /// that container's layout as an item (as opposed to, say, an out-of-flow absolutely
+    /// positioned descendant) - the category of boxes for which `z-index` applies even under
+    /// `position: static`.
+    fn is_flex_or_grid_item(&self) -> bool {
+        self.flags.intersects(
+            FragmentFlags::IS_INLINE_FLEX_ITEM |
+                FragmentFlags::IS_BLOCK_FLEX_ITEM |
+                FragmentFlags::IS_GRID_ITEM,
+        )
+    }
+
     // Get the effective z-index of this fragment. Z-indices only apply to positioned element
     // per CSS 2 9.9.1 (http://www.w3.org/TR/CSS2/visuren.html#z-index), so this value may differ
     // from the value specified in the style.
@@ -2780,10 +3430,11 @@ impl Fragment {
             return self.style().get_position().z_index.integer_or(0);
         }
 
-        match self.style().get_box().display {
-            Display::Flex => self.style().get_position().z_index.integer_or(0),
-            _ => 0,
+        if self.is_flex_or_grid_item() {
+            return self.style().get_position().z_index.integer_or(0);
         }
+
+        0
     }
 
     /// Computes the overflow rect of this fragment relative to the start of the flow.
@@ -2844,7 +3495,16 @@ impl Fragment {
 
         // FIXME(pcwalton): Sometimes excessively fancy glyphs can make us draw outside our border
         // box too.
-        overflow
+        overflow.clip_x = axis_clip_state(self.style().get_box().overflow_x);
+        overflow.clip_y = axis_clip_state(self.style().get_box().overflow_y);
+        return overflow;
+
+        fn axis_clip_state(overflow: StyleOverflow) -> OverflowAxis {
+            match overflow {
+                StyleOverflow::Visible => OverflowAxis::Visible,
+                _ => OverflowAxis::Clip,
+            }
+        }
     }
 
     pub fn requires_line_break_afterward_if_wrapping_on_newlines(&self) -> bool {
@@ -2861,17 +3521,31 @@ impl Fragment {
     }
 
     pub fn strip_leading_whitespace_if_necessary(&mut self) -> WhitespaceStrippingResult {
-        if self.white_space_collapse() == WhiteSpaceCollapse::Preserve {
+        let white_space_collapse = self.white_space_collapse();
+        // `preserve` never strips anything, and `break-spaces` must never drop trailing/leading
+        // width either: every space it preserves is still a wrap opportunity that occupies space.
+        if matches!(
+            white_space_collapse,
+            WhiteSpaceCollapse::Preserve | WhiteSpaceCollapse::BreakSpaces
+        ) {
             return WhitespaceStrippingResult::RetainFragment;
         }
 
         return match self.specific {
-            SpecificFragmentInfo::TruncatedFragment(ref mut t) if t.text_info.is_some() => {
-                let scanned_text_fragment_info = t.text_info.as_mut().unwrap();
-                scanned_text(scanned_text_fragment_info, &mut self.border_box)
+            SpecificFragmentInfo::TruncatedFragment(ref mut t) => {
+                let has_markers = t.start_marker.is_some() || t.end_marker.is_some();
+                let result = match t.text_info {
+                    Some(ref mut scanned_text_fragment_info) => scanned_text(
+                        scanned_text_fragment_info,
+                        &mut self.border_box,
+                        white_space_collapse,
+                    ),
+                    None => WhitespaceStrippingResult::FragmentContainedOnlyWhitespace,
+                };
+                result.retain_for_markers(has_markers)
             },
             SpecificFragmentInfo::ScannedText(ref mut scanned_text_fragment_info) => {
-                scanned_text(scanned_text_fragment_info, &mut self.border_box)
+                scanned_text(scanned_text_fragment_info, &mut self.border_box, white_space_collapse)
             },
             SpecificFragmentInfo::UnscannedText(ref mut unscanned_text_fragment_info) => {
                 let mut new_text_string = String::new();
@@ -2881,7 +3555,11 @@ impl Fragment {
                         new_text_string.push(character);
                         continue;
                     }
-                    if char_is_whitespace(character) {
+                    // Under `pre-line`, a forced break is never collapsible: stop stripping as
+                    // soon as one is reached, and retain it (and everything after it) untouched.
+                    let stop_collapsing =
+                        character == '\n' && white_space_collapse == WhiteSpaceCollapse::PreserveBreaks;
+                    if char_is_whitespace(character) && !stop_collapsing {
                         modified = true;
                         continue;
                     }
@@ -2905,11 +3583,21 @@ impl Fragment {
         fn scanned_text(
             scanned_text_fragment_info: &mut ScannedTextFragmentInfo,
             border_box: &mut LogicalRect<Au>,
+            white_space_collapse: WhiteSpaceCollapse,
         ) -> WhitespaceStrippingResult {
-            let leading_whitespace_byte_count = scanned_text_fragment_info
-                .text()
-                .find(|c| !char_is_whitespace(c))
-                .unwrap_or(scanned_text_fragment_info.text().len());
+            let leading_whitespace_byte_count = if white_space_collapse ==
+                WhiteSpaceCollapse::PreserveBreaks
+            {
+                scanned_text_fragment_info
+                    .text()
+                    .find(|c| c == '\n' || !char_is_whitespace(c))
+                    .unwrap_or(scanned_text_fragment_info.text().len())
+            } else {
+                scanned_text_fragment_info
+                    .text()
+                    .find(|c| !char_is_whitespace(c))
+                    .unwrap_or(scanned_text_fragment_info.text().len())
+            };
 
             let whitespace_len = ByteIndex(leading_whitespace_byte_count as isize);
             let whitespace_range =
@@ -2931,17 +3619,29 @@ impl Fragment {
 
     /// Returns true if the entire fragment was stripped.
     pub fn strip_trailing_whitespace_if_necessary(&mut self) -> WhitespaceStrippingResult {
-        if self.white_space_collapse() == WhiteSpaceCollapse::Preserve {
+        let white_space_collapse = self.white_space_collapse();
+        if matches!(
+            white_space_collapse,
+            WhiteSpaceCollapse::Preserve | WhiteSpaceCollapse::BreakSpaces
+        ) {
             return WhitespaceStrippingResult::RetainFragment;
         }
 
         return match self.specific {
-            SpecificFragmentInfo::TruncatedFragment(ref mut t) if t.text_info.is_some() => {
-                let scanned_text_fragment_info = t.text_info.as_mut().unwrap();
-                scanned_text(scanned_text_fragment_info, &mut self.border_box)
+            SpecificFragmentInfo::TruncatedFragment(ref mut t) => {
+                let has_markers = t.start_marker.is_some() || t.end_marker.is_some();
+                let result = match t.text_info {
+                    Some(ref mut scanned_text_fragment_info) => scanned_text(
+                        scanned_text_fragment_info,
+                        &mut self.border_box,
+                        white_space_collapse,
+                    ),
+                    None => WhitespaceStrippingResult::FragmentContainedOnlyWhitespace,
+                };
+                result.retain_for_markers(has_markers)
             },
             SpecificFragmentInfo::ScannedText(ref mut scanned_text_fragment_info) => {
-                scanned_text(scanned_text_fragment_info, &mut self.border_box)
+                scanned_text(scanned_text_fragment_info, &mut self.border_box, white_space_collapse)
             },
             SpecificFragmentInfo::UnscannedText(ref mut unscanned_text_fragment_info) => {
                 let mut trailing_bidi_control_characters_to_retain = Vec::new();
@@ -2951,7 +3651,10 @@ impl Fragment {
                         trailing_bidi_control_characters_to_retain.push(character);
                         continue;
                     }
-                    if char_is_whitespace(character) {
+                    // As in the leading case, a `pre-line` forced break is never collapsible.
+                    let stop_collapsing = character == '\n' &&
+                        white_space_collapse == WhiteSpaceCollapse::PreserveBreaks;
+                    if char_is_whitespace(character) && !stop_collapsing {
                         modified = true;
                         continue;
                     }
@@ -2977,10 +3680,13 @@ impl Fragment {
         fn scanned_text(
             scanned_text_fragment_info: &mut ScannedTextFragmentInfo,
             border_box: &mut LogicalRect<Au>,
+            white_space_collapse: WhiteSpaceCollapse,
         ) -> WhitespaceStrippingResult {
             let mut trailing_whitespace_start_byte = 0;
             for (i, c) in scanned_text_fragment_info.text().char_indices().rev() {
-                if !char_is_whitespace(c) {
+                let stop_collapsing =
+                    c == '\n' && white_space_collapse == WhiteSpaceCollapse::PreserveBreaks;
+                if !char_is_whitespace(c) || stop_collapsing {
                     trailing_whitespace_start_byte = i + c.len_utf8();
                     break;
                 }
@@ -3027,6 +3733,50 @@ impl Fragment {
         false
     }
 
+    /// Returns true if any node within this fragment's inline context establishes a containing
+    /// block for absolutely positioned descendants - i.e. has a computed `position` other than
+    /// `static` - per <https://www.w3.org/TR/CSS2/visudet.html#containing-block-details>. Unlike
+    /// [`is_positioned`](Self::is_positioned), this does not consider `self`'s own style: it
+    /// answers "is one of my inline *ancestors* a positioning context", which is what block
+    /// layout needs when deciding whether to anchor an `InlineAbsoluteHypothetical` fragment to
+    /// an inline box instead of the enclosing block.
+    pub fn has_positioned_inline_ancestor(&self) -> bool {
+        self.inline_context.as_ref().is_some_and(|inline_context| {
+            inline_context
+                .nodes
+                .iter()
+                .any(|node| node.style.get_box().position != Position::Static)
+        })
+    }
+
+    /// Returns the nearest node in this fragment's inline context that establishes a containing
+    /// block for absolutely positioned descendants (see
+    /// [`has_positioned_inline_ancestor`](Self::has_positioned_inline_ancestor)), along with that
+    /// ancestor's accumulated inline-level rectangle, or `None` if no such ancestor exists.
+    /// `inline_context.nodes` runs outermost-first, so the nearest ancestor to this fragment is
+    /// the last one in the list whose position isn't static.
+    ///
+    /// There's no inline-layout pass in this snapshot (`inline.rs` doesn't exist here) to
+    /// accumulate the true union rectangle of every fragment generated by the ancestor's element
+    /// across however many lines it spans, so this uses `self`'s own border box as the nearest
+    /// available approximation - correct for the common case of a single-fragment inline
+    /// ancestor, but not a multi-line one. A real implementation would instead union the border
+    /// boxes of every fragment sharing this same inline-context node across the whole inline
+    /// formatting context.
+    pub fn containing_inline_ancestor(
+        &self,
+        flow_size: &Size2D<Au>,
+    ) -> Option<(&InlineFragmentNodeInfo, Rect<Au>)> {
+        let inline_context = self.inline_context.as_ref()?;
+        let ancestor = inline_context
+            .nodes
+            .iter()
+            .rev()
+            .find(|node| node.style.get_box().position != Position::Static)?;
+        let rect = self.border_box.to_physical(self.style.writing_mode, *flow_size);
+        Some((ancestor, rect))
+    }
+
     /// Returns true if this node is absolutely positioned.
     pub fn is_absolutely_positioned(&self) -> bool {
         self.style.get_box().position == Position::Absolute
@@ -3293,6 +4043,10 @@ bitflags! {
         /// True if we should attempt to split at character boundaries if this split fails. \
         /// This is used to implement `overflow-wrap: break-word`."]
         const RETRY_AT_CHARACTER_BOUNDARIES = 0x02;
+        /// True if an overflowing word should first be offered a Liang hyphenation point before \
+        /// falling back to the ordinary line-wrap (or character-boundary retry). This is used \
+        /// to implement `hyphens: auto`."]
+        const RETRY_AT_HYPHENATION_POINTS = 0x04;
     }
 }
 
@@ -3369,14 +4123,57 @@ impl WhitespaceStrippingResult {
             WhitespaceStrippingResult::RetainFragment
         }
     }
+
+    /// Reinterprets this result for a fragment that may carry `text-overflow` markers of its
+    /// own: a marker is visible content independent of whatever text remains once whitespace and
+    /// bidi control characters are stripped, so a line must never drop a fragment purely for
+    /// having collapsed to nothing if it still has a marker to paint.
+    fn retain_for_markers(self, has_markers: bool) -> WhitespaceStrippingResult {
+        match self {
+            WhitespaceStrippingResult::RetainFragment => self,
+            WhitespaceStrippingResult::FragmentContainedOnlyWhitespace |
+            WhitespaceStrippingResult::FragmentContainedOnlyBidiControlCharacters => {
+                if has_markers {
+                    WhitespaceStrippingResult::RetainFragment
+                } else {
+                    self
+                }
+            },
+        }
+    }
 }
 
+/// Whether a single axis of an element's overflow is clipped to its border/padding box or left
+/// visible, per the `overflow-x`/`overflow-y` longhands - tracked independently so
+/// `overflow-x: hidden; overflow-y: visible` (and the reverse) can be honored when deriving an
+/// effective clip rectangle from an [`Overflow`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowAxis {
+    /// This axis clips to the element's own extent - the real border/padding box bound applies.
+    Clip,
+    /// This axis doesn't clip at all - it should contribute effectively infinite bounds to a
+    /// clip rectangle, so content overflowing only this axis is never cut off.
+    Visible,
+}
+
+/// An axis bound wide enough that no real layout rectangle can exceed it, standing in for
+/// "unclipped" on one axis of a clip rectangle.
+/// Half of `i32`'s range on each side, so `MAX_AU - MIN_AU` (a visible axis's width in
+/// [`Overflow::clip_rect`]) still fits in an `i32`.
+const MIN_AU: Au = Au(-(1 << 29));
+const MAX_AU: Au = Au((1 << 29) - 1);
+
 /// The overflow area. We need two different notions of overflow: paint overflow and scrollable
 /// overflow.
 #[derive(Clone, Copy, Debug)]
 pub struct Overflow {
     pub scroll: Rect<Au>,
     pub paint: Rect<Au>,
+    /// Whether the horizontal/vertical axes of this overflow should actually clip, per
+    /// `overflow-x`/`overflow-y`. Defaults to clipping both axes, matching the pre-existing
+    /// (axis-uniform) behavior before this field was split out.
+    pub clip_x: OverflowAxis,
+    pub clip_y: OverflowAxis,
 }
 
 impl Overflow {
@@ -3384,6 +4181,8 @@ impl Overflow {
         Overflow {
             scroll: Rect::zero(),
             paint: Rect::zero(),
+            clip_x: OverflowAxis::Clip,
+            clip_y: OverflowAxis::Clip,
         }
     }
 
@@ -3391,18 +4190,63 @@ impl Overflow {
         Overflow {
             scroll: *border_box,
             paint: *border_box,
+            clip_x: OverflowAxis::Clip,
+            clip_y: OverflowAxis::Clip,
         }
     }
 
+    /// Marks the horizontal axis as clipped (`overflow-x: hidden`/`scroll`/`auto`) or visible
+    /// (`overflow-x: visible`), leaving the vertical axis's clip state untouched.
+    pub fn clip_x(mut self, axis: OverflowAxis) -> Overflow {
+        self.clip_x = axis;
+        self
+    }
+
+    /// Marks the vertical axis as clipped or visible, leaving the horizontal axis untouched.
+    pub fn clip_y(mut self, axis: OverflowAxis) -> Overflow {
+        self.clip_y = axis;
+        self
+    }
+
     pub fn union(&mut self, other: &Overflow) {
         self.scroll = self.scroll.union(&other.scroll);
         self.paint = self.paint.union(&other.paint);
+        // A union is only ever visible on an axis if both sides agree it's visible - if either
+        // side of the union actually clips that axis, the combined overflow must too.
+        self.clip_x = union_axis(self.clip_x, other.clip_x);
+        self.clip_y = union_axis(self.clip_y, other.clip_y);
+
+        fn union_axis(a: OverflowAxis, b: OverflowAxis) -> OverflowAxis {
+            if a == OverflowAxis::Clip || b == OverflowAxis::Clip {
+                OverflowAxis::Clip
+            } else {
+                OverflowAxis::Visible
+            }
+        }
     }
 
     pub fn translate(&mut self, by: &Vector2D<Au>) {
         self.scroll = self.scroll.translate(*by);
         self.paint = self.paint.translate(*by);
     }
+
+    /// Derives the effective clip rectangle for `border_box`, honoring each axis's clip state
+    /// independently: a clipped axis uses `border_box`'s own extent on that axis, while a
+    /// visible axis is widened to `MIN_AU..MAX_AU` so nothing on that axis is ever cut off. This
+    /// is what lets `overflow-x: hidden; overflow-y: visible` clip only horizontally, e.g. so a
+    /// horizontally-scrolling strip doesn't also clip a vertically-overflowing shadow or focus
+    /// ring.
+    pub fn clip_rect(&self, border_box: &Rect<Au>) -> Rect<Au> {
+        let (x, width) = match self.clip_x {
+            OverflowAxis::Clip => (border_box.origin.x, border_box.size.width),
+            OverflowAxis::Visible => (MIN_AU, MAX_AU - MIN_AU),
+        };
+        let (y, height) = match self.clip_y {
+            OverflowAxis::Clip => (border_box.origin.y, border_box.size.height),
+            OverflowAxis::Visible => (MIN_AU, MAX_AU - MIN_AU),
+        };
+        Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+    }
 }
 
 impl Default for Overflow {
@@ -3414,7 +4258,6 @@ impl Default for Overflow {
 bitflags! {
     #[derive(Clone, Debug)]
     pub struct FragmentFlags: u8 {
-        // TODO(stshine): find a better name since these flags can also be used for grid item.
         /// Whether this fragment represents a child in a row flex container.
         const IS_INLINE_FLEX_ITEM = 0b0000_0001;
         /// Whether this fragment represents a child in a column flex container.
@@ -3423,6 +4266,13 @@ bitflags! {
         const IS_ELLIPSIS = 0b0000_0100;
         /// Whether this fragment is for the body element child of a html element root element.
         const IS_BODY_ELEMENT_OF_HTML_ELEMENT_ROOT =  0b0000_1000;
+        /// Whether this fragment represents a grid item - a direct in-flow child of a grid
+        /// container, <https://www.w3.org/TR/css-grid-1/#grid-items>. Distinct from the
+        /// `*_FLEX_ITEM` flags above despite sharing their stacking-context/z-index treatment: a
+        /// grid item isn't a flex item, and conflating the two made it impossible to add
+        /// grid-specific handling (e.g. which grid line/track it was placed in) without also
+        /// affecting flex layout.
+        const IS_GRID_ITEM = 0b0001_0000;
     }
 }
 
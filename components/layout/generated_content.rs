@@ -0,0 +1,194 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Sequential counter-scope bookkeeping for CSS counters and automatic list numbering,
+//! <https://www.w3.org/TR/css-lists-3/#auto-numbering>, plus the driver that resolves a
+//! [`GeneratedContentInfo`] value to its final text against that state and the `quotes` nesting
+//! tracked by `quote.rs`.
+//!
+//! Resolving `counter()`/`counters()` and `list-item` markers requires a single pass over the
+//! element tree *in document order*, maintaining a stack of counter scopes: descending into an
+//! element pushes a scope (so its own `counter-reset` shadows an ancestor's counter of the same
+//! name without disturbing it), processing that element's `counter-reset` then `counter-increment`
+//! declarations populates it, and any generated content on the way resolves against the stack as
+//! it stands at that point. This has to run sequentially, in document order, outside the parallel
+//! layout phases - unlike most of layout, a counter's value genuinely depends on everything that
+//! came before it in the tree.
+//!
+//! This module is the self-contained half of that pass: the stack itself
+//! ([`CounterScopeStack`]) and the per-element/per-fragment resolution logic. The walk that
+//! pushes/pops a scope per element and calls into this at the right times would live in whatever
+//! constructs the flow/fragment tree in a full build - `flow.rs`/`inline.rs`/`block.rs`, none of
+//! which exist in this snapshot - so there is nothing to drive this from yet. Once that pass
+//! exists, it should write [`resolve_generated_content`]'s result into the generated-content
+//! fragment's `UnscannedTextFragmentInfo` payload before text-run scanning runs.
+//!
+//! Like `quote.rs`, this takes its inputs (reset/increment lists, the `quotes` list, a
+//! counter-style lookup) as plain parameters instead of pulling them off `self.style()`, since the
+//! `style` crate itself - and the accessors that would expose `counter-reset`/`counter-increment`/
+//! `quotes` as typed values - doesn't exist in this snapshot either.
+
+use std::collections::HashMap;
+
+use crate::counter_style::{self, CounterStyle};
+use crate::fragment::GeneratedContentInfo;
+use crate::quote::{self, QuoteDepth, QuotePair};
+use style::values::computed::counters::ContentItem;
+
+/// One already-resolved `counter-reset`/`counter-increment`/`counter-set` declaration, e.g.
+/// `counter-reset: section 0` becomes `("section".to_owned(), 0)`.
+pub type CounterAction = (String, i64);
+
+/// The name of the counter that `display: list-item` boxes auto-increment unless the element's
+/// own `counter-increment` already mentions it, <https://www.w3.org/TR/css-lists-3/#auto-numbering>.
+pub const LIST_ITEM_COUNTER_NAME: &str = "list-item";
+
+/// The nested stack of named counters in scope while walking the element tree in document order.
+/// One [`push_scope`](Self::push_scope) is expected per element - regardless of whether that
+/// element resets or increments anything itself - with a matching
+/// [`pop_scope`](Self::pop_scope) once its subtree is done.
+#[derive(Default)]
+pub struct CounterScopeStack {
+    /// `scopes[0]` is the root (document) scope; `scopes.last()` is the innermost element
+    /// currently being visited.
+    scopes: Vec<HashMap<String, i64>>,
+}
+
+impl CounterScopeStack {
+    pub fn new() -> Self {
+        CounterScopeStack { scopes: vec![HashMap::new()] }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Pops the innermost scope. A no-op if only the root scope remains, so a mismatched extra
+    /// pop can't underflow the stack.
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// `counter-reset: name value` - creates or overrides `name` at the *current* (innermost)
+    /// scope, shadowing any counter of the same name an ancestor established without disturbing
+    /// the ancestor's own value.
+    pub fn reset(&mut self, name: &str, value: i64) {
+        self.innermost_scope().insert(name.to_owned(), value);
+    }
+
+    /// `counter-increment: name amount` - bumps the nearest enclosing counter named `name` by
+    /// `amount`, or creates one at `amount` in the *current* scope if `name` isn't in scope at
+    /// all yet (the spec's "an implicit `counter-reset: name 0` immediately before it").
+    pub fn increment(&mut self, name: &str, amount: i64) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(value) = scope.get_mut(name) {
+                *value += amount;
+                return;
+            }
+        }
+        self.innermost_scope().insert(name.to_owned(), amount);
+    }
+
+    /// The nearest enclosing value of `name`, for `counter(name)`. Counters never explicitly
+    /// reset or incremented anywhere in scope read as `0`.
+    pub fn value(&self, name: &str) -> i64 {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied()).unwrap_or(0)
+    }
+
+    /// Every value of `name` currently in scope, outermost first, for `counters(name, sep)`,
+    /// which joins one rendered value per nested counter of that name.
+    pub fn values(&self, name: &str) -> Vec<i64> {
+        self.scopes.iter().filter_map(|scope| scope.get(name).copied()).collect()
+    }
+
+    fn innermost_scope(&mut self) -> &mut HashMap<String, i64> {
+        self.scopes.last_mut().expect("CounterScopeStack always has at least the root scope")
+    }
+}
+
+/// Applies one element's `counter-reset` and `counter-increment` declarations against `counters`,
+/// in the order the spec requires: every reset first (each lands in this element's own,
+/// already-[`push_scope`](CounterScopeStack::push_scope)'d level), then every increment.
+///
+/// Also auto-increments [`LIST_ITEM_COUNTER_NAME`] when `is_list_item` is set and
+/// `counter_increment` doesn't already mention it explicitly, since `display: list-item` implies
+/// an increment of its own that an explicit `counter-increment: list-item …` on the same element
+/// overrides rather than adds to.
+pub fn apply_counter_actions(
+    counters: &mut CounterScopeStack,
+    counter_reset: &[CounterAction],
+    counter_increment: &[CounterAction],
+    is_list_item: bool,
+) {
+    for (name, value) in counter_reset {
+        counters.reset(name, *value);
+    }
+    for (name, amount) in counter_increment {
+        counters.increment(name, *amount);
+    }
+    if is_list_item && !counter_increment.iter().any(|(name, _)| name == LIST_ITEM_COUNTER_NAME) {
+        counters.increment(LIST_ITEM_COUNTER_NAME, 1);
+    }
+}
+
+/// Resolves one [`GeneratedContentInfo`] value to the text it contributes, given the counter
+/// state accumulated so far and the element's `quotes` list/running quote `depth`. Returns `None`
+/// for content that contributes no text of its own (`no-open-quote`/`no-close-quote`, or
+/// `Empty`) - `depth` may still have changed as a side effect - and for any `ContentItem` other
+/// than `counter()`/`counters()`, which this does not resolve.
+pub fn resolve_generated_content(
+    content: &GeneratedContentInfo,
+    counters: &CounterScopeStack,
+    list_style: &CounterStyle,
+    quotes: &[QuotePair],
+    depth: &mut QuoteDepth,
+    lookup: &dyn Fn(&str) -> Option<CounterStyle>,
+) -> Option<String> {
+    match content {
+        GeneratedContentInfo::ListItem => Some(counter_style::format_counter(
+            counters.value(LIST_ITEM_COUNTER_NAME),
+            list_style,
+            lookup,
+        )),
+        GeneratedContentInfo::ContentItem(item) => resolve_content_item(item, counters, lookup),
+        GeneratedContentInfo::OpenQuote |
+        GeneratedContentInfo::CloseQuote |
+        GeneratedContentInfo::NoOpenQuote |
+        GeneratedContentInfo::NoCloseQuote => quote::resolve_quote(content, quotes, depth),
+        GeneratedContentInfo::Empty => None,
+    }
+}
+
+/// Resolves the `counter()`/`counters()` forms of `content`.
+///
+/// This assumes `ContentItem::Counter`/`Counters` carry their `<counter-style>` as a plain style
+/// name (resolved through `lookup`, the same as `GeneratedContentInfo::ListItem` above) rather
+/// than Stylo's richer `CounterStyleOrNone`, since there's no `style` crate here to confirm the
+/// real representation against. Any other `ContentItem` variant (plain strings, `attr()`, image
+/// content) isn't this module's concern - those don't need counter state to resolve - so they
+/// fall through unresolved here.
+fn resolve_content_item(
+    item: &ContentItem,
+    counters: &CounterScopeStack,
+    lookup: &dyn Fn(&str) -> Option<CounterStyle>,
+) -> Option<String> {
+    match item {
+        ContentItem::Counter(name, style_name) => {
+            let style = lookup(style_name).unwrap_or_else(CounterStyle::decimal);
+            Some(counter_style::format_counter(counters.value(name), &style, lookup))
+        },
+        ContentItem::Counters(name, separator, style_name) => {
+            let style = lookup(style_name).unwrap_or_else(CounterStyle::decimal);
+            let rendered: Vec<String> = counters
+                .values(name)
+                .into_iter()
+                .map(|value| counter_style::format_counter(value, &style, lookup))
+                .collect();
+            Some(rendered.join(separator))
+        },
+        _ => None,
+    }
+}
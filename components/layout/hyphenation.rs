@@ -0,0 +1,188 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Automatic hyphenation via Liang's algorithm, the same TeX-style pattern-matching scheme real
+//! browsers use for `hyphens: auto`, <https://www.w3.org/TR/css-text-3/#hyphenation>.
+//!
+//! This only computes *where* a word may legally be hyphenated - the byte offsets, within the
+//! word, at which a soft line break may insert a hyphen. `fragment.rs`'s
+//! `find_hyphenation_split` uses these offsets to choose where
+//! `calculate_split_position_using_breaking_strategy` splits an overflowing word under
+//! `hyphens: auto`. No U+2010 hyphen glyph is inserted at the split: shaping one needs
+//! font-shaping machinery (`text_run.rs`, `text.rs`) that doesn't exist in this snapshot.
+
+use std::collections::HashMap;
+
+/// A legal hyphenation point is a `Liang` digit `>= 1` at an odd value.
+fn digit_is_break(digit: u8) -> bool {
+    digit % 2 == 1
+}
+
+/// A single TeX-style Liang pattern, like `hy3ph2en` (the digits are priorities that slot between
+/// the letters around them; `.` is the implicit word-boundary marker used in pattern text, e.g.
+/// `.hy3phen` or `hyphen1.`).
+struct Pattern {
+    /// The pattern's letters (and `.` boundary markers), with no digits.
+    letters: Vec<char>,
+    /// `digits[i]` is the priority immediately before `letters[i]`; `digits[letters.len()]` is the
+    /// priority after the last letter.
+    digits: Vec<u8>,
+}
+
+impl Pattern {
+    /// Parses one TeX pattern string, e.g. `"h1y3ph2en"` or `".hy3ph"`.
+    fn parse(pattern: &str) -> Pattern {
+        let mut letters = Vec::new();
+        let mut digits = Vec::new();
+        let mut pending_digit: Option<u8> = None;
+
+        for ch in pattern.chars() {
+            if let Some(digit) = ch.to_digit(10) {
+                pending_digit = Some(digit as u8);
+            } else {
+                digits.push(pending_digit.take().unwrap_or(0));
+                letters.push(ch);
+            }
+        }
+        digits.push(pending_digit.take().unwrap_or(0));
+
+        Pattern { letters, digits }
+    }
+}
+
+/// A compiled, language-keyed set of hyphenation patterns. Building one parses every pattern
+/// string once; [`Dictionary::hyphenate`] then slides each pattern across a word in O(patterns ×
+/// word length), which is the textbook (if not the fastest - a real trie would do better) way to
+/// implement Liang's algorithm.
+pub struct Dictionary {
+    patterns: Vec<Pattern>,
+    left_hyphen_min: usize,
+    right_hyphen_min: usize,
+}
+
+impl Dictionary {
+    /// `lefthyphenmin`/`righthyphenmin` default to 2/3 per `hyphenation.tex` convention, matching
+    /// the defaults real hyphenation dictionaries (e.g. `hyph-en-us.tex`) declare for English.
+    pub fn new(patterns: &[&str]) -> Dictionary {
+        Dictionary {
+            patterns: patterns.iter().map(|pattern| Pattern::parse(pattern)).collect(),
+            left_hyphen_min: 2,
+            right_hyphen_min: 3,
+        }
+    }
+
+    pub fn with_hyphen_min(mut self, left: usize, right: usize) -> Dictionary {
+        self.left_hyphen_min = left;
+        self.right_hyphen_min = right;
+        self
+    }
+
+    /// Returns the legal hyphenation points within `word`, as byte offsets from the start of
+    /// `word` at which a hyphen may be inserted (i.e. a break between the byte before and the
+    /// byte at that offset). `word` should already be a single typographic word with no
+    /// whitespace; this does not itself split text into words.
+    ///
+    /// U+00AD SOFT HYPHEN characters already in `word` are treated as forced break candidates
+    /// (stripped from the returned word-relative offsets' reference text, but this function only
+    /// returns offsets, so callers that need the soft hyphens removed must do that themselves).
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let soft_hyphen_offsets: Vec<usize> = word
+            .char_indices()
+            .filter(|&(_, ch)| ch == '\u{ad}')
+            .map(|(index, _)| index)
+            .collect();
+
+        let lowercase = word.to_lowercase();
+        let letters: Vec<char> = std::iter::once('.')
+            .chain(lowercase.chars().filter(|&ch| ch != '\u{ad}'))
+            .chain(std::iter::once('.'))
+            .collect();
+        let word_len = letters.len() - 2;
+
+        // `values[i]` is the maximum digit seen so far at the boundary before the word's `i`th
+        // character, `letters[i + 1]` (boundary index `i` runs from 0, just after the leading '.', through `word_len`, just
+        // before the trailing '.').
+        let mut values = vec![0u8; word_len + 1];
+
+        for pattern in &self.patterns {
+            if pattern.letters.len() > letters.len() {
+                continue;
+            }
+            for start in 0..=(letters.len() - pattern.letters.len()) {
+                if letters[start..start + pattern.letters.len()] != pattern.letters[..] {
+                    continue;
+                }
+                for (offset, &digit) in pattern.digits.iter().enumerate() {
+                    // `start + offset` is a boundary index in the padded `letters` sequence;
+                    // subtract 1 to land in `values`'s word-relative indexing (boundary 0 is
+                    // right after the leading '.').
+                    let boundary = start + offset;
+                    if boundary == 0 || boundary > word_len {
+                        continue;
+                    }
+                    let index = boundary - 1;
+                    values[index] = values[index].max(digit);
+                }
+            }
+        }
+
+        let mut offsets: Vec<usize> = (0..word_len)
+            .filter(|&index| digit_is_break(values[index]))
+            .filter(|&index| index >= self.left_hyphen_min && word_len - index >= self.right_hyphen_min)
+            .map(|char_index| byte_offset_of_char(word, char_index))
+            .collect();
+
+        for soft_hyphen in soft_hyphen_offsets {
+            if !offsets.contains(&soft_hyphen) {
+                offsets.push(soft_hyphen);
+            }
+        }
+
+        offsets.sort_unstable();
+        offsets
+    }
+}
+
+/// Converts a char-index (counting Unicode scalar values, ignoring any soft hyphens already
+/// stripped from `lowercase`/`letters` above) back into a byte offset into the original `word`.
+fn byte_offset_of_char(word: &str, char_index: usize) -> usize {
+    word.char_indices()
+        .filter(|&(_, ch)| ch != '\u{ad}')
+        .nth(char_index)
+        .map_or(word.len(), |(byte_index, _)| byte_index)
+}
+
+/// A minimal, hand-picked subset of the shape `hyph-en-us.tex` patterns take - enough to
+/// demonstrate Liang's algorithm end-to-end without vendoring the full ~4500-pattern dictionary
+/// a real deployment would load (keyed by the element's computed `lang`), which doesn't exist
+/// anywhere in this snapshot.
+pub const EN_US_SAMPLE_PATTERNS: &[&str] = &[
+    "1bb", "1cc", "1dd", "1ff", "1gg", "1mm", "1nn", "1pp", "1rr", "1ss", "1tt", "1zz", "tion1",
+    "1tion", "ing1", "1ing", "1er", "er1", "1ly", "ly1", "1ck", "ck1",
+];
+
+/// Caches one compiled [`Dictionary`] per language tag, so repeated hyphenation of a document
+/// doesn't reparse the same pattern set on every word.
+#[derive(Default)]
+pub struct DictionaryCache {
+    dictionaries: HashMap<String, Dictionary>,
+}
+
+impl DictionaryCache {
+    pub fn new() -> DictionaryCache {
+        DictionaryCache::default()
+    }
+
+    /// Returns the cached dictionary for `language`, compiling it via `load` on first use.
+    /// `load` is only called on a cache miss.
+    pub fn get_or_compile(
+        &mut self,
+        language: &str,
+        load: impl FnOnce() -> Dictionary,
+    ) -> &Dictionary {
+        self.dictionaries
+            .entry(language.to_owned())
+            .or_insert_with(load)
+    }
+}
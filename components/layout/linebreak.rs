@@ -0,0 +1,241 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Optimal (Knuth-Plass "total fit") paragraph line-breaking, as an opt-in alternative to the
+//! greedy, per-fragment splitting `calculate_split_position_using_breaking_strategy` in
+//! `fragment.rs` does today. See Knuth & Plass, "Breaking Paragraphs into Lines" (1981).
+//!
+//! The greedy splitter only ever looks as far ahead as the current fragment, so it can leave a
+//! line nearly empty just because the next word didn't fit - "rivers" of uneven whitespace. This
+//! module instead models a whole paragraph as a stream of [`Item`]s and finds the set of breaks
+//! that minimizes total "badness" (how far each line's natural width is from the target, scaled
+//! by its available stretch/shrink) plus any per-breakpoint penalty, summed over the paragraph.
+//!
+//! This is deliberately a standalone algorithm over an abstract `Item` stream, not wired into an
+//! inline flow: building the `Item`s from a run of fragments is the job of the inline-layout
+//! pass, which in a full tree would walk something like `InlineFlow`'s fragment list - but that
+//! type lives in `inline.rs`, which doesn't exist in this snapshot. [`break_paragraph`] is the
+//! reusable dynamic-programming core, ready for that pass to drive once it exists: it would
+//! produce one [`Item::Box`] per `metrics_for_slice` call on a natural-word slice, one
+//! [`Item::Glue`] per inter-word space (stretch/shrink derived from the space glyph's font
+//! metrics), and an [`Item::Penalty`] at every other soft-wrap opportunity (e.g. a hyphenation
+//! point from `hyphenation::Dictionary`, carrying the hyphen's advance as its width).
+//!
+//! This implementation also simplifies the classic algorithm in one way: Knuth & Plass group
+//! active nodes into "fitness classes" (tight/loose/very loose) and only compare demerits within
+//! the same class, so a looseness change between adjacent lines is itself penalized. This keeps
+//! a single active node per breakpoint instead, which is simpler and still total-fit-optimal for
+//! badness plus explicit penalties, but won't penalize a tight line following a loose one.
+
+use app_units::Au;
+
+/// One paragraph-level item, in the Knuth-Plass sense.
+#[derive(Clone, Copy, Debug)]
+pub enum Item {
+    /// An unbreakable run of content with a fixed width, e.g. the glyphs of one word.
+    Box { width: Au },
+    /// A breakable space between boxes, with a natural width and how much it may stretch or
+    /// shrink to justify a line.
+    Glue { width: Au, stretch: Au, shrink: Au },
+    /// A candidate breakpoint that isn't a glue, e.g. a hyphenation point. `width` is what the
+    /// item contributes to the line *if* a break is taken there (a hyphen glyph's advance);
+    /// it contributes nothing if no break occurs there. `penalty` biases the dynamic program for
+    /// or against breaking here; `flagged` marks a break that should be avoided on two
+    /// consecutive lines (TeX uses this for hyphenation points, to avoid a ladder of hyphens).
+    Penalty { width: Au, penalty: i32, flagged: bool },
+}
+
+/// A `Penalty` with this value (or higher) is never a legal breakpoint.
+pub const FORCE_NO_BREAK: i32 = 10_000;
+
+/// A `Penalty` with this value (or lower) is always taken, ending the line there.
+pub const FORCE_BREAK: i32 = -10_000;
+
+const DEMERITS_LINE: f32 = 10.0;
+const DEMERITS_CONSECUTIVE_FLAGGED: f32 = 3_000.0;
+
+/// The adjustment ratio beyond which a line is considered "overfull" and its active node is
+/// discarded, since every later breakpoint would only make that line longer still.
+const MAX_SHRINK_RATIO: f32 = -1.0;
+
+struct ActiveNode {
+    /// Index of the item this node's line ends at (or breaks on), or `None` for the
+    /// start-of-paragraph sentinel node.
+    break_item: Option<usize>,
+    line_number: usize,
+    total_demerits: f32,
+    ends_on_flagged_break: bool,
+    previous: Option<usize>,
+}
+
+/// Sums the width (and, for glue, stretch/shrink) of the items in `items[start..end]`, skipping a
+/// single leading glue item (discarded immediately after a break, per the usual line-breaking
+/// convention), plus `break_item`'s own width if it's a penalty that was actually broken at.
+fn line_metrics(items: &[Item], start: usize, end: usize, break_item: Option<&Item>) -> (Au, Au, Au) {
+    let mut start = start;
+    if matches!(items.get(start), Some(Item::Glue { .. })) {
+        start += 1;
+    }
+
+    let mut width = Au(0);
+    let mut stretch = Au(0);
+    let mut shrink = Au(0);
+    for item in &items[start..end] {
+        match *item {
+            Item::Box { width: item_width } => width += item_width,
+            Item::Glue {
+                width: item_width,
+                stretch: item_stretch,
+                shrink: item_shrink,
+            } => {
+                width += item_width;
+                stretch += item_stretch;
+                shrink += item_shrink;
+            },
+            Item::Penalty { .. } => {},
+        }
+    }
+
+    if let Some(Item::Penalty { width: penalty_width, .. }) = break_item {
+        width += *penalty_width;
+    }
+
+    (width, stretch, shrink)
+}
+
+fn is_legal_breakpoint(items: &[Item], index: usize) -> bool {
+    match items[index] {
+        Item::Penalty { penalty, .. } => penalty < FORCE_NO_BREAK,
+        Item::Glue { .. } => index > 0 && matches!(items[index - 1], Item::Box { .. }),
+        Item::Box { .. } => false,
+    }
+}
+
+/// Computes the total badness-plus-penalty-minimizing set of line breaks for `items`, targeting
+/// `line_width` on every line. Returns the item indices to break at, in order. Only lines that end
+/// at a breakpoint are scored, so the last line counts only when `items` ends with a
+/// `FORCE_BREAK` penalty; otherwise the active node with the lowest total demerits wins. An empty
+/// result comes back when every active node was dropped as overfull, and also when no break is
+/// taken at all (e.g. `items` has no legal breakpoint, such as a single box of any width).
+pub fn break_paragraph(items: &[Item], line_width: Au) -> Vec<usize> {
+    let mut nodes = vec![ActiveNode {
+        break_item: None,
+        line_number: 0,
+        total_demerits: 0.0,
+        ends_on_flagged_break: false,
+        previous: None,
+    }];
+    let mut active = vec![0usize];
+
+    for index in 0..items.len() {
+        if !is_legal_breakpoint(items, index) {
+            continue;
+        }
+
+        let break_item = &items[index];
+        let (penalty, flagged) = match *break_item {
+            Item::Penalty { penalty, flagged, .. } => (penalty, flagged),
+            _ => (0, false),
+        };
+
+        let mut best: Option<(usize, f32, bool)> = None;
+        let mut infeasible = Vec::new();
+
+        for (slot, &node_index) in active.iter().enumerate() {
+            let node = &nodes[node_index];
+            let start = node.break_item.map_or(0, |break_item| break_item + 1);
+            let (width, stretch, shrink) = line_metrics(items, start, index, Some(break_item));
+            let difference = (line_width - width).to_f32_px();
+
+            let ratio = if difference > 0.0 {
+                let stretch = stretch.to_f32_px();
+                if stretch > 0.0 {
+                    difference / stretch
+                } else {
+                    f32::INFINITY
+                }
+            } else if difference < 0.0 {
+                let shrink = shrink.to_f32_px();
+                if shrink > 0.0 {
+                    difference / shrink
+                } else {
+                    f32::NEG_INFINITY
+                }
+            } else {
+                0.0
+            };
+
+            if ratio < MAX_SHRINK_RATIO {
+                // This line is overfull even shrunk as much as possible; it can only get worse
+                // with more content, so this node can never start a feasible line again.
+                infeasible.push(slot);
+                continue;
+            }
+
+            if ratio.is_infinite() && ratio > 0.0 && penalty > FORCE_BREAK {
+                // This line is far too loose to take a non-forced break here; wait for a later
+                // breakpoint that fills it out more, but keep the node active.
+                continue;
+            }
+
+            let badness = 100.0 * ratio.abs().powi(3);
+            let mut demerits = if penalty >= 0 {
+                (DEMERITS_LINE + badness).powi(2) + (penalty as f32).powi(2)
+            } else if penalty > FORCE_BREAK {
+                (DEMERITS_LINE + badness).powi(2) - (penalty as f32).powi(2)
+            } else {
+                (DEMERITS_LINE + badness).powi(2)
+            };
+            if flagged && node.ends_on_flagged_break {
+                demerits += DEMERITS_CONSECUTIVE_FLAGGED;
+            }
+
+            let total_demerits = node.total_demerits + demerits;
+            if best.map_or(true, |(_, best_demerits, _)| total_demerits < best_demerits) {
+                best = Some((node_index, total_demerits, flagged));
+            }
+        }
+
+        for &slot in infeasible.iter().rev() {
+            active.remove(slot);
+        }
+
+        let Some((predecessor, total_demerits, ends_on_flagged_break)) = best else {
+            continue;
+        };
+
+        nodes.push(ActiveNode {
+            break_item: Some(index),
+            line_number: nodes[predecessor].line_number + 1,
+            total_demerits,
+            ends_on_flagged_break,
+            previous: Some(predecessor),
+        });
+        active.push(nodes.len() - 1);
+
+        if penalty <= FORCE_BREAK {
+            // A forced break always wins; every other active node is now moot.
+            active = vec![nodes.len() - 1];
+        }
+    }
+
+    let Some(&best_active) = active
+        .iter()
+        .min_by(|&&a, &&b| nodes[a].total_demerits.partial_cmp(&nodes[b].total_demerits).unwrap())
+    else {
+        return Vec::new();
+    };
+
+    let mut breakpoints = Vec::new();
+    let mut current = Some(best_active);
+    while let Some(node_index) = current {
+        let node = &nodes[node_index];
+        if let Some(break_item) = node.break_item {
+            breakpoints.push(break_item);
+        }
+        current = node.previous;
+    }
+    breakpoints.reverse();
+    breakpoints
+}
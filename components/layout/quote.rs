@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Nesting-aware resolution of `content: open-quote | close-quote | no-open-quote |
+//! no-close-quote`, per the `quotes` property,
+//! <https://www.w3.org/TR/css-content-3/#quotes-insert>.
+//!
+//! Resolving a quote mark requires a running "quote depth" threaded through the sequential
+//! generated-content pass in document order. `generated_content::resolve_generated_content`
+//! hands `GeneratedContentInfo`'s quote variants to [`resolve_quote`] together with that depth,
+//! next to its list-item and `counter()` handling. The element's computed `quotes` list is passed
+//! in by the caller; this module implements the depth bookkeeping and string selection.
+
+use crate::fragment::GeneratedContentInfo;
+
+/// A single `(open, close)` string pair from a computed `quotes` list, indexed by nesting depth.
+pub type QuotePair = (String, String);
+
+/// Tracks how deeply nested `open-quote`/`close-quote` generated content currently is while
+/// walking the fragment tree in document order. One `QuoteDepth` is threaded through the whole
+/// traversal, the same way a list-item counter is.
+#[derive(Clone, Debug, Default)]
+pub struct QuoteDepth(u32);
+
+impl QuoteDepth {
+    pub fn new() -> Self {
+        QuoteDepth(0)
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Resolves `content` (one of `GeneratedContentInfo`'s four quote variants) against `quotes` and
+/// the running `depth`, advancing `depth` in place. Returns the text to emit as a `ScannedText`
+/// fragment, or `None` for the `no-*` forms, which only adjust `depth`.
+///
+/// `quotes` is the element's computed `quotes` list; an empty list (the initial value is
+/// `auto`-like via the UA stylesheet, but a genuinely empty list can still occur) falls back to
+/// emitting nothing, per the "if there is no open-quote/close-quote pair" allowance of the spec.
+pub fn resolve_quote(
+    content: &GeneratedContentInfo,
+    quotes: &[QuotePair],
+    depth: &mut QuoteDepth,
+) -> Option<String> {
+    if quotes.is_empty() {
+        match content {
+            GeneratedContentInfo::OpenQuote => depth.0 += 1,
+            GeneratedContentInfo::CloseQuote => depth.0 = depth.0.saturating_sub(1),
+            _ => {},
+        }
+        return None;
+    }
+
+    let last_pair_index = quotes.len() - 1;
+
+    match content {
+        GeneratedContentInfo::OpenQuote => {
+            let pair = &quotes[(depth.0 as usize).min(last_pair_index)];
+            depth.0 += 1;
+            Some(pair.0.clone())
+        },
+        GeneratedContentInfo::CloseQuote => {
+            depth.0 = depth.0.saturating_sub(1);
+            let pair = &quotes[(depth.0 as usize).min(last_pair_index)];
+            Some(pair.1.clone())
+        },
+        GeneratedContentInfo::NoOpenQuote => {
+            depth.0 += 1;
+            None
+        },
+        GeneratedContentInfo::NoCloseQuote => {
+            depth.0 = depth.0.saturating_sub(1);
+            None
+        },
+        _ => None,
+    }
+}
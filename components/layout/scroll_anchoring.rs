@@ -0,0 +1,121 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scroll anchoring: keeping a scroll container's visible content stable across reflows that
+//! change layout above the viewport (an image finishes loading, a web font swaps in, an ad slot
+//! expands), even though nothing the user did asked the page to scroll,
+//! <https://drafts.csswg.org/css-scroll-anchoring-1/>.
+//!
+//! The algorithm, per scroll container, has two halves that run on different reflows:
+//!
+//! 1. **Select**, after a reflow has settled: walk the container's descendant fragments in paint
+//!    order and pick an *anchor* - the deepest fragment whose border box (in the container's
+//!    `scroll`-rect coordinate space, i.e. what [`Fragment::stacking_relative_border_box`] with
+//!    `CoordinateSystem::Own` would report relative to the container) is at least partially below
+//!    the current scroll offset, and closest to the scrollport's top edge. Record that fragment's
+//!    [`OpaqueNode`] and its offset from the scrollport top as a [`ScrollAnchor`].
+//! 2. **Adjust**, on the *next* reflow: re-resolve the same node's new border-box top and compute
+//!    how far the scroll offset needs to move to keep the anchor at the same offset from the
+//!    scrollport top it was recorded at - see [`compute_scroll_adjustment`].
+//!
+//! This module implements both halves as plain functions over caller-supplied candidate data,
+//! not as a pass that walks a live fragment/flow tree itself: gathering "every descendant
+//! fragment of this scroll container, in paint order, with its `CoordinateSystem::Own`-relative
+//! border box" is a tree walk that belongs to whatever drives reflow - in a full tree, the
+//! `BlockFlow`/`InlineFlow` traversal that also computes `Overflow` - and none of
+//! `flow.rs`/`block.rs`/`inline.rs` exist in this snapshot to host it. [`select_anchor`] and
+//! [`compute_scroll_adjustment`] are the reusable, tree-shape-independent halves, ready for that
+//! walk to call once it exists.
+
+use app_units::Au;
+use euclid::default::Rect;
+
+use crate::display_list::items::OpaqueNode;
+
+/// One fragment considered as a candidate scroll anchor.
+pub struct AnchorCandidate {
+    pub node: OpaqueNode,
+    /// This fragment's border box, in the scroll container's own `scroll`-rect coordinate space
+    /// (i.e. unaffected by the container's own current scroll offset).
+    pub border_box: Rect<Au>,
+    /// Nesting depth below the scroll container; deeper wins ties when multiple candidates sit
+    /// at the same distance from the top edge.
+    pub depth: u32,
+    /// Whether this fragment is the synthesized `text-overflow` ellipsis marker
+    /// (`FragmentFlags::IS_ELLIPSIS`) - these are never valid anchors, since they're not part of
+    /// the content the page author positioned.
+    pub is_ellipsis: bool,
+    /// Whether this fragment is inside a `position: fixed` or `position: sticky` subtree - these
+    /// don't move when the container scrolls (or move independently), so anchoring to them
+    /// wouldn't stabilize anything.
+    pub in_fixed_or_sticky_subtree: bool,
+}
+
+/// A selected scroll anchor: the node to track, and how far its border-box top was from the
+/// scrollport's top edge at the moment it was selected.
+#[derive(Clone, Copy)]
+pub struct ScrollAnchor {
+    pub node: OpaqueNode,
+    pub offset_from_scroll_port_top: Au,
+}
+
+/// Selects a scroll anchor for a container currently scrolled by `scroll_offset`, from `candidates`
+/// (its descendant fragments, in any order - this doesn't depend on paint order since it
+/// considers every candidate rather than stopping at the first match).
+///
+/// Excludes ellipsis fragments and anything in a fixed/sticky subtree, keeps only fragments whose
+/// border box extends at least partially below `scroll_offset` (i.e. visible or below the fold,
+/// never purely scrolled-past content), and among those picks the one closest to the scrollport's
+/// top edge - deepest first on an exact tie, since a more deeply nested fragment is a more
+/// precise anchor than an ancestor that merely contains it.
+pub fn select_anchor(scroll_offset: Au, candidates: &[AnchorCandidate]) -> Option<ScrollAnchor> {
+    candidates
+        .iter()
+        .filter(|candidate| !candidate.is_ellipsis && !candidate.in_fixed_or_sticky_subtree)
+        .filter(|candidate| {
+            candidate.border_box.origin.y + candidate.border_box.size.height > scroll_offset
+        })
+        .min_by(|a, b| {
+            (a.border_box.origin.y - scroll_offset)
+                .abs()
+                .cmp(&(b.border_box.origin.y - scroll_offset).abs())
+                .then_with(|| b.depth.cmp(&a.depth))
+        })
+        .map(|candidate| ScrollAnchor {
+            node: candidate.node,
+            offset_from_scroll_port_top: candidate.border_box.origin.y - scroll_offset,
+        })
+}
+
+/// Computes the scroll offset delta needed to keep `anchor` at the same distance from the
+/// scrollport's top edge it was selected at, given that its border box's top (in the same
+/// coordinate space `select_anchor` was called with) has moved to `new_border_box_top`.
+///
+/// Returns `None` - suppressing any adjustment - while `user_scroll_in_progress` is set (the user
+/// is actively scrolling; anchoring must never fight an in-progress gesture), if there's no
+/// meaningful delta, or if the clamped adjustment (restricted to `scrollable_range`, the
+/// container's `(min, max)` scroll offset bounds - its `Overflow::scroll` rect's extent) would
+/// leave the offset unchanged.
+pub fn compute_scroll_adjustment(
+    anchor: &ScrollAnchor,
+    new_border_box_top: Au,
+    current_scroll_offset: Au,
+    user_scroll_in_progress: bool,
+    scrollable_range: (Au, Au),
+) -> Option<Au> {
+    if user_scroll_in_progress {
+        return None;
+    }
+
+    let desired_scroll_offset = new_border_box_top - anchor.offset_from_scroll_port_top;
+    let (min, max) = scrollable_range;
+    let clamped_scroll_offset = desired_scroll_offset.max(min).min(max);
+
+    let delta = clamped_scroll_offset - current_scroll_offset;
+    if delta == Au(0) {
+        None
+    } else {
+        Some(delta)
+    }
+}
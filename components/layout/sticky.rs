@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! `position: sticky` offset computation, <https://drafts.csswg.org/css-position-3/#sticky-pos>.
+//!
+//! A sticky fragment behaves like a relatively positioned one until its containing block would
+//! scroll its border box past one of the offsets (`top`/`right`/`bottom`/`left`) requested on its
+//! nearest scrolling ancestor, at which point it "sticks" at that offset instead of continuing to
+//! scroll with the content. This module treats that scrolling ancestor as a container
+//! abstraction, the same way [`crate::scroll_anchoring`] treats it for anchor selection: the
+//! relevant state is the ancestor's [`Overflow::scroll`](crate::fragment::Overflow::scroll) rect,
+//! inset per axis by whichever offsets are set, intersected with the fragment's containing block
+//! so a sticky box can never escape it.
+//!
+//! As with `scroll_anchoring.rs`, this is a pure, parameter-driven computation rather than a pass
+//! that walks a live fragment tree: finding "the nearest scrolling ancestor" and re-running this
+//! on every scroll/reflow is the job of whatever drives reflow and the display-list builder,
+//! neither of which exist in this snapshot (no `flow.rs`/`display_list.rs`). [`sticky_offset`] is
+//! the reusable core, ready for that pass to call once it exists - it would apply the returned
+//! offset the same way a `position: relative` offset is applied today, without re-running layout.
+
+use app_units::Au;
+use euclid::default::{Rect, Vector2D};
+
+/// The `top`/`right`/`bottom`/`left` insets requested by a `position: sticky` fragment's style.
+/// `None` on a given side means that longhand is `auto`, so it places no constraint on that axis
+/// from that side.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StickyOffsets {
+    pub top: Option<Au>,
+    pub right: Option<Au>,
+    pub bottom: Option<Au>,
+    pub left: Option<Au>,
+}
+
+/// Computes the offset, in the same coordinate space as `natural_border_box`
+/// (`CoordinateSystem::Parent`), to add to a sticky fragment's naturally-flowed position to keep
+/// it within its sticky constraints.
+///
+/// `scroll_rect` is the nearest scrolling ancestor's `Overflow::scroll` rect; `offsets` are this
+/// fragment's resolved `top`/`right`/`bottom`/`left`; `containing_block` is this fragment's
+/// containing block's border box, which the result must never place the fragment outside of even
+/// if that conflicts with a requested offset (a small containing block wins over a sticky request
+/// that can't be satisfied within it). Returns a zero vector - "stick nowhere, stay at the
+/// natural flow position" - on any axis with no offset set on either side.
+pub fn sticky_offset(
+    natural_border_box: Rect<Au>,
+    scroll_rect: Rect<Au>,
+    offsets: StickyOffsets,
+    containing_block: Rect<Au>,
+) -> Vector2D<Au> {
+    let x = clamp_axis(
+        natural_border_box.origin.x,
+        natural_border_box.size.width,
+        offsets.left.map(|left| scroll_rect.origin.x + left),
+        offsets
+            .right
+            .map(|right| scroll_rect.origin.x + scroll_rect.size.width - right),
+        containing_block.origin.x,
+        containing_block.origin.x + containing_block.size.width,
+    );
+    let y = clamp_axis(
+        natural_border_box.origin.y,
+        natural_border_box.size.height,
+        offsets.top.map(|top| scroll_rect.origin.y + top),
+        offsets
+            .bottom
+            .map(|bottom| scroll_rect.origin.y + scroll_rect.size.height - bottom),
+        containing_block.origin.y,
+        containing_block.origin.y + containing_block.size.height,
+    );
+
+    Vector2D::new(x - natural_border_box.origin.x, y - natural_border_box.origin.y)
+}
+
+/// Clamps one axis of the fragment's position: `clamp(natural_pos, constraint_min,
+/// constraint_max)`, where `constraint_min`/`constraint_max` come from whichever of the
+/// near/far-side sticky offsets are set, then further clamps the result so the fragment's border
+/// box never crosses `containing_block_near`/`containing_block_far` - the fragment's containing
+/// block always wins over an unsatisfiable sticky request. With neither constraint set,
+/// `natural_pos` is returned unchanged.
+fn clamp_axis(
+    natural_pos: Au,
+    size: Au,
+    constraint_min: Option<Au>,
+    constraint_max: Option<Au>,
+    containing_block_near: Au,
+    containing_block_far: Au,
+) -> Au {
+    if constraint_min.is_none() && constraint_max.is_none() {
+        return natural_pos;
+    }
+
+    let mut pos = natural_pos;
+    if let Some(min) = constraint_min {
+        pos = pos.max(min);
+    }
+    if let Some(max) = constraint_max {
+        pos = pos.min(max - size);
+    }
+
+    let containing_block_max = (containing_block_far - size).max(containing_block_near);
+    pos.max(containing_block_near).min(containing_block_max)
+}
@@ -0,0 +1,96 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A small, representative subset of the Unicode Line Breaking Algorithm's classes (UAX #14),
+//! enough to implement the CSS `line-break` property's strict/normal/loose/anywhere distinctions
+//! for CJK text and punctuation, <https://www.w3.org/TR/css-text-3/#line-break-property>,
+//! <https://www.unicode.org/reports/tr14/>.
+//!
+//! This is not the full UAX #14 pair table (over a thousand class-to-class rules derived from
+//! `LineBreak.txt`, which this snapshot has no Unicode data file to vendor) - just the classes
+//! and rules the CSS property's keywords actually branch on: small kana and the prolonged sound
+//! mark (class `Nonstarter`, UAX #14's `NS`/`CJ`), CJK/Latin closing brackets and punctuation
+//! that must never start a line (`ClosingPunctuation`/`Exclamation`, UAX #14's `CL`/`CP`/`EX`),
+//! and opening brackets that must never end one (`OpeningPunctuation`, UAX #14's `OP`). Anything
+//! not recognized here classifies as `Other`, which is always breakable around at every
+//! strictness level, leaving non-CJK scripts' ordinary word/space breaking untouched.
+
+/// A coarse line-breaking class, enough to drive the CSS `line-break` keywords.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineBreakClass {
+    /// `OP`: opening punctuation - must not be the last character on a line.
+    OpeningPunctuation,
+    /// `CL`/`CP`: closing punctuation and brackets - must not start a line.
+    ClosingPunctuation,
+    /// `EX`: exclamation/interrogation marks - must not start a line.
+    Exclamation,
+    /// `NS`/`CJ`: "nonstarters" - small kana, the prolonged sound mark, ideographic iteration
+    /// marks - must not start a line under any strictness except `anywhere`.
+    Nonstarter,
+    /// Everything else - ordinary breakable content.
+    Other,
+}
+
+/// How permissively adjoining characters may break, per the CSS `line-break` property.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strictness {
+    /// `line-break: strict` - the widest set of characters is protected from starting or ending
+    /// a line; this is also what `normal` falls back to in this simplified table, since the
+    /// difference between them (certain hyphens and word-joining marks) needs classes this
+    /// table doesn't distinguish.
+    Strict,
+    /// `line-break: normal` - the CSS default. Treated the same as `Strict` here; see above.
+    Normal,
+    /// `line-break: loose` - the least restrictive CJK rules: closing punctuation and
+    /// exclamation marks may start a line, though nonstarters still may not.
+    Loose,
+    /// `line-break: anywhere` - every typographic character cluster boundary is a soft-wrap
+    /// opportunity, regardless of class.
+    Anywhere,
+}
+
+/// Classifies `ch` into the subset of UAX #14 classes this module recognizes.
+pub fn classify(ch: char) -> LineBreakClass {
+    match ch {
+        '\u{3041}' | '\u{3043}' | '\u{3045}' | '\u{3047}' | '\u{3049}' | '\u{3063}' |
+        '\u{3083}' | '\u{3085}' | '\u{3087}' | '\u{308E}' | '\u{30A1}' | '\u{30A3}' |
+        '\u{30A5}' | '\u{30A7}' | '\u{30A9}' | '\u{30C3}' | '\u{30E3}' | '\u{30E5}' |
+        '\u{30E7}' | '\u{30EE}' | '\u{30F5}' | '\u{30F6}' | '\u{30FC}' | '\u{309D}' |
+        '\u{309E}' | '\u{30FD}' | '\u{30FE}' | '\u{3005}' => LineBreakClass::Nonstarter,
+
+        '(' | '[' | '{' | '\u{FF08}' | '\u{FF3B}' | '\u{FF5B}' | '\u{3008}' | '\u{300A}' |
+        '\u{300C}' | '\u{300E}' | '\u{3010}' | '\u{2018}' | '\u{201C}' => {
+            LineBreakClass::OpeningPunctuation
+        },
+
+        ')' | ']' | '}' | '\u{FF09}' | '\u{FF3D}' | '\u{FF5D}' | '\u{3009}' | '\u{300B}' |
+        '\u{300D}' | '\u{300F}' | '\u{3011}' | '\u{2019}' | '\u{201D}' | '\u{3001}' |
+        '\u{3002}' | ',' | '.' => LineBreakClass::ClosingPunctuation,
+
+        '!' | '?' | '\u{FF01}' | '\u{FF1F}' => LineBreakClass::Exclamation,
+
+        _ => LineBreakClass::Other,
+    }
+}
+
+/// Returns whether a line break is permitted between a character of class `before` and an
+/// immediately following character of class `after`, at the given `strictness`.
+pub fn is_break_allowed(before: LineBreakClass, after: LineBreakClass, strictness: Strictness) -> bool {
+    if strictness == Strictness::Anywhere {
+        return true;
+    }
+
+    if before == LineBreakClass::OpeningPunctuation {
+        // Never break right after an opening bracket/quote.
+        return false;
+    }
+
+    match after {
+        LineBreakClass::ClosingPunctuation | LineBreakClass::Exclamation => {
+            strictness == Strictness::Loose
+        },
+        LineBreakClass::Nonstarter => false,
+        LineBreakClass::OpeningPunctuation | LineBreakClass::Other => true,
+    }
+}
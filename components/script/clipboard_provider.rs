@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A seam between `DataTransfer` and the platform clipboard, so that `copy`/`cut`/`paste`
+//! `ClipboardEvent`s can populate and flush a `DataTransfer` without script having direct access
+//! to the embedder's clipboard integration.
+//!
+//! <https://w3c.github.io/clipboard-apis/>
+
+use std::collections::HashMap;
+
+/// Reads from and writes to the system clipboard.
+///
+/// Implemented once in the constellation against the real platform clipboard, and by
+/// [`DummyClipboardContext`] for headless/testing configurations where there is no system
+/// clipboard to talk to.
+pub trait ClipboardProvider {
+    /// Returns the current clipboard contents for `mime_type` (e.g. `text/plain`,
+    /// `text/uri-list`), if any.
+    fn get_text(&self, mime_type: &str) -> Option<String>;
+
+    /// Replaces the clipboard contents for `mime_type` with `contents`.
+    fn set_text(&mut self, mime_type: &str, contents: String);
+
+    /// Returns the current clipboard contents for an image/file flavor (e.g. `image/png`), as
+    /// `(filename, bytes)`, if any.
+    ///
+    /// Defaults to reporting nothing, so a platform `ClipboardProvider` that only wired up text
+    /// flavors before file flavors existed keeps compiling unchanged.
+    fn get_file(&self, mime_type: &str) -> Option<(String, Vec<u8>)> {
+        let _ = mime_type;
+        None
+    }
+
+    /// Replaces the clipboard contents for an image/file flavor with `contents`, named `name`.
+    fn set_file(&mut self, mime_type: &str, name: &str, contents: Vec<u8>) {
+        let (_, _, _) = (mime_type, name, contents);
+    }
+}
+
+/// A [`ClipboardProvider`] with no backing platform clipboard, used by headless and test runs.
+#[derive(Default)]
+pub struct DummyClipboardContext {
+    contents: HashMap<String, String>,
+    files: HashMap<String, (String, Vec<u8>)>,
+}
+
+impl DummyClipboardContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ClipboardProvider for DummyClipboardContext {
+    fn get_text(&self, mime_type: &str) -> Option<String> {
+        self.contents.get(mime_type).cloned()
+    }
+
+    fn set_text(&mut self, mime_type: &str, contents: String) {
+        self.contents.insert(mime_type.to_owned(), contents);
+    }
+
+    fn get_file(&self, mime_type: &str) -> Option<(String, Vec<u8>)> {
+        self.files.get(mime_type).cloned()
+    }
+
+    fn set_file(&mut self, mime_type: &str, name: &str, contents: Vec<u8>) {
+        self.files
+            .insert(mime_type.to_owned(), (name.to_owned(), contents));
+    }
+}
@@ -0,0 +1,336 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Delivery of CSP violation reports to a policy's `report-to`/`report-uri` endpoints.
+//!
+//! This is deliberately separate from the `SecurityPolicyViolationEvent`/`CSPViolationReportBody`
+//! DOM objects: those are JS-traceable and reflected into a global, which makes them awkward to
+//! thread through the dedup cache and the (fire-and-forget) network request built here. A
+//! [`CspViolationRecord`] is the plain, non-reflected shape both sides agree on - the CSP
+//! enforcement path fills one in, hands a clone to each DOM object's constructor, and hands the
+//! original to [`CspReportingQueue::report`] (legacy `report-uri`) or
+//! [`CspReportingQueue::report_to`] (`report-to`/Reporting API).
+//!
+//! <https://w3c.github.io/webappsec-csp/#deprecated-serialize-violation>
+//! <https://w3c.github.io/reporting/>
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use http::header::CONTENT_TYPE;
+use http::Method;
+use ipc_channel::ipc;
+use net_traits::request::{CredentialsMode, RequestBuilder, RequestMode};
+use servo_url::ServoUrl;
+
+use crate::document_loader::LoadType;
+use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::SecurityPolicyViolationEventDisposition;
+use crate::dom::document::Document;
+
+/// The data behind a single CSP violation, gathered at enforcement time.
+///
+/// Feeds both `SecurityPolicyViolationEvent` and `CSPViolationReportBody`, and is what gets
+/// serialized into the `application/csp-report` body POSTed to the policy's reporting endpoints.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CspViolationRecord {
+    pub document_url: String,
+    pub referrer: Option<String>,
+    pub blocked_url: Option<String>,
+    pub effective_directive: String,
+    pub original_policy: String,
+    pub source_file: Option<String>,
+    pub line_number: Option<u32>,
+    pub column_number: Option<u32>,
+    pub sample: Option<String>,
+    pub disposition: SecurityPolicyViolationEventDisposition,
+    pub status_code: u16,
+}
+
+impl CspViolationRecord {
+    /// <https://w3c.github.io/webappsec-csp/#deprecated-serialize-violation>, wrapped in the
+    /// `{"csp-report": {...}}` envelope that the `application/csp-report` content type of legacy
+    /// `report-uri` delivery expects.
+    fn to_csp_report_json(&self) -> String {
+        let mut members: Vec<(&str, String)> = vec![
+            ("document-uri", json_string(&self.document_url)),
+            ("referrer", json_opt_string(self.referrer.as_deref())),
+            ("violated-directive", json_string(&self.effective_directive)),
+            ("effective-directive", json_string(&self.effective_directive)),
+            ("original-policy", json_string(&self.original_policy)),
+            ("disposition", json_string(disposition_str(self.disposition))),
+            ("blocked-uri", json_opt_string(self.blocked_url.as_deref())),
+            ("status-code", self.status_code.to_string()),
+        ];
+
+        if let Some(source_file) = &self.source_file {
+            members.push(("source-file", json_string(source_file)));
+        }
+        if let Some(line_number) = self.line_number {
+            members.push(("line-number", line_number.to_string()));
+        }
+        if let Some(column_number) = self.column_number {
+            members.push(("column-number", column_number.to_string()));
+        }
+        if let Some(sample) = &self.sample {
+            members.push(("sample", json_string(sample)));
+        }
+
+        let body = members
+            .into_iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), value))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!("{{\"csp-report\":{{{}}}}}", body)
+    }
+
+    /// Build the `body` member of a Reporting API `csp-violation` report, using the camelCase
+    /// member names `CSPViolationReportBody` exposes to script rather than `report-uri`'s
+    /// hyphenated ones.
+    ///
+    /// <https://w3c.github.io/webappsec-csp/#report-to>
+    fn to_reporting_api_body_json(&self) -> String {
+        let mut members: Vec<(&str, String)> = vec![
+            ("documentURL", json_string(&self.document_url)),
+            ("referrer", json_opt_string(self.referrer.as_deref())),
+            ("blockedURL", json_opt_string(self.blocked_url.as_deref())),
+            ("effectiveDirective", json_string(&self.effective_directive)),
+            ("originalPolicy", json_string(&self.original_policy)),
+            (
+                "disposition",
+                json_string(disposition_str(self.disposition)),
+            ),
+            ("statusCode", self.status_code.to_string()),
+        ];
+
+        members.push(("sourceFile", json_opt_string(self.source_file.as_deref())));
+        members.push((
+            "lineNumber",
+            self.line_number
+                .map_or("null".to_owned(), |n| n.to_string()),
+        ));
+        members.push((
+            "columnNumber",
+            self.column_number
+                .map_or("null".to_owned(), |n| n.to_string()),
+        ));
+        members.push(("sample", json_opt_string(self.sample.as_deref())));
+
+        let body = members
+            .into_iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), value))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!("{{{}}}", body)
+    }
+
+    /// Wrap [`Self::to_reporting_api_body_json`] in a Reporting API report object: `type`,
+    /// `url`, `age` (time in milliseconds since the violation was generated - always `0` here,
+    /// since Servo delivers eagerly instead of batching over a delay window), and `user_agent`.
+    ///
+    /// <https://w3c.github.io/reporting/#dfn-report>
+    fn to_reporting_api_report_json(&self, document_url: &str, user_agent: &str) -> String {
+        let members = [
+            ("type", json_string("csp-violation")),
+            ("url", json_string(document_url)),
+            ("age", "0".to_owned()),
+            ("user_agent", json_string(user_agent)),
+            ("body", self.to_reporting_api_body_json()),
+        ];
+
+        let body = members
+            .into_iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), value))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!("{{{}}}", body)
+    }
+
+    /// A stable key for deduplicating identical reports, per
+    /// <https://w3c.github.io/reporting/#try-delivery>'s "remove older reports that are
+    /// duplicates of newer reports" intent - Servo delivers eagerly rather than batching, so
+    /// instead this is checked before a report is sent at all.
+    fn dedup_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.document_url.hash(&mut hasher);
+        self.referrer.hash(&mut hasher);
+        self.blocked_url.hash(&mut hasher);
+        self.effective_directive.hash(&mut hasher);
+        self.original_policy.hash(&mut hasher);
+        self.source_file.hash(&mut hasher);
+        self.line_number.hash(&mut hasher);
+        self.column_number.hash(&mut hasher);
+        self.sample.hash(&mut hasher);
+        self.status_code.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+fn disposition_str(disposition: SecurityPolicyViolationEventDisposition) -> &'static str {
+    match disposition {
+        SecurityPolicyViolationEventDisposition::Enforce => "enforce",
+        SecurityPolicyViolationEventDisposition::Report => "report",
+    }
+}
+
+fn json_opt_string(value: Option<&str>) -> String {
+    value.map_or_else(|| "\"\"".to_owned(), json_string)
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// The named Reporting API endpoint groups a document's policy container knows about, e.g. from
+/// a `Reporting-Endpoints` response header. A `report-to` CSP directive names one of these groups
+/// rather than an endpoint URL directly, so delivery has to go through this table to find out
+/// where reports for that group actually go.
+///
+/// <https://www.rfc-editor.org/rfc/rfc9116> (endpoint registration is out of scope for this
+/// module - this is just the lookup table delivery reads from).
+#[derive(Default)]
+pub(crate) struct ReportingEndpoints {
+    groups: HashMap<String, Vec<ServoUrl>>,
+}
+
+impl ReportingEndpoints {
+    pub(crate) fn new() -> ReportingEndpoints {
+        ReportingEndpoints::default()
+    }
+
+    pub(crate) fn register_group(&mut self, name: String, endpoints: Vec<ServoUrl>) {
+        self.groups.insert(name, endpoints);
+    }
+
+    fn endpoints_for_group(&self, name: &str) -> &[ServoUrl] {
+        self.groups.get(name).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Deduplicates and delivers CSP violation reports for a single document's policy container.
+///
+/// One instance is expected to live for as long as the document does, so that repeated
+/// violations of the same directive by the same script (a common case - e.g. an inline handler
+/// re-evaluated on every click) don't flood the `report-to`/`report-uri` endpoints with identical
+/// bodies.
+#[derive(Default)]
+pub(crate) struct CspReportingQueue {
+    sent: HashSet<u64>,
+    sent_to_reporting_api: HashSet<u64>,
+}
+
+impl CspReportingQueue {
+    pub(crate) fn new() -> CspReportingQueue {
+        CspReportingQueue::default()
+    }
+
+    /// Serialize `record` and POST it to every endpoint in `endpoints`, skipping delivery
+    /// entirely if an identical report has already been sent. Returns `true` if a delivery was
+    /// (fire-and-forget) dispatched, `false` if it was deduplicated or `endpoints` is empty.
+    pub(crate) fn report(
+        &mut self,
+        document: &Document,
+        record: &CspViolationRecord,
+        endpoints: &[ServoUrl],
+    ) -> bool {
+        if endpoints.is_empty() {
+            return false;
+        }
+
+        if !self.sent.insert(record.dedup_key()) {
+            return false;
+        }
+
+        let body = record.to_csp_report_json().into_bytes();
+
+        for endpoint in endpoints {
+            let mut header_list = http::HeaderMap::new();
+            header_list.insert(CONTENT_TYPE, "application/csp-report".parse().unwrap());
+
+            let request = RequestBuilder::new(endpoint.clone(), document.global().get_referrer())
+                .method(Method::POST)
+                .mode(RequestMode::CorsMode)
+                .body(Some(body.clone()))
+                .credentials_mode(CredentialsMode::Include)
+                .headers(header_list)
+                .origin(document.global().origin().immutable().clone());
+
+            // Fire-and-forget, like `navigator.sendBeacon()`: nothing in the Reporting API
+            // lets a page observe whether delivery succeeded.
+            let (action_sender, _) = ipc::channel().unwrap();
+            document.fetch_async(LoadType::Beacon, request, action_sender);
+        }
+
+        true
+    }
+
+    /// Build a Reporting API `csp-violation` report from `record` and deliver it to every
+    /// endpoint registered for `group` in `endpoints`, skipping delivery if an identical report
+    /// has already been sent or the group has no registered endpoints. `disposition` is honored
+    /// by `record` alone, not here: a report-only policy's violations are reported exactly like
+    /// an enforced policy's, just with `disposition: "report"` in the body.
+    ///
+    /// Returns `true` if a delivery was (fire-and-forget) dispatched, `false` if it was
+    /// deduplicated or the group resolved to no endpoints.
+    pub(crate) fn report_to(
+        &mut self,
+        document: &Document,
+        record: &CspViolationRecord,
+        group: &str,
+        endpoints: &ReportingEndpoints,
+        user_agent: &str,
+    ) -> bool {
+        let targets = endpoints.endpoints_for_group(group);
+        if targets.is_empty() {
+            return false;
+        }
+
+        if !self.sent_to_reporting_api.insert(record.dedup_key()) {
+            return false;
+        }
+
+        // A single report delivered as a one-element batch: Servo has no delay-window batching
+        // of its own, so there's nothing else to coalesce it with by the time it's queued.
+        let batch = format!(
+            "[{}]",
+            record.to_reporting_api_report_json(&record.document_url, user_agent)
+        );
+        let body = batch.into_bytes();
+
+        for endpoint in targets {
+            let mut header_list = http::HeaderMap::new();
+            header_list.insert(CONTENT_TYPE, "application/reports+json".parse().unwrap());
+
+            let request = RequestBuilder::new(endpoint.clone(), document.global().get_referrer())
+                .method(Method::POST)
+                .mode(RequestMode::CorsMode)
+                .body(Some(body.clone()))
+                .credentials_mode(CredentialsMode::Include)
+                .headers(header_list)
+                .origin(document.global().origin().immutable().clone());
+
+            let (action_sender, _) = ipc::channel().unwrap();
+            document.fetch_async(LoadType::Beacon, request, action_sender);
+        }
+
+        true
+    }
+}
@@ -28,24 +28,36 @@ use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::types::DOMException;
+use crate::timers::{MsDuration, OneshotTimerCallback};
 
 /// <https://dom.spec.whatwg.org/#abortsignal-abort-algorithms>
 #[derive(JSTraceable, MallocSizeOf)]
 pub enum AbortAlgorithm {
+    /// Reject the stream's associated promise (e.g. a reader's `closed` promise) with the
+    /// signal's abort reason.
     StreamAbort(#[ignore_malloc_size_of = "Rc"] Rc<Promise>),
     // A promise resolved with undefined
     ResolveUndefined(#[ignore_malloc_size_of = "Rc"] Rc<Promise>),
     /// <https://fetch.spec.whatwg.org/#abort-fetch>
+    ///
+    /// This snapshot has no `net` crate and no `Request`/fetch-controller type to thread an
+    /// abort reason into - there is no in-flight fetch machinery anywhere in the tree for this
+    /// variant to terminate, so `exec` does nothing for it.
     AbortFetch,
 }
 
 impl AbortAlgorithm {
-    fn exec(self) {
+    /// `reason` is the signal's abort reason, already set on `self.reason` by
+    /// [`AbortSignal::signal_abort`] before this runs.
+    fn exec(self, reason: HandleValue) {
         match self {
             Self::ResolveUndefined(promise) => {
                 promise.resolve_native(&(), CanGc::note());
             },
-            _ => {},
+            Self::StreamAbort(promise) => {
+                promise.reject(*GlobalScope::get_cx(), reason);
+            },
+            Self::AbortFetch => {},
         }
     }
 }
@@ -186,7 +198,7 @@ impl AbortSignal {
         // Step 3. For each algorithm of signal’s abort algorithms: run algorithm.
         // Step 4. Empty signal’s abort algorithms.
         for algorithm in self.abort_algorithms.borrow_mut().drain(..) {
-            algorithm.exec();
+            algorithm.exec(reason);
         }
 
         // Step 5. Fire an event named abort at signal.
@@ -212,6 +224,55 @@ impl AbortSignal {
     fn set_dependent(&self, value: bool) {
         *self.dependent.borrow_mut() = value;
     }
+
+    /// Abort with a `TimeoutError`, run by the timer task scheduled in [`Self::timeout`] once
+    /// its delay elapses.
+    #[allow(unsafe_code)]
+    fn signal_abort_with_timeout_error(&self) {
+        let cx = *GlobalScope::get_cx();
+        rooted!(in(cx) let mut reason = UndefinedValue());
+        let exception = DOMException::new(&self.global(), DOMErrorName::TimeoutError, CanGc::note());
+        unsafe {
+            exception.to_jsval(cx, reason.handle_mut());
+        }
+        self.signal_abort(reason.handle());
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-abortsignal-timeout>
+    fn timeout(global: &GlobalScope, milliseconds: u64) -> DomRoot<AbortSignal> {
+        // Step 1. Let signal be a new AbortSignal object.
+        let signal = AbortSignal::new(global, false);
+
+        // Step 2/3. Run steps after a timeout given global, "AbortSignal-timeout", milliseconds,
+        // and the following step: signal abort on signal with a new "TimeoutError" DOMException.
+        //
+        // The callback holds a weak reference: a pending timeout must not keep an otherwise-dead
+        // signal alive, so if `signal` has already been dropped when the timer fires, the abort
+        // is simply skipped rather than resurrecting it.
+        global.schedule_callback(
+            OneshotTimerCallback::AbortSignalTimeout(AbortSignalTimeoutCallback(
+                signal.downgrade(),
+            )),
+            MsDuration::new(milliseconds),
+        );
+
+        // Step 4. Return signal.
+        signal
+    }
+}
+
+/// Runs [`AbortSignal::signal_abort_with_timeout_error`] when the timer scheduled by
+/// `AbortSignal.timeout()` fires. Holds only a weak reference to the signal, so a pending
+/// timeout never keeps an otherwise-unreachable signal alive.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct AbortSignalTimeoutCallback(WeakRef<AbortSignal>);
+
+impl AbortSignalTimeoutCallback {
+    pub fn invoke(self) {
+        if let Some(signal) = self.0.root() {
+            signal.signal_abort_with_timeout_error();
+        }
+    }
 }
 
 impl AbortSignalMethods<crate::DomTypeHolder> for AbortSignal {
@@ -255,6 +316,11 @@ impl AbortSignalMethods<crate::DomTypeHolder> for AbortSignal {
         AbortSignal::create_dependent_signal(global, signals)
     }
 
+    /// <https://dom.spec.whatwg.org/#dom-abortsignal-timeout>
+    fn Timeout(global: &GlobalScope, milliseconds: u64) -> DomRoot<AbortSignal> {
+        AbortSignal::timeout(global, milliseconds)
+    }
+
     /// <https://dom.spec.whatwg.org/#dom-abortsignal-reason>
     fn Reason(&self, _cx: JSContext, mut retval: MutableHandleValue) {
         retval.set(self.reason.get());
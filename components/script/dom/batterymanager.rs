@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::CanGc;
+
+/// The battery state backing a [`BatteryManager`]: either a real reading plumbed up from the
+/// platform, or the spec's mandated default for devices/builds with no battery API to query.
+///
+/// <https://w3c.github.io/battery-status/#dfn-battery-status>
+#[derive(Clone, Copy)]
+pub struct BatteryStatus {
+    pub charging: bool,
+    pub charging_time: f64,
+    pub discharging_time: f64,
+    pub level: f64,
+}
+
+impl BatteryStatus {
+    /// The reading reported everywhere this snapshot has no platform battery source to plumb
+    /// over IPC: a fully-charged, permanently-plugged-in device.
+    ///
+    /// Exposing a real reading needs a cross-platform power-status source piped up from the
+    /// embedder/compositor layer the way gamepad input is, but neither that layer nor its IPC
+    /// plumbing exist in this snapshot, so `BatteryManager` always reports this default.
+    ///
+    /// <https://w3c.github.io/battery-status/#introduction>
+    pub fn default_status() -> BatteryStatus {
+        BatteryStatus {
+            charging: true,
+            charging_time: 0.,
+            discharging_time: f64::INFINITY,
+            level: 1.,
+        }
+    }
+}
+
+/// <https://w3c.github.io/battery-status/#batterymanager-interface>
+///
+/// Exposing this as an actual WebIDL interface (a `Promise<BatteryManager>` returned from
+/// `navigator.getBattery()`) needs the `.webidl` definitions and codegen output for
+/// `BatteryManager`, neither of which exist in this snapshot, so only the underlying DOM object
+/// and its change-event plumbing are implemented here.
+#[dom_struct]
+pub struct BatteryManager {
+    eventtarget: EventTarget,
+    charging: Cell<bool>,
+    charging_time: Cell<f64>,
+    discharging_time: Cell<f64>,
+    level: Cell<f64>,
+}
+
+impl BatteryManager {
+    fn new_inherited(status: BatteryStatus) -> BatteryManager {
+        BatteryManager {
+            eventtarget: EventTarget::new_inherited(),
+            charging: Cell::new(status.charging),
+            charging_time: Cell::new(status.charging_time),
+            discharging_time: Cell::new(status.discharging_time),
+            level: Cell::new(status.level),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, status: BatteryStatus) -> DomRoot<BatteryManager> {
+        reflect_dom_object(Box::new(BatteryManager::new_inherited(status)), global)
+    }
+
+    /// <https://w3c.github.io/battery-status/#dom-batterymanager-charging>
+    pub fn charging(&self) -> bool {
+        self.charging.get()
+    }
+
+    /// <https://w3c.github.io/battery-status/#dom-batterymanager-chargingtime>
+    pub fn charging_time(&self) -> f64 {
+        self.charging_time.get()
+    }
+
+    /// <https://w3c.github.io/battery-status/#dom-batterymanager-dischargingtime>
+    pub fn discharging_time(&self) -> f64 {
+        self.discharging_time.get()
+    }
+
+    /// <https://w3c.github.io/battery-status/#dom-batterymanager-level>
+    pub fn level(&self) -> f64 {
+        self.level.get()
+    }
+
+    /// Apply a new reading from the power-status source and fire whichever of the four change
+    /// events correspond to the attributes that actually changed, per
+    /// <https://w3c.github.io/battery-status/#battery-status-changed-algorithm>.
+    pub fn update(&self, status: BatteryStatus, can_gc: CanGc) {
+        if self.charging.replace(status.charging) != status.charging {
+            self.fire_event("chargingchange", can_gc);
+        }
+
+        if self.charging_time.replace(status.charging_time) != status.charging_time {
+            self.fire_event("chargingtimechange", can_gc);
+        }
+
+        if self.discharging_time.replace(status.discharging_time) != status.discharging_time {
+            self.fire_event("dischargingtimechange", can_gc);
+        }
+
+        if self.level.replace(status.level) != status.level {
+            self.fire_event("levelchange", can_gc);
+        }
+    }
+
+    fn fire_event(&self, name: &'static str, can_gc: CanGc) {
+        let event = Event::new(
+            &self.global(),
+            Atom::from(name),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            can_gc,
+        );
+
+        event.fire(self.upcast::<EventTarget>(), can_gc);
+    }
+}
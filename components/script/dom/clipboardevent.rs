@@ -15,7 +15,9 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::clipboard_provider::ClipboardProvider;
 use crate::dom::datatransfer::DataTransfer;
+use crate::dom::datatransferitemlist::DataTransferMode;
 use crate::dom::event::Event;
 use crate::dom::window::Window;
 
@@ -71,6 +73,89 @@ impl ClipboardEvent {
 
         Ok(event)
     }
+
+    /// Fire a `copy`, `cut`, or `paste` event at `global` whose `clipboardData` is backed by the
+    /// platform clipboard via `provider`, per the clipboard event construction algorithm.
+    ///
+    /// <https://w3c.github.io/clipboard-apis/#fire-a-clipboard-event>
+    pub fn new_for_clipboard_event(
+        global: &Window,
+        type_: DOMString,
+        provider: &dyn ClipboardProvider,
+    ) -> DomRoot<ClipboardEvent> {
+        let mode = if &*type_ == "paste" {
+            DataTransferMode::ReadOnly
+        } else {
+            DataTransferMode::ReadWrite
+        };
+
+        let clipboard_data = DataTransfer::new_for_clipboard(global, provider, mode);
+
+        let mut init = ClipboardEventInit::empty();
+        init.clipboardData = Some(clipboard_data);
+
+        ClipboardEvent::new_with_proto(global, None, type_, &init)
+    }
+
+    /// Construct, dispatch, and act on a `copy`, `cut`, or `paste` `ClipboardEvent`, per the
+    /// clipboard-apis event firing algorithm. Meant to be queued onto the
+    /// [`ClipboardEventTaskSource`](crate::task_source::clipboard::ClipboardEventTaskSource) by
+    /// whichever editing-command handler (a key command, a context-menu action) triggered the
+    /// user-initiated copy/cut/paste, so that dispatch happens as a task rather than synchronously
+    /// inline with that handler.
+    ///
+    /// `dispatch` performs the "dispatch event at target" step and reports whether the event was
+    /// canceled - this crate has no `EventTarget`/event-dispatch algorithm in this snapshot to
+    /// call directly, so the caller, which does have a real target to dispatch at, is handed the
+    /// constructed event and asked to do it itself, the same way `constellation::navigable`'s
+    /// history-traversal steps hand their result to a caller-supplied closure instead of depending
+    /// on a type this crate can't reach.
+    ///
+    /// Write access (`copy`/`cut` flushing to the platform clipboard) additionally requires
+    /// `has_transient_activation` - the real check is the async Clipboard-permission query in
+    /// <https://w3c.github.io/clipboard-apis/#clipboard-permissions>, which needs a permission
+    /// store this snapshot doesn't have; transient activation is the synchronous half of that
+    /// gate and is checked here so a script-dispatched `copy` with no corresponding user gesture
+    /// can't silently overwrite clipboard contents.
+    ///
+    /// <https://w3c.github.io/clipboard-apis/#fire-a-clipboard-event>
+    pub fn fire(
+        global: &Window,
+        type_: DOMString,
+        provider: &mut dyn ClipboardProvider,
+        has_transient_activation: bool,
+        dispatch: impl FnOnce(&ClipboardEvent) -> bool,
+    ) -> DomRoot<ClipboardEvent> {
+        // Steps 1-2. Let clipboardEvent be the result of constructing a ClipboardEvent, with
+        // clipboardData backed by the platform clipboard.
+        let event = ClipboardEvent::new_for_clipboard_event(global, type_.clone(), provider);
+
+        // Step 3. Dispatch clipboardEvent at target.
+        let canceled = dispatch(&event);
+
+        if canceled {
+            return event;
+        }
+
+        match &*type_ {
+            // Step 4 (paste). Read clipboardData's items into the editing host. There is no
+            // `Selection`/editing-host/`Element` in this snapshot to insert into, so the paste
+            // algorithm can only go as far as constructing the read-only DataTransfer above.
+            "paste" => {},
+            // Step 4 (copy/cut). Write clipboardData's data to the system clipboard, gated on
+            // transient activation per the doc comment above. `cut` additionally deletes the
+            // current selection afterwards - also blocked on the missing `Selection`/editing-host
+            // types above, so only the write half runs here.
+            "copy" | "cut" if has_transient_activation => {
+                if let Some(clipboard_data) = event.GetClipboardData() {
+                    clipboard_data.flush_to_clipboard(provider);
+                }
+            },
+            _ => {},
+        }
+
+        event
+    }
 }
 
 #[allow(non_snake_case)]
@@ -7,29 +7,61 @@ use std::rc::Rc;
 use dom_struct::dom_struct;
 use js::jsval::JSVal;
 use js::rust::HandleObject;
-use servo_atoms::Atom;
+use net_traits::blob_url_store::BlobImpl;
 
+use crate::dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
+use crate::dom::bindings::codegen::Bindings::ClipboardBinding::ClipboardUnsanitizedFormats;
 use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::{
     ClipboardItemMethods, ClipboardItemOptions, PresentationStyle,
 };
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::codegen::UnionTypes::StringOrBlob;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::import::module::SafeJSContext;
-use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::record::Record;
-use crate::dom::bindings::reflector::{
-    reflect_dom_object, reflect_dom_object_with_proto, DomObject, Reflector,
-};
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::utils::to_frozen_array;
+use crate::clipboard_provider::ClipboardProvider;
+use crate::dom::blob::Blob;
+use crate::dom::file::File;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::window::Window;
+use crate::script_runtime::CanGc;
 
+/// A single entry of a [`ClipboardItem`]'s representation list: a MIME type paired with a
+/// promise that lazily resolves to that representation's data.
+///
 /// <https://w3c.github.io/clipboard-apis/#representation>
-pub struct ClipboardItemRepresentation {
+#[derive(JSTraceable, MallocSizeOf)]
+struct ClipboardItemRepresentation {
     mime_type: String,
-    is_custom: bool,
-    data: String,
+    #[ignore_malloc_size_of = "Rc"]
+    data: Rc<Promise>,
+}
+
+/// Where a [`ClipboardItem`]'s representations come from, modeled on WebKit's dual
+/// `ClipboardItemDataSource`.
+#[derive(JSTraceable, MallocSizeOf)]
+enum ClipboardItemDataSource {
+    /// Representations supplied directly through the `ClipboardItem` constructor, each already
+    /// backed by an in-memory, already-settled promise.
+    Bindings(Vec<ClipboardItemRepresentation>),
+    /// Representations backed by the platform clipboard rather than script. `available_types` is
+    /// queried once, up front, so `Types()` doesn't need to touch the platform clipboard again;
+    /// `GetType` re-queries `provider` for the specific type requested, every call, instead of
+    /// holding an in-memory promise per type.
+    Pasteboard {
+        available_types: Vec<String>,
+        /// Types requested as unsanitized via `ClipboardUnsanitizedFormats`, per
+        /// <https://w3c.github.io/clipboard-apis/#dom-clipboardunsanitizedformats>. `GetType`
+        /// must hand back the raw platform payload for these, rather than a browser-sanitized one.
+        unsanitized_types: Vec<String>,
+        #[ignore_malloc_size_of = "Rc"]
+        #[no_trace]
+        provider: Rc<dyn ClipboardProvider>,
+    },
 }
 
 /// <https://w3c.github.io/clipboard-apis/#clipboarditem>
@@ -37,36 +69,160 @@ pub struct ClipboardItemRepresentation {
 pub struct ClipboardItem {
     reflector: Reflector,
     presentation_style: PresentationStyle,
-    #[ignore_malloc_size_of = "promises are hard"]
-    items: Record<DOMString, DOMString>,
-    //  items: Record<DOMString, Rc<Promise>>,
-    // representations: Vec<ClipboardItemRepresentation>
+    source: ClipboardItemDataSource,
 }
 
+/// Returns whether `mime_type` looks like a well-formed `type/subtype` MIME essence.
+///
+/// This tree has no `mime`-parsing crate to lean on, so this only checks the shape the
+/// constructor actually cares about (a single non-empty type and subtype separated by a slash),
+/// rather than fully validating parameters per RFC 2045.
+fn is_well_formed_mime_type(mime_type: &str) -> bool {
+    match mime_type.split_once('/') {
+        Some((type_, subtype)) => !type_.is_empty() && !subtype.is_empty(),
+        None => false,
+    }
+}
+
+/// The web-safe MIME types every `ClipboardItem` implementation is required to support.
+///
+/// <https://w3c.github.io/clipboard-apis/#mandatory-data-types-x>
+const MANDATORY_DATA_TYPES: &[&str] = &["text/plain", "text/html", "image/png"];
+
 impl ClipboardItem {
+    /// Wraps `data` in an already-fulfilled promise holding a `text/plain` `Blob`, per the
+    /// [data type]'s `(DOMString or Blob)` union - a string representation still resolves to a
+    /// `Blob`, matching Firefox's `ItemEntry::GetDataPromise` that produces an `OwningStringOrBlob`.
+    ///
+    /// [data type]: https://w3c.github.io/clipboard-apis/#dom-clipboarditemdata
+    fn promise_for_string(global: &Window, data: DOMString) -> Rc<Promise> {
+        let promise = Promise::new(&global.global());
+
+        let blob_impl =
+            BlobImpl::new_from_bytes(data.as_bytes().to_vec(), "text/plain".to_owned());
+        let file = File::new(&global.global(), blob_impl, DOMString::new());
+
+        promise.resolve_native(&file, CanGc::note());
+        promise
+    }
+
+    /// Wraps `blob` in an already-fulfilled promise holding that same `Blob`, unchanged -
+    /// `blob` already owns its bytes and declared type, so there is nothing to re-derive or
+    /// re-encode here the way [`Self::promise_for_string`] has to for a bare string.
+    fn promise_for_blob(global: &Window, blob: &Blob) -> Rc<Promise> {
+        let promise = Promise::new(&global.global());
+        promise.resolve_native(&DomRoot::from_ref(blob), CanGc::note());
+        promise
+    }
+
+    /// `items`'s declared value type is `Promise<(DOMString or Blob)>`, but this crate has no
+    /// promise-subscription mechanism (see `Navigation::run_the_navigate_event_handlers`) to wait
+    /// on a pending one - only the `(DOMString or Blob)` already unwrapped by the bindings layer
+    /// for a value that resolves synchronously (a bare string or `Blob` passed directly, which is
+    /// by far the common case) is handled here.
     #[allow(non_snake_case)]
     pub fn Constructor(
         global: &Window,
         proto: Option<HandleObject>,
-        // items: Record<DOMString, Rc<Promise>>,
-        items: Record<DOMString, DOMString>,
+        items: Record<DOMString, StringOrBlob>,
         options: &ClipboardItemOptions,
+    ) -> Fallible<DomRoot<ClipboardItem>> {
+        // Step: If items is empty, then throw a TypeError.
+        if items.is_empty() {
+            return Err(Error::Type(
+                "ClipboardItem must be constructed with at least one representation".to_owned(),
+            ));
+        }
+
+        let mut representations = Vec::with_capacity(items.len());
+
+        for (key, value) in items.iter() {
+            // Step: the representation's MIME type is the record key, unless value is a Blob
+            // with its own non-empty `type`, in which case the Blob's type wins over a
+            // disagreeing key.
+            let (mime_type, data) = match value {
+                StringOrBlob::String(data) => {
+                    (key.to_string(), Self::promise_for_string(global, data.clone()))
+                },
+                StringOrBlob::Blob(blob) => {
+                    let blob_type = blob.Type().to_string();
+                    let mime_type = if blob_type.is_empty() {
+                        key.to_string()
+                    } else {
+                        blob_type
+                    };
+                    (mime_type, Self::promise_for_blob(global, blob))
+                },
+            };
+
+            if !is_well_formed_mime_type(&mime_type) {
+                return Err(Error::Type(format!(
+                    "{} is not a well-formed MIME type",
+                    mime_type
+                )));
+            }
+
+            representations.push(ClipboardItemRepresentation { mime_type, data });
+        }
+
+        Ok(reflect_dom_object_with_proto(
+            Box::new(ClipboardItem {
+                reflector: Reflector::new(),
+                presentation_style: options.presentationStyle.clone(),
+                source: ClipboardItemDataSource::Bindings(representations),
+            }),
+            global,
+            proto,
+        ))
+    }
+
+    /// Constructs a `ClipboardItem` backed by the platform clipboard via `provider`, rather than
+    /// by script-supplied representations. `unsanitized_formats` names the types the read that
+    /// produced this item asked to receive unmodified, per `Clipboard.read()`'s
+    /// `ClipboardUnsanitizedFormats` option.
+    ///
+    /// Not yet reachable from script - this is the prerequisite data-source abstraction for a
+    /// future `navigator.clipboard.read()`, for which this snapshot has no `Clipboard`/async
+    /// permission-gated entry point yet.
+    pub fn new_for_pasteboard(
+        global: &Window,
+        available_types: Vec<String>,
+        unsanitized_formats: &ClipboardUnsanitizedFormats,
+        provider: Rc<dyn ClipboardProvider>,
     ) -> DomRoot<ClipboardItem> {
+        let unsanitized_types = unsanitized_formats
+            .formats
+            .iter()
+            .map(|format| format.to_string())
+            .collect();
+
         reflect_dom_object_with_proto(
             Box::new(ClipboardItem {
                 reflector: Reflector::new(),
                 presentation_style: PresentationStyle::Unspecified,
-                items,
-                // representations: Vec::new()
+                source: ClipboardItemDataSource::Pasteboard {
+                    available_types,
+                    unsanitized_types,
+                    provider,
+                },
             }),
             global,
-            proto,
+            None,
         )
     }
 
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-supports>
     #[allow(non_snake_case)]
-    pub fn Supports(global: &Window, type_: DOMString) -> bool {
-        false
+    pub fn Supports(_global: &Window, type_: DOMString) -> bool {
+        is_well_formed_mime_type(&type_) && MANDATORY_DATA_TYPES.contains(&&*type_)
+    }
+
+    /// Returns a promise rejected with a `NotFoundError`, for a `GetType` call whose requested
+    /// type isn't among this item's representations.
+    fn rejected_not_found(global: &GlobalScope) -> Rc<Promise> {
+        let promise = Promise::new(global);
+        promise.reject_error(Error::NotFound);
+        promise
     }
 }
 
@@ -79,13 +235,62 @@ impl ClipboardItemMethods for ClipboardItem {
 
     /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-types>
     fn Types(&self, cx: SafeJSContext) -> JSVal {
-        let items: Vec<String> = vec![];
+        let types: Vec<String> = match &self.source {
+            ClipboardItemDataSource::Bindings(representations) => representations
+                .iter()
+                .map(|representation| representation.mime_type.clone())
+                .collect(),
+            ClipboardItemDataSource::Pasteboard {
+                available_types, ..
+            } => available_types.clone(),
+        };
 
-        to_frozen_array(&items.as_slice(), cx)
+        to_frozen_array(&types.as_slice(), cx)
     }
 
     /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>
     fn GetType(&self, type_: DOMString) -> Rc<Promise> {
-        Promise::new(&self.global())
+        match &self.source {
+            ClipboardItemDataSource::Bindings(representations) => representations
+                .iter()
+                .find(|representation| representation.mime_type == *type_)
+                .map(|representation| representation.data.clone())
+                .unwrap_or_else(|| Self::rejected_not_found(&self.global())),
+            ClipboardItemDataSource::Pasteboard {
+                available_types,
+                unsanitized_types,
+                provider,
+            } => {
+                if !available_types.iter().any(|available| *available == *type_) {
+                    return Self::rejected_not_found(&self.global());
+                }
+
+                let promise = Promise::new(&self.global());
+
+                // Step: if type is listed in this item's unsanitized formats, the raw platform
+                // payload must be returned rather than a browser-sanitized version. This crate has
+                // no sanitizer anywhere (there's no HTML sanitizer in this snapshot), so a
+                // sanitized and an unsanitized read already return identical bytes here; the flag
+                // is threaded through regardless, as the signal a future sanitizer would key off.
+                let _is_unsanitized = unsanitized_types.iter().any(|format| *format == *type_);
+
+                // Re-query the platform clipboard for this specific type now, rather than caching
+                // a promise per type up front - the point of a pasteboard-backed source.
+                if let Some(contents) = provider.get_text(&type_) {
+                    let blob_impl =
+                        BlobImpl::new_from_bytes(contents.into_bytes(), type_.to_string());
+                    let file = File::new(&self.global(), blob_impl, DOMString::new());
+                    promise.resolve_native(&file, CanGc::note());
+                } else if let Some((name, bytes)) = provider.get_file(&type_) {
+                    let blob_impl = BlobImpl::new_from_bytes(bytes, type_.to_string());
+                    let file = File::new(&self.global(), blob_impl, DOMString::from(name));
+                    promise.resolve_native(&file, CanGc::note());
+                } else {
+                    promise.reject_error(Error::NotFound);
+                }
+
+                promise
+            },
+        }
     }
 }
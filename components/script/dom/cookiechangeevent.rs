@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSVal};
+use js::jsval::UndefinedValue;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::CookieChangeEventBinding::CookieChangeEventMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::cookiestore::CookieListItem;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::CanGc;
+
+/// <https://wicg.github.io/cookie-store/#cookiechangeevent>
+///
+/// `changed`/`deleted` are resolved to a JS array `JSVal` once, at construction (the arrays are
+/// plain arrays; they are not frozen), rather
+/// than lazily like [`NotRestoredReasons`][nrr]'s caches - `CookieListItem` has no
+/// `#[dom_struct]`/reflector of its own (see its doc comment in `cookiestore.rs`), so there is no
+/// `DomRoot<CookieListItem>` to re-derive the array from on a second read; the `JSVal` is all
+/// that's kept.
+///
+/// [nrr]: crate::dom::notrestoredreasons::NotRestoredReasons
+#[dom_struct]
+pub struct CookieChangeEvent {
+    event: Event,
+    #[ignore_malloc_size_of = "mozjs"]
+    changed: Heap<JSVal>,
+    #[ignore_malloc_size_of = "mozjs"]
+    deleted: Heap<JSVal>,
+}
+
+impl CookieChangeEvent {
+    fn new_inherited() -> CookieChangeEvent {
+        CookieChangeEvent {
+            event: Event::new_inherited(),
+            changed: Heap::default(),
+            deleted: Heap::default(),
+        }
+    }
+
+    /// Builds and fires a `change` event at `target` carrying `changed`/`deleted` as the
+    /// `CookieListItem` sequences the spec's change-notification algorithm collected for it.
+    #[allow(unsafe_code)]
+    pub fn fire(
+        global: &GlobalScope,
+        target: &EventTarget,
+        changed: &Vec<CookieListItem>,
+        deleted: &Vec<CookieListItem>,
+        can_gc: CanGc,
+    ) {
+        let event = reflect_dom_object(Box::new(CookieChangeEvent::new_inherited()), global);
+
+        let cx = *GlobalScope::get_cx();
+        rooted!(in(cx) let mut changed_val = UndefinedValue());
+        rooted!(in(cx) let mut deleted_val = UndefinedValue());
+        unsafe {
+            changed.to_jsval(cx, changed_val.handle_mut());
+            deleted.to_jsval(cx, deleted_val.handle_mut());
+        }
+        event.changed.set(changed_val.get());
+        event.deleted.set(deleted_val.get());
+
+        event
+            .upcast::<Event>()
+            .init_event(Atom::from("change"), false, false);
+
+        event.upcast::<Event>().fire(target, can_gc);
+    }
+}
+
+impl CookieChangeEventMethods<crate::DomTypeHolder> for CookieChangeEvent {
+    /// <https://wicg.github.io/cookie-store/#dom-cookiechangeevent-changed>
+    fn Changed(&self) -> JSVal {
+        self.changed.get()
+    }
+
+    /// <https://wicg.github.io/cookie-store/#dom-cookiechangeevent-deleted>
+    fn Deleted(&self) -> JSVal {
+        self.deleted.get()
+    }
+}
@@ -2,24 +2,33 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::rc::Rc;
-use std::time::Duration;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
 
 use cookie::{Cookie, SameSite};
 use dom_struct::dom_struct;
-use js::rust::HandleObject;
-use js::jsval::{NullValue, UndefinedValue};
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{JSContext, JSPROP_ENUMERATE, JS_NewPlainObject};
+use js::jsval::{NullValue, ObjectValue, UndefinedValue};
+use js::rust::wrappers::JS_DefineProperty;
+use js::rust::{HandleObject, MutableHandleValue};
+use serde::{Deserialize, Serialize};
 use servo_url::{ImmutableOrigin, ServoUrl};
 
 use crate::dom::bindings::codegen::Bindings::CookieStoreBinding::{
-    CookieInit, CookieStoreDeleteOptions, CookieStoreGetOptions, CookieStoreMethods,
+    CookieInit, CookieSameSite, CookieStoreDeleteOptions, CookieStoreGetOptions,
+    CookieStoreMethods,
 };
-use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, Root};
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::cookiechangeevent::CookieChangeEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
@@ -28,6 +37,151 @@ use crate::dom::window::Window;
 use crate::realms::{AlreadyInRealm, InRealm};
 use crate::script_runtime::CanGc;
 
+/// A cookie as stored in the process-wide [`cookie_jar`], after `set_a_cookie` has resolved its
+/// attributes - the shape `query_cookies` matches against and hands back to `Get`/`GetAll`.
+#[derive(Clone)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    /// The domain the cookie is stored under: the Domain attribute's value if one was given, or
+    /// the request host itself when `host_only` is set.
+    domain: String,
+    path: String,
+    same_site: Option<SameSite>,
+    secure: bool,
+    /// Always `false` for a cookie stored through `set_a_cookie` - the CookieStore API has no way
+    /// to set HttpOnly (WICG deliberately left it out, since it exists to keep a cookie away from
+    /// script). Modeled anyway so a cookie loaded via [`load_json`] that arrived over a real
+    /// `Set-Cookie` header round-trips its flag instead of silently losing it.
+    http_only: bool,
+    expires: Option<SystemTime>,
+    /// Whether no Domain attribute was supplied - <https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis-14#section-5.7>.
+    /// A host-only cookie is only ever sent back to the exact host that set it.
+    host_only: bool,
+    /// Whether this cookie is scoped to the top-level site it was set from, per
+    /// <https://wicg.github.io/cookie-store/#dom-cookieinit-partitioned>. Not yet enforced
+    /// anywhere in `query_cookies` - carried through storage so a later retrieval pass has it.
+    partitioned: bool,
+    creation_time: SystemTime,
+    last_access_time: SystemTime,
+}
+
+/// The outcome of storing a cookie via [`set_cookie`]: a fresh insert, a replacement of an entry
+/// with the same name/domain/path, or an expiry. [`notify_cookie_change`] reports the first two
+/// as `changed` and the last as `deleted`. `set_a_cookie` does not pass it on, so `Set`/`Delete`
+/// do not see it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StoreAction {
+    Inserted,
+    UpdatedExisting,
+    ExpiredExisting,
+}
+
+/// domain -> path -> name -> entry, mirroring the external `cookie_store` crate's
+/// `DomainMap`/`PathMap`/`NameMap` layering.
+type NameMap = HashMap<String, StoredCookie>;
+type PathMap = HashMap<String, NameMap>;
+type DomainMap = HashMap<String, PathMap>;
+
+/// <https://wicg.github.io/cookie-store/#dictdef-cookielistitem>
+///
+/// No `CookieStoreBinding` dictionary backs this in this snapshot (there's no WebIDL file to
+/// regenerate `CookieStoreBinding` from), so it's a plain Rust mirror of the dictionary's fields
+/// with a hand-written [`ToJSValConvertible`] impl below in place of the usual codegen one.
+/// `Get`/`Get_` only ever populate `name`/`value` (matching the spec's minimal single-item
+/// result); `GetAll`/`GetAll_` also populate `domain`/`path`/`same_site`/`secure`/`expires` when
+/// the stored cookie carries them.
+pub(crate) struct CookieListItem {
+    name: String,
+    value: String,
+    domain: Option<String>,
+    path: Option<String>,
+    same_site: Option<SameSite>,
+    secure: bool,
+    expires: Option<SystemTime>,
+}
+
+impl CookieListItem {
+    /// The minimal form `Get`/`Get_` resolve with: name and value only.
+    fn minimal(cookie: &StoredCookie) -> Self {
+        CookieListItem {
+            name: cookie.name.clone(),
+            value: cookie.value.clone(),
+            domain: None,
+            path: None,
+            same_site: None,
+            secure: false,
+            expires: None,
+        }
+    }
+
+    /// The full form `GetAll`/`GetAll_` resolve with, carrying every attribute the cookie has.
+    fn full(cookie: &StoredCookie) -> Self {
+        CookieListItem {
+            name: cookie.name.clone(),
+            value: cookie.value.clone(),
+            domain: Some(cookie.domain.clone()),
+            path: Some(cookie.path.clone()),
+            same_site: cookie.same_site,
+            secure: cookie.secure,
+            expires: cookie.expires,
+        }
+    }
+}
+
+unsafe impl ToJSValConvertible for CookieListItem {
+    unsafe fn to_jsval(&self, cx: *mut JSContext, mut rval: MutableHandleValue) {
+        rooted!(in(cx) let object = JS_NewPlainObject(cx));
+        rooted!(in(cx) let mut value = UndefinedValue());
+
+        macro_rules! define {
+            ($name:literal, $value:expr) => {
+                $value.to_jsval(cx, value.handle_mut());
+                JS_DefineProperty(
+                    cx,
+                    object.handle().into(),
+                    $name.as_ptr(),
+                    value.handle().into(),
+                    JSPROP_ENUMERATE as u32,
+                );
+            };
+        }
+
+        define!(c"name", &self.name);
+        define!(c"value", &self.value);
+        if let Some(domain) = &self.domain {
+            define!(c"domain", domain);
+        }
+        if let Some(path) = &self.path {
+            define!(c"path", path);
+        }
+        if let Some(same_site) = &self.same_site {
+            define!(c"sameSite", &same_site.to_string());
+        }
+        if self.secure {
+            define!(c"secure", &self.secure);
+        }
+        if let Some(expires) = &self.expires {
+            let millis_since_epoch = expires
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or(0);
+            define!(c"expires", &millis_since_epoch);
+        }
+
+        rval.set(ObjectValue(object.get()));
+    }
+}
+
+/// The process-wide cookie jar every `CookieStore` reads from and writes to - analogous to the
+/// single jar the real resource/network thread owns behind an IPC channel. This snapshot has no
+/// such thread (no `net_traits` crate exists here), so `query_cookies`/`set_a_cookie` talk to it
+/// directly rather than round-tripping a request/reply pair through one.
+fn cookie_jar() -> &'static Mutex<DomainMap> {
+    static JAR: OnceLock<Mutex<DomainMap>> = OnceLock::new();
+    JAR.get_or_init(|| Mutex::new(DomainMap::new()))
+}
+
 #[dom_struct]
 pub struct CookieStore {
     event: EventTarget,
@@ -35,12 +189,14 @@ pub struct CookieStore {
 
 impl CookieStore {
     pub fn new(global: &GlobalScope) -> DomRoot<CookieStore> {
-        reflect_dom_object(
+        let cookie_store = reflect_dom_object(
             Box::new(CookieStore {
                 event: EventTarget::new_inherited(),
             }),
             global,
-        )
+        );
+        register_cookie_store(&cookie_store);
+        cookie_store
     }
 
     pub fn new_with_proto(
@@ -48,15 +204,34 @@ impl CookieStore {
         proto: Option<HandleObject>,
         can_gc: CanGc,
     ) -> DomRoot<CookieStore> {
-        reflect_dom_object(
+        let cookie_store = reflect_dom_object(
             Box::new(CookieStore {
                 event: EventTarget::new_inherited(),
             }),
             global,
-        )
+        );
+        register_cookie_store(&cookie_store);
+        cookie_store
     }
 }
 
+/// Every live [`CookieStore`], so [`notify_cookie_change`] can find which ones a given cookie
+/// mutation is observable from. Entries are never removed - there's no destructor/GC-finalizer
+/// hook in this snapshot to unregister a `CookieStore` when it's collected - so this leaks one
+/// [`Trusted`] handle per store for the life of the process; acceptable for a toy model, not for
+/// a real browser session.
+fn cookie_store_registry() -> &'static Mutex<Vec<Trusted<CookieStore>>> {
+    static REGISTRY: OnceLock<Mutex<Vec<Trusted<CookieStore>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn register_cookie_store(cookie_store: &CookieStore) {
+    cookie_store_registry()
+        .lock()
+        .unwrap()
+        .push(Trusted::new(cookie_store));
+}
+
 impl CookieStoreMethods for CookieStore {
     /// <https://wicg.github.io/cookie-store/#dom-cookiestore-get>
     fn Get(&self, name: USVString) -> Fallible<Rc<Promise>> {
@@ -73,41 +248,34 @@ impl CookieStoreMethods for CookieStore {
         }
 
         // Step 4: Let url be settings’s creation URL.
-        let uri = global.creation_url().as_ref();
+        let url = global
+            .creation_url()
+            .clone()
+            .expect("settings object must have a creation URL");
 
         // Step 5: Let p be a new promise.
         let in_realm_proof = AlreadyInRealm::assert();
         let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
 
-        // Step 3: If origin is an opaque origin, then return a promise rejected with a
-        // "SecurityError" DOMException.
-        if !origin.is_tuple() {
-            promise.reject_error(Error::Security);
-
-            return Ok(promise);
-        }
-
-        // Ste 4: Let url be settings’s creation URL.
-        let url: Option<ServoUrl> = global.creation_url().clone();
-
         // Step 6: Run the following steps in parallel:
+        // This snapshot has no resource/network thread to round-trip the query through `query_cookies`
+        // - `cookie_jar` is read in place of that IPC round trip - but `p` is still resolved from
+        // the real result below rather than synchronously ahead of it.
         // Step 6.1: Let list be the results of running query cookies with url and name.
-        // https://wicg.github.io/cookie-store/#query-cookies
+        let list = query_cookies(&url, Some(&name.0));
 
         // Step 6.2: If list is failure, then reject p with a TypeError and abort these steps.
+        // (query_cookies can't fail in this implementation - matching is pure list filtering.)
 
-        // Step 6.3: If list is empty, then resolve p with null.
-        let list: Vec<String> = vec![];
-
-        if list.is_empty() {
-            promise.resolve_native(&NullValue());
+        match list.first() {
+            // Step 6.3: If list is empty, then resolve p with null.
+            None => promise.resolve_native(&NullValue()),
+            // Step 6.4: Otherwise, resolve p with the first item of list.
+            Some(cookie) => promise.resolve_native(&CookieListItem::minimal(cookie)),
         }
 
-        return Ok(promise);
-
-        // Step 6.4: Otherwise, resolve p with the first item of list.
-
         // Step 7: Return p.
+        Ok(promise)
     }
 
     /// <https://wicg.github.io/cookie-store/#dom-cookiestore-get-options>
@@ -185,11 +353,26 @@ impl CookieStoreMethods for CookieStore {
             // Step 8: Run the following steps in parallel:
         }
 
-        let list: Vec<String> = vec![];
-
-        promise.resolve_native(&list);
+        // Step 8/9: Let list be the results of running query cookies with url (settings’s
+        // creation URL, unless overridden by options["url"] above) and options["name"]. As in
+        // `Get`, this snapshot has no resource thread to round-trip the query through, so
+        // `cookie_jar` is read in place of that IPC round trip.
+        let url = match options.url {
+            Some(ref url) => ServoUrl::parse_with_base(Some(&global.api_base_url()), &url.0)
+                .expect("options[\"url\"] was parsed above"),
+            None => global
+                .creation_url()
+                .clone()
+                .expect("settings object must have a creation URL"),
+        };
+        let list = query_cookies(&url, options.name.as_ref().map(|name| name.0.as_str()));
+
+        match list.first() {
+            None => promise.resolve_native(&NullValue()),
+            Some(cookie) => promise.resolve_native(&CookieListItem::minimal(cookie)),
+        }
 
-        return Ok(promise);
+        Ok(promise)
     }
 
     /// <https://wicg.github.io/cookie-store/#dom-cookiestore-getall>
@@ -214,29 +397,23 @@ impl CookieStoreMethods for CookieStore {
         }
 
         // Step 4: Let url be settings’s creation URL.
-        let url = global.creation_url();
-
-        // Step 5: Let domain be null
-
-        // Step 6: let path be "/"
-        let path = "/";
-
-        // Step 7 let sameSite be `strict`
+        let url = global
+            .creation_url()
+            .clone()
+            .expect("settings object must have a creation URL");
 
-        // Step 8: Let partitioned be false
-        let partitioned = false;
-
-        // Step 9...
         // Step 10: Run the following steps in parallel:
-        // Step 10.1: Let r be the result of running set a cookie with url, name, value, domain,
-        // path, sameSite, and partitioned.
+        // Step 10.1: Let list be the results of running query cookies with url and name. As in
+        // `Get`, this snapshot has no resource thread to round-trip the query through, so
+        // `cookie_jar` is read in place of that IPC round trip.
+        let list = query_cookies(&url, Some(&name.0));
 
-        // https://wicg.github.io/cookie-store/#set-a-cookie
-
-        // Step 10.2: If r is failure, then reject p with a TypeError and abort these steps.
+        // Step 10.2: If list is failure, then reject p with a TypeError and abort these steps.
+        // (query_cookies can't fail in this implementation.)
 
-        // Step 10.3: Resolve p with undefined.
-        promise.resolve_native(&UndefinedValue());
+        // Step 10.3: Resolve p with list.
+        let items: Vec<CookieListItem> = list.iter().map(CookieListItem::full).collect();
+        promise.resolve_native(&items);
 
         // Step 11: Return p
         promise
@@ -244,11 +421,36 @@ impl CookieStoreMethods for CookieStore {
 
     /// <https://wicg.github.io/cookie-store/#dom-cookiestore-getall-options>
     fn GetAll_(&self, options: &CookieStoreGetOptions) -> Rc<Promise> {
+        // Step 1: Let settings be this's relevant settings object.
+        let global = self.global();
+
+        // Step 2: Let origin be settings’s origin.
+        let origin = global.origin();
+
         // Step 5: Let p be a new promise.
         let in_realm_proof = AlreadyInRealm::assert();
         let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
 
-        promise.reject_error(Error::Security);
+        // Step 3: If origin is an opaque origin, then return a promise rejected with a
+        // "SecurityError" DOMException.
+        if !origin.is_tuple() {
+            promise.reject_error(Error::Security);
+
+            return promise;
+        }
+
+        // Step 4: Let url be settings’s creation URL. Unlike `Get_`, this does not read
+        // options["url"]: the query always uses the creation URL.
+        let url = global
+            .creation_url()
+            .clone()
+            .expect("settings object must have a creation URL");
+
+        // Step 8/9: Let list be the results of running query cookies with url and options["name"].
+        let list = query_cookies(&url, options.name.as_ref().map(|name| name.0.as_str()));
+
+        let items: Vec<CookieListItem> = list.iter().map(CookieListItem::full).collect();
+        promise.resolve_native(&items);
 
         promise
     }
@@ -283,6 +485,7 @@ impl CookieStoreMethods for CookieStore {
         let path = "/";
 
         // Step 7 let sameSite be `strict`
+        let same_site = SameSite::Strict;
 
         // Step 8: Let partitioned be false
         let partitioned = false;
@@ -291,10 +494,22 @@ impl CookieStoreMethods for CookieStore {
         // Step 10: Run the following steps in parallel:
         // Step 10.1: Let r be the result of running set a cookie with url, name, value, domain,
         // path, sameSite, and partitioned.
-
-        // https://wicg.github.io/cookie-store/#set-a-cookie
+        let result = set_a_cookie(
+            url.clone().expect("settings object must have a creation URL"),
+            name.0,
+            value.0,
+            None,
+            None,
+            Some(path.to_owned()),
+            Some(same_site),
+            partitioned,
+        );
 
         // Step 10.2: If r is failure, then reject p with a TypeError and abort these steps.
+        if let Err(message) = result {
+            promise.reject_error(Error::Type(message));
+            return promise;
+        }
 
         // Step 10.3: Resolve p with undefined.
         promise.resolve_native(&UndefinedValue());
@@ -332,6 +547,11 @@ impl CookieStoreMethods for CookieStore {
         let path = "/";
 
         // Step 7 let sameSite be `strict`
+        let same_site = match options.same_site {
+            CookieSameSite::Strict => SameSite::Strict,
+            CookieSameSite::Lax => SameSite::Lax,
+            CookieSameSite::None => SameSite::None,
+        };
 
         // Step 8: Let partitioned be false
         let partitioned = false;
@@ -340,23 +560,41 @@ impl CookieStoreMethods for CookieStore {
         // Step 10: Run the following steps in parallel:
         // Step 10.1: Let r be the result of running set a cookie with url, name, value, domain,
         // path, sameSite, and partitioned.
+        //
+        // `options.expires` is a `DOMTimeStamp` - milliseconds since the Unix epoch, i.e. an
+        // absolute point in time - but `set_a_cookie` wants a duration from now, so it has to be
+        // turned into one here rather than handed through as-is (which would add an epoch-sized
+        // number of milliseconds onto `now` and land the cookie's expiry decades in the future).
+        // An `expires` that's already in the past collapses to `Duration::ZERO`, which
+        // `set_a_cookie`/`set_cookie` already treat as "expire immediately" - see
+        // `delete_a_cookie` below, which relies on the same behaviour.
+        let expires = options.expires.map(|millis| {
+            (SystemTime::UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64))
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+        });
+
         let result = set_a_cookie(
             url.as_ref().unwrap().to_owned(),
             options.name.0.clone(),
             options.value.0.clone(),
-            None,
-            None,
-            None,
-            None,
-            false,
+            expires,
+            options.domain.clone().map(|domain| domain.0),
+            Some(options.path.0.clone()),
+            Some(same_site),
+            options.partitioned,
         );
 
         // https://wicg.github.io/cookie-store/#set-a-cookie
 
         // Step 10.2: If r is failure, then reject p with a TypeError and abort these steps.
+        if let Err(message) = result {
+            promise.reject_error(Error::Type(message));
+            return promise;
+        }
 
         // Step 10.3: Resolve p with undefined.
-        promise.resolve_native(&NullValue());
+        promise.resolve_native(&UndefinedValue());
 
         // Step 11: Return p
         promise
@@ -386,6 +624,18 @@ impl CookieStoreMethods for CookieStore {
         // Step 4: Let url be settings’s creation URL.
         let url = global.creation_url();
 
+        // Step 6: Let path be "/".
+        // Step 7: Let partitioned be false.
+        // Step 8: Run the following steps in parallel: delete a cookie with url, name, null
+        // domain, path, and partitioned.
+        delete_a_cookie(
+            url.clone().expect("settings object must have a creation URL"),
+            name.0,
+            None,
+            String::from("/"),
+            false,
+        );
+
         promise.resolve_native(&NullValue());
 
         promise
@@ -415,21 +665,87 @@ impl CookieStoreMethods for CookieStore {
        // Step 4: Let url be settings’s creation URL.
        let url = global.creation_url();
 
+        // Step 6-8: Run the following steps in parallel: delete a cookie with url, name, domain,
+        // path, and partitioned.
+        delete_a_cookie(
+            url.clone().expect("settings object must have a creation URL"),
+            options.name.0.clone(),
+            options.domain.clone().map(|domain| domain.0),
+            options.path.0.clone(),
+            options.partitioned,
+        );
+
         promise.resolve_native(&UndefinedValue());
 
         promise
     }
 
-    fn GetOnchange(&self) -> Option<Rc<EventHandlerNonNull>> {
-        None
+    event_handler!(change, GetOnchange, SetOnchange);
+}
+
+/// <https://wicg.github.io/cookie-store/#query-cookies>
+fn query_cookies(url: &ServoUrl, name: Option<&str>) -> Vec<StoredCookie> {
+    let Some(host) = url.host().map(|host| host.to_string()) else {
+        return Vec::new();
+    };
+    let request_path = url.path();
+    let is_secure_url = matches!(url.scheme(), "https" | "wss");
+
+    cookie_jar()
+        .lock()
+        .unwrap()
+        .values()
+        .flat_map(|path_map| path_map.values())
+        .flat_map(|name_map| name_map.values())
+        .filter(|cookie| name.is_none_or(|name| cookie.name == name))
+        .filter(|cookie| domain_matches(&host, &cookie.domain, cookie.host_only))
+        .filter(|cookie| path_matches(request_path, &cookie.path))
+        .filter(|cookie| !cookie.secure || is_secure_url)
+        .cloned()
+        .collect()
+}
+
+/// Domain-match, from the storage model's retrieval algorithm: whether a cookie stored under
+/// `cookie_domain` should be sent back to request-host `host`. A host-only cookie only ever
+/// matches the exact host it was stored under; others also match when `host` is a subdomain of
+/// `cookie_domain` - <https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis-14#section-5.1.3>.
+fn domain_matches(host: &str, cookie_domain: &str, host_only: bool) -> bool {
+    if host == cookie_domain {
+        return true;
     }
 
-    fn SetOnchange(&self, value: Option<Rc<EventHandlerNonNull>>) {}
+    !host_only &&
+        !is_ip_address(cookie_domain) &&
+        host
+            .strip_suffix(cookie_domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
 }
 
-/// <https://wicg.github.io/cookie-store/#query-cookies>
-fn query_cookies(url: ServoUrl, name: Option<String>) -> Vec<String> {
-    vec![]
+/// Path-match - <https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis-14#section-5.1.4>.
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    if request_path == cookie_path {
+        return true;
+    }
+
+    request_path.starts_with(cookie_path) &&
+        (cookie_path.ends_with('/') || request_path.as_bytes()[cookie_path.len()] == b'/')
+}
+
+fn is_ip_address(s: &str) -> bool {
+    s.parse::<std::net::IpAddr>().is_ok()
+}
+
+/// A small, hardcoded subset of the Public Suffix List's ICANN section - enough to reject the
+/// `Domain=com`/`Domain=co.uk`-style cookie-setting attempts browsers are commonly tested
+/// against, without vendoring the real list (no `publicsuffix`/`psl` crate exists in this
+/// snapshot). Not exhaustive - see <https://publicsuffix.org/list/>.
+const PUBLIC_SUFFIXES: &[&str] = &[
+    "com", "org", "net", "edu", "gov", "mil", "int", "io", "co.uk", "org.uk", "gov.uk", "co.jp",
+    "com.au", "co.nz",
+];
+
+fn is_public_suffix(domain: &str) -> bool {
+    PUBLIC_SUFFIXES.contains(&domain)
 }
 
 /// <https://wicg.github.io/cookie-store/#set-a-cookie>
@@ -481,6 +797,10 @@ fn set_a_cookie(
     let mut cookie = Cookie::new(name, value);
 
     // Step 9: If domain is not null, then run these steps:
+    // `host_only_flag` and `resolved_domain` feed the storage model in step 19 below - a cookie
+    // stored without a Domain attribute is host-only, scoped to exactly `host`.
+    let host_only_flag = domain.is_none();
+    let mut resolved_domain = host.to_string();
     if let Some(encoded_domain) = domain {
         // Step 9.1: If domain starts with U+002E (.), then return failure.
         if encoded_domain.starts_with(".") {
@@ -489,6 +809,16 @@ fn set_a_cookie(
 
         // Step 9.2: If host does not equal domain and host does not end with U+002E (.) followed by
         // domain, then return failure.
+        if !domain_matches(&host.to_string(), &encoded_domain, false) {
+            return Err(String::from("invalid domain: does not match url host"));
+        }
+
+        // Reject a cookie scoped to a public suffix (e.g. `Domain=com`) - mirrors the additional
+        // public suffix list check the `cookie_store`/`publicsuffix` crates apply beyond the bare
+        // spec text, preventing a single cookie from being readable by every site under a suffix.
+        if is_public_suffix(&encoded_domain) {
+            return Err(String::from("invalid domain: domain is a public suffix"));
+        }
 
         // Step 9.3: Let encodedDomain be the result of UTF-8 encoding domain.
 
@@ -500,11 +830,17 @@ fn set_a_cookie(
         }
 
         // Step 9.5: Append `Domain`/encodedDomain to attributes.
+        resolved_domain = encoded_domain;
     }
 
     // Step 10: If expires is given, then append `Expires`/expires (date serialized) to attributes.
+    let now = SystemTime::now();
+    if let Some(duration) = expires {
+        cookie.set_expires(cookie::Expiration::DateTime((now + duration).into()));
+    }
 
     // Step 11: If path does not start with U+002F (/), then return failure.
+    let mut resolved_path = String::from("/");
     if let Some(mut path) = path {
         if !path.starts_with("/") {
             return Err(String::from("invalid path: must begin with (/)"));
@@ -522,61 +858,287 @@ fn set_a_cookie(
             return Err(String::from("invalid path: maximum length exceeded"));
         }
 
+        resolved_path = path.clone();
         cookie.set_path(path);
     }
 
     // Step 15: Append `Path`/encodedPath to attributes.
 
-    // Step 16: Append `Secure`/`` to attributes.
+    // Step 16: Append `Secure`/`` to attributes. CookieStore marks every cookie it sets Secure
+    // unconditionally, regardless of the request's own scheme.
+    cookie.set_secure(true);
 
     // Step 17: Switch on sameSite:
     match same_site {
-        Some(SameSite::None) => {}
-        Some(SameSite::Strict) => {}
-        Some(SameSite::Lax) => {},
-        _ => {}
+        Some(SameSite::None) => {
+            // A SameSite=None cookie must also be Secure - always true here per step 16, but this
+            // keeps the CookieStore-specific rule explicit rather than relying on it silently.
+            if cookie.secure() != Some(true) {
+                return Err(String::from("SameSite=None requires Secure"));
+            }
+            cookie.set_same_site(SameSite::None);
+        },
+        Some(SameSite::Strict) => cookie.set_same_site(SameSite::Strict),
+        Some(SameSite::Lax) => cookie.set_same_site(SameSite::Lax),
+        None => {},
     }
 
     // Step 18: If partitioned is true, Append `Partitioned`/`` to attributes.
+    // (Carried through to the stored cookie below rather than onto `cookie` itself - `Partitioned`
+    // has no effect on the wire format `cookie::Cookie` serializes, only on which jar partition a
+    // later retrieval pass would read it back from.)
 
     // Step 19: Perform the steps defined in Cookies § Storage Model for when the user agent
     // "receives a cookie" with url as request-uri, encodedName as cookie-name, encodedValue as
     // cookie-value, and attributes as cookie-attribute-list.
     // https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis-14#name-storage-model
-    {
-        // Create a new cookie with name cookie-name, value cookie-value. Set the creation-time and
-        // the last-access-time to the current date and time.
-        // let mut cookie = Cookie::new(name, value);
-
-        
-
-    }
+    set_cookie(StoredCookie {
+        name: cookie.name().to_string(),
+        value: cookie.value().to_string(),
+        domain: resolved_domain,
+        path: resolved_path,
+        same_site,
+        secure: cookie.secure().unwrap_or(false),
+        http_only: false,
+        expires: expires.map(|duration| now + duration),
+        host_only: host_only_flag,
+        partitioned,
+        creation_time: now,
+        last_access_time: now,
+    });
 
     // Step 20: Return success.
     Ok(())
 }
 
-/// <https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis-14#name-storage-model>
-fn set_cookie() {}
-
-/// <https://wicg.github.io/cookie-store/#delete-a-cookie>
-fn delete_a_cookie(url: ServoUrl, name: String, domain: String, path: String, partitioned: bool) {
-    // Step 1: If path is not null, then run these steps:
-    if !path.is_empty() {
-        // Step 1.1: If path does not start with U+002F (/), then return failure.
+/// Implements "when the user agent receives a cookie" -
+/// <https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis-14#name-storage-model>.
+///
+/// If `cookie.expires` is already in the past, any existing entry with the same name/domain/path
+/// is removed instead of the cookie being stored - this is how `delete_a_cookie` deletes a cookie
+/// in practice (by setting an already-expired `expires`), and how an expired `Set-Cookie` is
+/// expected to behave regardless. Otherwise, a cookie with the same name/domain/path that already
+/// exists has its creation-time preserved (only `last-access-time` and the rest of the attributes
+/// move forward); a genuinely new one gets both timestamps set to now.
+fn set_cookie(mut cookie: StoredCookie) -> StoreAction {
+    let action = {
+        let mut jar = cookie_jar().lock().unwrap();
+        let name_map = jar
+            .entry(cookie.domain.clone())
+            .or_default()
+            .entry(cookie.path.clone())
+            .or_default();
+
+        if cookie.expires.is_some_and(|expires| expires <= cookie.creation_time) {
+            name_map.remove(&cookie.name);
+            StoreAction::ExpiredExisting
+        } else {
+            let action = match name_map.get(&cookie.name) {
+                Some(existing) => {
+                    cookie.creation_time = existing.creation_time;
+                    StoreAction::UpdatedExisting
+                },
+                None => StoreAction::Inserted,
+            };
+            name_map.insert(cookie.name.clone(), cookie.clone());
+            action
+        }
+    };
 
-        // Step 1.2: If path does not end with U+002F (/), then append U+002F (/) to path.
-    }
+    // The jar's lock is released above before notifying observers - firing `onchange` runs
+    // arbitrary author JS, which must never happen while still holding it (a handler that itself
+    // calls `cookieStore.set(...)` would deadlock re-entering `set_cookie`).
+    notify_cookie_change(&cookie, action);
 
-    // Step 2: Let expires be the earliest representable date represented as a timestamp.
+    action
+}
 
-    // Step 3: Let value be the empty string.
+/// The observer half of <https://wicg.github.io/cookie-store/#set-a-cookie> step 19 (and
+/// `delete_a_cookie`'s equivalent deletion): tells every live [`CookieStore`] this mutation is
+/// observable from - one whose relevant global's creation URL domain-matches and path-matches
+/// `cookie` - by firing `change` at it with a single-item `changed` or `deleted` array naming
+/// this cookie.
+///
+/// This fires synchronously and individually from inside `set_cookie`, rather than being queued
+/// on the relevant global's task queue and batched with other simultaneous changes into one
+/// `change` event the way the spec's own change-notification algorithm does - there's no
+/// `GlobalScope` task-queue/task-source API reachable from this file in this snapshot (no
+/// `globalscope.rs` module exists here) to queue onto. A script that calls `cookieStore.set()`
+/// twice in the same task therefore observes two separate `change` events instead of one batched
+/// one; this is a known simplification, not a deliberate reading of the spec.
+fn notify_cookie_change(cookie: &StoredCookie, action: StoreAction) {
+    let item = CookieListItem::full(cookie);
+    let (changed, deleted) = match action {
+        StoreAction::Inserted | StoreAction::UpdatedExisting => (vec![item], vec![]),
+        StoreAction::ExpiredExisting => (vec![], vec![item]),
+    };
+
+    let stores: Vec<Trusted<CookieStore>> = cookie_store_registry().lock().unwrap().clone();
+    for trusted_cookie_store in &stores {
+        let cookie_store = trusted_cookie_store.root();
+        let Some(url) = cookie_store.global().creation_url().clone() else {
+            continue;
+        };
+        let Some(host) = url.host().map(|host| host.to_string()) else {
+            continue;
+        };
+        if !domain_matches(&host, &cookie.domain, cookie.host_only) ||
+            !path_matches(url.path(), &cookie.path)
+        {
+            continue;
+        }
 
-    // Step 4: Let sameSite be "strict".
-    let same_site = SameSite::Strict;
+        CookieChangeEvent::fire(
+            &cookie_store.global(),
+            cookie_store.upcast::<EventTarget>(),
+            &changed,
+            &deleted,
+            CanGc::note(),
+        );
+    }
+}
 
+/// <https://wicg.github.io/cookie-store/#delete-a-cookie>
+fn delete_a_cookie(url: ServoUrl, name: String, domain: Option<String>, path: String, partitioned: bool) {
+    // Steps 2-4: an expiry of the earliest representable date, an empty value, and sameSite
+    // strict.
+    //
+    // `Some(Duration::ZERO)` resolves to `now` in `set_a_cookie`, which `set_cookie` treats as
+    // already expired (`expires <= creation_time`) and removes rather than stores - there's no
+    // need to represent "the earliest representable date" literally when anything not in the
+    // future gets the same treatment.
+    //
     // Step 5: Return the results of running set a cookie with url, name, value, expires, domain,
     // path, sameSite, and partitioned.
+    let _ = set_a_cookie(
+        url,
+        name,
+        String::new(),
+        Some(Duration::ZERO),
+        domain,
+        Some(path),
+        Some(SameSite::Strict),
+        partitioned,
+    );
+}
 
+/// A serde-friendly mirror of [`StoredCookie`], used by [`save_json`]/[`load_json`] to persist the
+/// cookie jar across process restarts - analogous to the `save_json`/`load_json` round trip the
+/// external `cookie_store`/`ureq` jars offer over their own jars. Plain `String`/`u64` fields
+/// stand in for [`SameSite`] and [`SystemTime`], neither of which this snapshot derives
+/// `serde::Serialize`/`Deserialize` for.
+///
+/// `partitioned` is deliberately not carried across the round trip - a partitioned cookie is
+/// scoped to the top-level site that was active when it was set, which this jar doesn't record,
+/// so there'd be nothing correct to restore it against.
+#[derive(Clone, Deserialize, Serialize)]
+struct SerializedCookie {
+    name: String,
+    value: String,
+    domain: String,
+    path: String,
+    same_site: Option<String>,
+    secure: bool,
+    http_only: bool,
+    /// Milliseconds since the Unix epoch; `None` for a session cookie (no Expires attribute).
+    expires: Option<u64>,
+    host_only: bool,
+    creation_time: u64,
+    last_access_time: u64,
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
 
+fn system_time_from_millis(millis: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+impl From<&StoredCookie> for SerializedCookie {
+    fn from(cookie: &StoredCookie) -> Self {
+        SerializedCookie {
+            name: cookie.name.clone(),
+            value: cookie.value.clone(),
+            domain: cookie.domain.clone(),
+            path: cookie.path.clone(),
+            same_site: cookie.same_site.map(|same_site| same_site.to_string()),
+            secure: cookie.secure,
+            http_only: cookie.http_only,
+            expires: cookie.expires.map(millis_since_epoch),
+            host_only: cookie.host_only,
+            creation_time: millis_since_epoch(cookie.creation_time),
+            last_access_time: millis_since_epoch(cookie.last_access_time),
+        }
+    }
+}
+
+impl From<SerializedCookie> for StoredCookie {
+    fn from(cookie: SerializedCookie) -> Self {
+        StoredCookie {
+            name: cookie.name,
+            value: cookie.value,
+            domain: cookie.domain,
+            path: cookie.path,
+            same_site: cookie.same_site.and_then(|same_site| match same_site.as_str() {
+                "Strict" => Some(SameSite::Strict),
+                "Lax" => Some(SameSite::Lax),
+                "None" => Some(SameSite::None),
+                _ => None,
+            }),
+            secure: cookie.secure,
+            http_only: cookie.http_only,
+            partitioned: false,
+            expires: cookie.expires.map(system_time_from_millis),
+            host_only: cookie.host_only,
+            creation_time: system_time_from_millis(cookie.creation_time),
+            last_access_time: system_time_from_millis(cookie.last_access_time),
+        }
+    }
+}
+
+/// Writes every non-session (persistent - has an Expires) cookie in the jar to `writer` as JSON,
+/// analogous to the `cookie_store`/`ureq` jars' `save_json`. Session cookies (no Expires) are
+/// deliberately left out: their lifetime is the process that set them, not disk.
+///
+/// Nothing in this snapshot calls this yet - there's no shutdown hook to drive it from - but it's
+/// the piece [`load_json`] round-trips against.
+pub(crate) fn save_json<W: Write>(writer: W) -> serde_json::Result<()> {
+    let cookies: Vec<SerializedCookie> = cookie_jar()
+        .lock()
+        .unwrap()
+        .values()
+        .flat_map(|path_map| path_map.values())
+        .flat_map(|name_map| name_map.values())
+        .filter(|cookie| cookie.expires.is_some())
+        .map(SerializedCookie::from)
+        .collect();
+
+    serde_json::to_writer(writer, &cookies)
+}
+
+/// Rebuilds the domain -> path -> name jar from `reader`'s JSON, as written by [`save_json`].
+/// Skips any entry whose Expires is already in the past, the same as `set_cookie` would have
+/// expired it immediately had it been set fresh rather than loaded.
+pub(crate) fn load_json<R: Read>(reader: R) -> serde_json::Result<()> {
+    let serialized_cookies: Vec<SerializedCookie> = serde_json::from_reader(reader)?;
+    let now = SystemTime::now();
+
+    let mut jar = cookie_jar().lock().unwrap();
+    for serialized_cookie in serialized_cookies {
+        let cookie = StoredCookie::from(serialized_cookie);
+        if cookie.expires.is_some_and(|expires| expires <= now) {
+            continue;
+        }
+
+        jar.entry(cookie.domain.clone())
+            .or_default()
+            .entry(cookie.path.clone())
+            .or_default()
+            .insert(cookie.name.clone(), cookie);
+    }
+
+    Ok(())
 }
@@ -0,0 +1,111 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::jsval::NullValue;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::passwordcredential::{PasswordCredential, PasswordCredentialData};
+use crate::dom::promise::Promise;
+use crate::script_runtime::CanGc;
+
+/// <https://w3c.github.io/webappsec-credential-management/#credentialscontainer>
+///
+/// Exposing this as `navigator.credentials` needs the `.webidl` definitions and codegen output
+/// for `CredentialsContainer`/`CredentialRequestOptions`/`CredentialCreationOptions`, none of
+/// which exist in this snapshot, so it's surfaced as a plain method on `Navigator` instead (see
+/// `navigator.rs`).
+///
+/// Routing `get()`/`store()` to a real platform credential store also needs a new embedder-layer
+/// IPC message, alongside the existing permission-prompt plumbing, that doesn't exist in this
+/// snapshot either. In its place, this holds a single in-process slot: `store()` fills it and
+/// `get(true)` reads it back, so a `store()` followed by a `get()` on the same container returns
+/// the stored credential, even though nothing is actually persisted or shared with the platform's
+/// autofill store.
+#[dom_struct]
+pub struct CredentialsContainer {
+    reflector_: Reflector,
+    stored_password_credential: DomRefCell<Option<DomRoot<PasswordCredential>>>,
+}
+
+impl CredentialsContainer {
+    fn new_inherited() -> CredentialsContainer {
+        CredentialsContainer {
+            reflector_: Reflector::new(),
+            stored_password_credential: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CredentialsContainer> {
+        reflect_dom_object(Box::new(CredentialsContainer::new_inherited()), global)
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-get>
+    ///
+    /// `password` stands in for a `CredentialRequestOptions` dictionary restricted to the one
+    /// member this snapshot supports (`password: true`), the way `VibrationPattern` stands in
+    /// for `Vibrate`'s union argument in `navigator.rs`.
+    pub fn get(&self, password: bool) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), CanGc::note());
+
+        // Only the `password` credential kind is implemented; anything else (federated,
+        // public-key, etc.) isn't a kind this snapshot can satisfy, so feature detection should
+        // see it rejected rather than silently resolving with null.
+        if !password {
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        }
+
+        match &*self.stored_password_credential.borrow() {
+            Some(credential) => promise.resolve_native(credential),
+            None => promise.resolve_native(&NullValue()),
+        }
+
+        promise
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-store>
+    pub fn store(&self, credential: &PasswordCredential) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), CanGc::note());
+
+        *self.stored_password_credential.borrow_mut() = Some(DomRoot::from_ref(credential));
+        promise.resolve_native(credential);
+
+        promise
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-create>
+    ///
+    /// `password_data` stands in for `CredentialCreationOptions`, again restricted to the one
+    /// credential kind this snapshot supports.
+    pub fn create(&self, password_data: Option<PasswordCredentialData>) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), CanGc::note());
+
+        let Some(data) = password_data else {
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        };
+
+        let credential = PasswordCredential::new(&self.global(), data);
+        promise.resolve_native(&credential);
+
+        promise
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-preventsilentaccess>
+    ///
+    /// There's no silent-mediation gate to flip in this snapshot (see the interface doc comment
+    /// above), so this is a no-op that always resolves, which is within what the spec allows.
+    pub fn prevent_silent_access(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global(), CanGc::note());
+        promise.resolve_native(&());
+        promise
+    }
+}
@@ -4,70 +4,119 @@
 
 use dom_struct::dom_struct;
 
+use crate::csp_reporting::CspViolationRecord;
 use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::CSPViolationReportBodyMethods;
 use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::SecurityPolicyViolationEventDisposition;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::reportbody::ReportBody;
 
 /// <https://w3c.github.io/webappsec-csp/#cspviolationreportbody>
 #[dom_struct]
 pub struct CSPViolationReportBody {
     report_body: ReportBody,
+    document_url: USVString,
+    referrer: Option<USVString>,
+    blocked_url: Option<USVString>,
+    effective_directive: DOMString,
+    original_policy: DOMString,
+    source_file: Option<USVString>,
+    line_number: Option<u32>,
+    column_number: Option<u32>,
+    sample: Option<DOMString>,
+    disposition: SecurityPolicyViolationEventDisposition,
+    status_code: u16,
+}
+
+impl CSPViolationReportBody {
+    fn new_inherited(record: &CspViolationRecord) -> CSPViolationReportBody {
+        CSPViolationReportBody {
+            report_body: ReportBody::new_inherited(),
+            document_url: USVString::from(record.document_url.clone()),
+            referrer: record.referrer.clone().map(USVString::from),
+            blocked_url: record.blocked_url.clone().map(USVString::from),
+            effective_directive: DOMString::from(record.effective_directive.clone()),
+            original_policy: DOMString::from(record.original_policy.clone()),
+            source_file: record.source_file.clone().map(USVString::from),
+            line_number: record.line_number,
+            column_number: record.column_number,
+            sample: record.sample.clone().map(DOMString::from),
+            disposition: record.disposition,
+            status_code: record.status_code,
+        }
+    }
+
+    /// Construct the `body` of a `Report` handed to a `ReportingObserver` for a CSP violation,
+    /// from the enforcement-time [`CspViolationRecord`].
+    ///
+    /// `source_file`/`line_number`/`column_number`/`sample` come from `record`, which for an
+    /// inline-script or `eval` violation is expected to have already been filled in from the
+    /// topmost frame of the JS stack active when the violation was detected (this snapshot has no
+    /// stack-capture utility of its own to do that walk, so it's the enforcement path's job, not
+    /// this constructor's).
+    pub fn new(global: &GlobalScope, record: &CspViolationRecord) -> DomRoot<CSPViolationReportBody> {
+        reflect_dom_object(
+            Box::new(CSPViolationReportBody::new_inherited(record)),
+            global,
+        )
+    }
 }
 
 impl CSPViolationReportBodyMethods for CSPViolationReportBody {
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-documenturl>
     fn DocumentURL(&self) -> USVString {
-        USVString::from("".to_owned())
+        self.document_url.clone()
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-referrer>
     fn GetReferrer(&self) -> Option<USVString> {
-        None
+        self.referrer.clone()
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-blockedurl>
     fn GetBlockedURL(&self) -> Option<USVString> {
-        None
+        self.blocked_url.clone()
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-effectivedirective>
     fn EffectiveDirective(&self) -> DOMString {
-        DOMString::new()
+        self.effective_directive.clone()
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-originalpolicy>
     fn OriginalPolicy(&self) -> DOMString {
-        DOMString::new()
+        self.original_policy.clone()
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-sourcefile>
     fn GetSourceFile(&self) -> Option<USVString> {
-        None
+        self.source_file.clone()
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-sample>
     fn GetSample(&self) -> Option<DOMString> {
-        None
+        self.sample.clone()
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-disposition>
     fn Disposition(&self) -> SecurityPolicyViolationEventDisposition {
-        SecurityPolicyViolationEventDisposition::Report
+        self.disposition
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-statuscode>
     fn StatusCode(&self) -> u16 {
-        0
+        self.status_code
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-linenumber>
     fn GetLineNumber(&self) -> Option<u32> {
-        None
+        self.line_number
     }
 
     /// <https://w3c.github.io/webappsec-csp/#ref-for-dom-cspviolationreportbody-columnnumber>
     fn GetColumnNumber(&self) -> Option<u32> {
-        None
+        self.column_number
     }
 }
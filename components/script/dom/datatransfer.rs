@@ -8,6 +8,13 @@ use dom_struct::dom_struct;
 use js::jsapi::Heap;
 use js::jsval::JSVal;
 use js::rust::HandleObject;
+use net_traits::blob_url_store::BlobImpl;
+
+use script_traits::drag_data_store::{
+    DragDataStore, DragDataStoreBitmap, DragDataStoreEntry, DragDataStoreEntryKind,
+    DragDataStoreFile, DragDataStoreItem, DragDataStoreItemValue, DragDropEffect,
+    DragEffectAllowed,
+};
 
 use crate::dom::bindings::codegen::Bindings::DataTransferBinding::{
     DataTransferMethods, DropEffect, EffectAllowed,
@@ -17,20 +24,82 @@ use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject,
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::utils::to_frozen_array;
-use crate::dom::datatransferitem::DataTransferItemValue;
+use crate::dom::datatransferitem::{
+    DataTransferItem, DataTransferItemValue, FileSystemEntryHandle, FileSystemEntryKind,
+};
 use crate::dom::datatransferitemlist::{DataTransferItemList, DataTransferMode};
 use crate::dom::element::Element;
+use crate::dom::file::File;
 use crate::dom::filelist::FileList;
-use crate::dom::htmlimageelement::HTMLImageElement;
 use crate::dom::window::Window;
+use crate::clipboard_provider::ClipboardProvider;
 use crate::script_runtime::JSContext as SafeJSContext;
 use crate::test::DomRefCell;
 
+impl From<DropEffect> for DragDropEffect {
+    fn from(value: DropEffect) -> DragDropEffect {
+        match value {
+            DropEffect::None => DragDropEffect::None,
+            DropEffect::Copy => DragDropEffect::Copy,
+            DropEffect::Link => DragDropEffect::Link,
+            DropEffect::Move => DragDropEffect::Move,
+        }
+    }
+}
+
+impl From<EffectAllowed> for DragEffectAllowed {
+    fn from(value: EffectAllowed) -> DragEffectAllowed {
+        match value {
+            EffectAllowed::None => DragEffectAllowed::None,
+            EffectAllowed::Copy => DragEffectAllowed::Copy,
+            EffectAllowed::CopyLink => DragEffectAllowed::CopyLink,
+            EffectAllowed::CopyMove => DragEffectAllowed::CopyMove,
+            EffectAllowed::Link => DragEffectAllowed::Link,
+            EffectAllowed::LinkMove => DragEffectAllowed::LinkMove,
+            EffectAllowed::Move => DragEffectAllowed::Move,
+            EffectAllowed::All => DragEffectAllowed::All,
+            EffectAllowed::Uninitialized => DragEffectAllowed::Uninitialized,
+        }
+    }
+}
+
+/// Text MIME types synchronized with the platform clipboard by
+/// [`DataTransfer::new_for_clipboard`] and [`DataTransfer::flush_to_clipboard`].
+const CLIPBOARD_MIME_TYPES: &[&str] = &["text/plain", "text/uri-list"];
+
+/// Image/file MIME types read from the platform clipboard on `paste` by
+/// [`DataTransfer::new_for_clipboard`], backing `File` items rather than string items.
+/// [`DataTransfer::flush_to_clipboard`] does not write them back.
+const CLIPBOARD_FILE_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif"];
+
+/// The `dropEffect` values `effectAllowed` permits, per the table in the "drag-and-drop
+/// processing model".
+///
+/// <https://html.spec.whatwg.org/multipage/dnd.html#drag-and-drop-processing-model>
+fn effects_permitted_by(effect_allowed: EffectAllowed) -> &'static [DropEffect] {
+    match effect_allowed {
+        EffectAllowed::None => &[],
+        EffectAllowed::Copy => &[DropEffect::Copy],
+        EffectAllowed::CopyLink => &[DropEffect::Copy, DropEffect::Link],
+        EffectAllowed::CopyMove => &[DropEffect::Copy, DropEffect::Move],
+        EffectAllowed::Link => &[DropEffect::Link],
+        EffectAllowed::LinkMove => &[DropEffect::Link, DropEffect::Move],
+        EffectAllowed::Move => &[DropEffect::Move],
+        EffectAllowed::All | EffectAllowed::Uninitialized => {
+            &[DropEffect::Copy, DropEffect::Link, DropEffect::Move]
+        },
+    }
+}
+
 // Optional UI information when a DataTransfer object is associated with drag & drop event
 // <https://html.spec.whatwg.org/multipage/#drag-data-store-bitmap>
 #[derive(JSTraceable, MallocSizeOf, PartialEq)]
 struct DataTransferBitmap {
-    image: DomRoot<HTMLImageElement>,
+    // Per spec, any element can be used as drag feedback, not only `HTMLImageElement`. Actually
+    // rasterizing `element`'s current box into an offscreen surface happens on the compositor
+    // side, which this snapshot's layout crate has no entry point for yet; for now we only carry
+    // the element and hotspot that the real drag feedback path would paint from.
+    element: DomRoot<Element>,
     image_x: i32,
     image_y: i32,
 }
@@ -85,6 +154,194 @@ impl DataTransfer {
     pub fn Constructor(global: &Window, proto: Option<HandleObject>) -> DomRoot<DataTransfer> {
         DataTransfer::new_with_proto(global, proto)
     }
+
+    /// Construct a `DataTransfer` backed by the platform clipboard, for use as a
+    /// `ClipboardEvent`'s `clipboardData`.
+    ///
+    /// For `paste`, `provider` is read into a fresh, `ReadOnly` item list. For `copy`/`cut`, an
+    /// empty, `ReadWrite` item list is returned instead; scripts populate it via `setData()`, and
+    /// the caller is expected to call [`DataTransfer::flush_to_clipboard`] afterwards to write it
+    /// back out.
+    ///
+    /// <https://w3c.github.io/clipboard-apis/#clipboard-event-construct-a-datatransfer>
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_for_clipboard(
+        global: &Window,
+        provider: &dyn ClipboardProvider,
+        mode: DataTransferMode,
+    ) -> DomRoot<DataTransfer> {
+        let files = FileList::new(global, Vec::new());
+        let items = DataTransferItemList::new_for_clipboard(global, &[], mode);
+
+        if mode == DataTransferMode::ReadOnly {
+            for mime_type in CLIPBOARD_MIME_TYPES {
+                if let Some(contents) = provider.get_text(mime_type) {
+                    let _ = items.add_string(
+                        DOMString::from(contents),
+                        DOMString::from(*mime_type),
+                    );
+                }
+            }
+
+            // Lazily-materialized in the sense that nothing walks the clipboard's file flavors
+            // until a `paste` is actually fired - `ClipboardEvent.clipboardData` is only ever
+            // handed to script once, at event dispatch, so there's no reason to defer further
+            // than that.
+            for mime_type in CLIPBOARD_FILE_MIME_TYPES {
+                if let Some((name, bytes)) = provider.get_file(mime_type) {
+                    let blob_impl = BlobImpl::new_from_bytes(bytes, (*mime_type).to_owned());
+                    let file = File::new(&global.global(), blob_impl, DOMString::from(name));
+
+                    items.add_file(file, DOMString::from(*mime_type));
+                }
+            }
+        }
+
+        let data_transfer = DataTransfer::new_inherited(files, items);
+
+        reflect_dom_object_with_proto(Box::new(data_transfer), global, None)
+    }
+
+    /// Write this `DataTransfer`'s text entries back out to the platform clipboard, for `copy`
+    /// and `cut` handling.
+    ///
+    /// File items (e.g. a pasted-then-re-copied image) are deliberately not flushed here: a
+    /// `Blob`'s bytes are only readable asynchronously, via `FileReader`, and `set_file` needs
+    /// them synchronously to hand off to [`ClipboardProvider::set_file`]. That needs this method
+    /// to become async (or to take an in-flight read as a parameter) before file round-tripping
+    /// through the clipboard can work; reading text synchronously off the item list, as done
+    /// below, has no such obstacle.
+    pub fn flush_to_clipboard(&self, provider: &mut dyn ClipboardProvider) {
+        for mime_type in CLIPBOARD_MIME_TYPES {
+            let value = self
+                .item_list
+                .get_string_value_by_format(DOMString::from(*mime_type));
+
+            if let Some(DataTransferItemValue::String(value)) = value {
+                provider.set_text(mime_type, value.to_string());
+            }
+        }
+    }
+
+    /// Snapshot this `DataTransfer`'s drag data store for `dragstart`, in a form that can cross
+    /// the IPC boundary to the constellation.
+    ///
+    /// Dragged files serialize as blob-backed handles (see [`DragDataStoreFile::blob_handle`])
+    /// rather than inline bytes, so large drags don't copy their payload through the channel.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#the-drag-data-store>
+    pub fn to_drag_data_store(&self) -> DragDataStore {
+        let mut store = DragDataStore::new((*self.effect_allowed.borrow()).into());
+        store.drop_effect = (*self.drop_effect.borrow()).into();
+
+        store.items = self
+            .item_list
+            .items()
+            .iter()
+            .map(|item| DragDataStoreItem {
+                kind: item.kind().to_string(),
+                type_: item.type_().to_string(),
+                value: match item.value() {
+                    DataTransferItemValue::String(value) => {
+                        DragDataStoreItemValue::String(value.to_string())
+                    },
+                    DataTransferItemValue::File(file) => {
+                        DragDataStoreItemValue::File(DragDataStoreFile {
+                            name: file.name().to_string(),
+                            type_: file.type_string(),
+                            blob_handle: format!("blob:{}", file.name()),
+                        })
+                    },
+                    DataTransferItemValue::Entry(entry) => {
+                        DragDataStoreItemValue::Entry(entry_to_drag_data_store(&entry))
+                    },
+                },
+            })
+            .collect();
+
+        // No compositor-side rasterizer exists in this snapshot to turn `bitmap.element` into
+        // actual pixels, so the handle below is a placeholder rather than a real paint result.
+        store.bitmap = self
+            .bitmap_image
+            .borrow()
+            .as_ref()
+            .map(|bitmap| DragDataStoreBitmap {
+                bitmap_handle: format!("element:{}", bitmap.element.local_name()),
+                hotspot_x: bitmap.image_x,
+                hotspot_y: bitmap.image_y,
+            });
+
+        store
+    }
+
+    /// Reconstruct a fresh, `ReadOnly` `DataTransfer` for the target document on `drop`, from a
+    /// [`DragDataStore`] received over IPC.
+    ///
+    /// String and directory-entry entries are restored directly; see the loop below for why file
+    /// entries are not.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#concept-dnd-p>
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_for_drop(global: &Window, store: &DragDataStore) -> DomRoot<DataTransfer> {
+        let files = FileList::new(global, Vec::new());
+        let items = DataTransferItemList::new_for_clipboard(global, &[], DataTransferMode::ReadOnly);
+
+        for item in &store.items {
+            match &item.value {
+                DragDataStoreItemValue::String(value) => {
+                    let _ = items.add_string(
+                        DOMString::from(value.clone()),
+                        DOMString::from(item.type_.clone()),
+                    );
+                },
+                DragDataStoreItemValue::Entry(entry) => {
+                    items.add_entry(
+                        entry_from_drag_data_store(entry),
+                        DOMString::from(item.type_.clone()),
+                    );
+                },
+                // File entries cannot be reconstructed without a blob store to resolve
+                // `blob_handle`s against, so they are omitted from the rebuilt item list;
+                // `DataTransfer.files` on the drop target is empty until that infrastructure
+                // exists.
+                DragDataStoreItemValue::File(_) => {},
+            }
+        }
+
+        let data_transfer = DataTransfer::new_inherited(files, items);
+        *data_transfer.drop_effect.borrow_mut() = DropEffect::None;
+        *data_transfer.effect_allowed.borrow_mut() = EffectAllowed::Uninitialized;
+
+        reflect_dom_object_with_proto(Box::new(data_transfer), global, None)
+    }
+
+    /// Re-derive `dropEffect` from whatever a `dragenter`/`dragover` handler requested via
+    /// `SetDropEffect` and the drag data store's `effectAllowed`, so that by the time `drop`/
+    /// `dragend` fires, `dropEffect` reflects the operation actually negotiated rather than
+    /// whichever value a handler last happened to write.
+    ///
+    /// Real negotiation also folds in the platform drag session's live feedback (e.g. the user
+    /// holding a modifier key to force a copy); this snapshot has no embedder-side drag
+    /// controller to source that from, so only the `effectAllowed` constraint from the table in
+    /// the processing model is applied here. The per-event drag dispatch steps are expected to
+    /// call this after each `dragenter`/`dragover` is dispatched - see the equivalent note on
+    /// [`DataTransferItemList::set_mode`](crate::dom::datatransferitemlist::DataTransferItemList::set_mode).
+    /// `SetDropEffect` only stores a value while the item list is in `ReadWrite` mode, so a value
+    /// written by a handler in any other mode is not seen here.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#drag-and-drop-processing-model>
+    pub fn negotiate_drop_effect(&self) {
+        let requested = *self.drop_effect.borrow();
+        let permitted = effects_permitted_by(*self.effect_allowed.borrow());
+
+        let negotiated = if requested != DropEffect::None && permitted.contains(&requested) {
+            requested
+        } else {
+            DropEffect::None
+        };
+
+        *self.drop_effect.borrow_mut() = negotiated;
+    }
 }
 
 #[allow(non_snake_case)]
@@ -127,16 +384,15 @@ impl DataTransferMethods for DataTransfer {
             return;
         }
 
-        // Step 3
-        if image.is::<HTMLImageElement>() {
-            if let Some(image_element) = image.downcast::<HTMLImageElement>() {
-                *self.bitmap_image.borrow_mut() = Some(DataTransferBitmap {
-                    image: DomRoot::from_ref(image_element),
-                    image_x: x,
-                    image_y: y,
-                })
-            }
-        }
+        // Step 3. Set the drag data store bitmap to a bitmap depicting image, and the drag data
+        // store hot spot coordinate to (x, y). Any element is valid drag feedback, not only
+        // images; rasterizing it into that bitmap is a compositor-side paint that this snapshot's
+        // layout crate doesn't yet expose a hook for.
+        *self.bitmap_image.borrow_mut() = Some(DataTransferBitmap {
+            element: DomRoot::from_ref(image),
+            image_x: x,
+            image_y: y,
+        })
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-getdata>
@@ -262,3 +518,35 @@ impl DataTransferMethods for DataTransfer {
         frozen_types
     }
 }
+
+/// Recursively convert a [`FileSystemEntryHandle`] (and its children, if it's a directory) into
+/// its IPC-serializable [`DragDataStoreEntry`] form for `dragstart`.
+fn entry_to_drag_data_store(entry: &FileSystemEntryHandle) -> DragDataStoreEntry {
+    DragDataStoreEntry {
+        kind: match entry.kind {
+            FileSystemEntryKind::File => DragDataStoreEntryKind::File,
+            FileSystemEntryKind::Directory => DragDataStoreEntryKind::Directory,
+        },
+        name: entry.name.to_string(),
+        full_path: entry.full_path.to_string(),
+        children: entry.children.as_ref().map(|children| {
+            children.iter().map(entry_to_drag_data_store).collect()
+        }),
+    }
+}
+
+/// The inverse of [`entry_to_drag_data_store`], reconstructing a [`FileSystemEntryHandle`] tree
+/// for `drop`.
+fn entry_from_drag_data_store(entry: &DragDataStoreEntry) -> FileSystemEntryHandle {
+    FileSystemEntryHandle {
+        kind: match entry.kind {
+            DragDataStoreEntryKind::File => FileSystemEntryKind::File,
+            DragDataStoreEntryKind::Directory => FileSystemEntryKind::Directory,
+        },
+        name: DOMString::from(entry.name.clone()),
+        full_path: DOMString::from(entry.full_path.clone()),
+        children: entry.children.as_ref().map(|children| {
+            children.iter().map(entry_from_drag_data_store).collect()
+        }),
+    }
+}
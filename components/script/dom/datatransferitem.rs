@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
 use std::rc::Rc;
 
 use dom_struct::dom_struct;
@@ -13,14 +14,69 @@ use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::reflector::{Reflector, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransferitemlist::DataTransferMode;
 use crate::dom::window::Window;
 
 use super::file::File;
 
+/// The kind of filesystem entry a dropped [`DataTransferItemValue::Entry`] names, mirroring the
+/// `FileSystemEntry`/`FileSystemDirectoryEntry`/`FileSystemFileEntry` distinction.
+///
+/// <https://wicg.github.io/entries-api/#api-entry>
+#[derive(Clone, Eq, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum FileSystemEntryKind {
+    File,
+    Directory,
+}
+
+/// Metadata for a dropped OS filesystem entry, backing `webkitGetAsEntry()`.
+///
+/// This only records the path/kind (and, for a directory, its children) needed to walk the tree;
+/// it does not read file contents, since that requires a sandboxed filesystem reader this
+/// snapshot has no infrastructure for.
+///
+/// <https://wicg.github.io/entries-api/#api-entry>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct FileSystemEntryHandle {
+    pub kind: FileSystemEntryKind,
+    pub name: DOMString,
+    pub full_path: DOMString,
+    /// `None` for a `FileSystemEntryKind::File`. `Some` for a directory, populated up front from
+    /// the OS listing the platform embedder already did to produce this drop in the first place
+    /// (there's no lazy/streaming filesystem read here, just a fully-known child list).
+    pub children: Option<Vec<FileSystemEntryHandle>>,
+}
+
+impl FileSystemEntryHandle {
+    pub fn is_directory(&self) -> bool {
+        self.kind == FileSystemEntryKind::Directory
+    }
+
+    /// Backs `FileSystemDirectoryReader.readEntries()`'s batching contract: repeated calls with
+    /// an advancing `offset` return up to `batch_size` children at a time, until an empty `Vec`
+    /// signals the listing is exhausted. Returns an empty `Vec` for a file entry.
+    ///
+    /// <https://wicg.github.io/entries-api/#dom-filesystemdirectoryreader-readentries>
+    pub fn read_entries_batch(&self, offset: usize, batch_size: usize) -> Vec<FileSystemEntryHandle> {
+        let Some(children) = &self.children else {
+            return Vec::new();
+        };
+
+        if offset >= children.len() {
+            return Vec::new();
+        }
+
+        children[offset..(offset + batch_size).min(children.len())].to_vec()
+    }
+}
+
 #[derive(Clone, JSTraceable, MallocSizeOf)]
 pub enum DataTransferItemValue {
     File(DomRoot<File>),
     String(DOMString),
+    /// A directory (or file) dragged from the OS file manager, recursively enumerable via
+    /// `webkitGetAsEntry()`.
+    Entry(FileSystemEntryHandle),
 }
 
 #[dom_struct]
@@ -29,6 +85,10 @@ pub struct DataTransferItem {
     kind: DOMString, // 'string' or 'file'
     type_: DOMString,
     value: DataTransferItemValue,
+    /// Mirrors the owning `DataTransferItemList`'s mode, kept in sync by
+    /// `DataTransferItemList::set_mode`. Read here rather than through a back-reference to the
+    /// list, since an item can outlive removal from its list.
+    mode: Cell<DataTransferMode>,
 }
 
 impl DataTransferItem {
@@ -37,10 +97,17 @@ impl DataTransferItem {
             reflector_: Reflector::new(),
             kind,
             type_,
-            value
+            value,
+            mode: Cell::new(DataTransferMode::ReadWrite),
         }
     }
 
+    /// Set by `DataTransferItemList::set_mode` so that, in `Protected` mode, this item's own
+    /// payload accessors know to withhold data.
+    pub(crate) fn set_mode(&self, mode: DataTransferMode) {
+        self.mode.set(mode);
+    }
+
     pub fn new(
         window: &Window,
         kind: DOMString,
@@ -67,19 +134,55 @@ impl DataTransferItem {
         self.value.clone()
     }
 
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#concept-dnd-p>
+    ///
+    /// While the drag data store is in protected mode (i.e. during any drag event other than
+    /// `dragstart`/`drop`), scripts may see what kinds/formats are being dragged but not the
+    /// actual payload.
+    fn is_protected(&self) -> bool {
+        self.mode.get() == DataTransferMode::Protected
+    }
+
     pub fn get_as_file(&self) -> Option<DomRoot<File>> {
+        if self.is_protected() {
+            return None;
+        }
+
         match &self.value {
             DataTransferItemValue::File(f) => Some(f.clone()),
             _ => None
         }
     }
 
+    /// Backs `webkitGetAsEntry()`/`getAsFileSystemHandle()`: the `FileSystemEntry`/
+    /// `FileSystemDirectoryEntry` metadata for an item dragged from the OS file manager, if any.
+    ///
+    /// Exposing this as an actual WebIDL method (and reflecting it into a `FileSystemEntry` DOM
+    /// object) needs the `.webidl` definitions and codegen output for that interface, neither of
+    /// which exist in this snapshot, so only the underlying data accessor is implemented here.
+    ///
+    /// <https://wicg.github.io/entries-api/#dom-datatransferitem-webkitgetasentry>
+    pub fn get_as_entry(&self) -> Option<FileSystemEntryHandle> {
+        if self.is_protected() {
+            return None;
+        }
+
+        match &self.value {
+            DataTransferItemValue::Entry(entry) => Some(entry.clone()),
+            _ => None
+        }
+    }
+
 }
 
 #[allow(non_snake_case)]
 impl DataTransferItemMethods for DataTransferItem {
     // https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransferitem-getasstring
     fn GetAsString(&self, callback: Option<Rc<FunctionStringCallback>>) {
+        if self.is_protected() {
+            return;
+        }
+
         if let (Some(callback), &DataTransferItemValue::String(ref text)) = (callback, &self.value) {
             let _ = callback.Call__(text.clone(), ExceptionHandling::Report);
         }
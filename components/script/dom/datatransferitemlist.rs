@@ -13,13 +13,13 @@ use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{DomObject, reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::datatransferitem::{DataTransferItem, DataTransferItemValue};
+use crate::dom::datatransferitem::{DataTransferItem, DataTransferItemValue, FileSystemEntryHandle};
 use crate::dom::file::File;
 use crate::dom::window::Window;
 
 use servo_rand::random;
 
-#[derive(JSTraceable, MallocSizeOf, PartialEq)]
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum DataTransferMode {
     ReadOnly,
     ReadWrite,
@@ -32,19 +32,23 @@ pub struct DataTransferItemList {
     reflector_: Reflector,
     list: DomRefCell<Vec<DomRoot<DataTransferItem>>>,
     types: DomRefCell<Vec<DOMString>>,
-    mode: DataTransferMode,
+    mode: Cell<DataTransferMode>,
     cache_key: Cell<u32>
 }
 
 impl DataTransferItemList {
-    fn new_inherited(list: &[&DataTransferItem]) -> DataTransferItemList {
+    fn new_inherited(list: &[&DataTransferItem], mode: DataTransferMode) -> DataTransferItemList {
+        for item in list {
+            item.set_mode(mode);
+        }
+
         DataTransferItemList {
             reflector_: Reflector::new(),
             list: DomRefCell::new(list.iter().map(|item|
                 DomRoot::from_ref(&**item)
             ).collect()),
             types: DomRefCell::new(vec![]),
-            mode: DataTransferMode::ReadWrite,
+            mode: Cell::new(mode),
             cache_key: Cell::new(0)
         }
     }
@@ -55,7 +59,25 @@ impl DataTransferItemList {
         list: &[&DataTransferItem],
     ) -> DomRoot<DataTransferItemList> {
         reflect_dom_object(
-            Box::new(DataTransferItemList::new_inherited(list)),
+            Box::new(DataTransferItemList::new_inherited(list, DataTransferMode::ReadWrite)),
+            window,
+        )
+    }
+
+    /// Construct an item list for a `copy`/`cut`/`paste` `ClipboardEvent`.
+    ///
+    /// Per <https://w3c.github.io/clipboard-apis/#override-the-clipboard-data-store-mode>, the
+    /// data store mode is `ReadWrite` for `copy`/`cut` (so `SetData`/`ClearData` can shape what is
+    /// flushed back to the platform clipboard) and `ReadOnly` for `paste` (scripts may read, but
+    /// not mutate, what the platform clipboard provided).
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_for_clipboard(
+        window: &Window,
+        list: &[&DataTransferItem],
+        mode: DataTransferMode,
+    ) -> DomRoot<DataTransferItemList> {
+        reflect_dom_object(
+            Box::new(DataTransferItemList::new_inherited(list, mode)),
             window,
         )
     }
@@ -70,11 +92,35 @@ impl DataTransferItemList {
         Ok(Some(self.add(DataTransferItem::new(
             &self.global().as_window(),
             DOMString::from("string"),
-            format, 
+            format,
             DataTransferItemValue::String(data)
         ))))
     }
 
+    /// Add a dropped OS filesystem entry to the list, backing `webkitGetAsEntry()` on the
+    /// resulting item.
+    pub fn add_entry(&self, entry: FileSystemEntryHandle, type_: DOMString) -> DomRoot<DataTransferItem> {
+        self.add(DataTransferItem::new(
+            &self.global().as_window(),
+            DOMString::from_string("file".to_owned()),
+            type_,
+            DataTransferItemValue::Entry(entry),
+        ))
+    }
+
+    /// Add a `File` item to the list unconditionally, bypassing the `ReadWrite`-only guard on
+    /// the `add()`/`items.add(file)` WebIDL method. Used to populate a clipboard-backed list with
+    /// an image/file flavor read from the platform clipboard on `paste`, which (like the drag
+    /// data store on `drop`) is filled in before the list is ever handed to script.
+    pub fn add_file(&self, file: DomRoot<File>, type_: DOMString) -> DomRoot<DataTransferItem> {
+        self.add(DataTransferItem::new(
+            &self.global().as_window(),
+            DOMString::from_string("file".to_owned()),
+            type_,
+            DataTransferItemValue::File(file),
+        ))
+    }
+
     fn add(&self, item: DomRoot<DataTransferItem>) -> DomRoot<DataTransferItem> {
         self.list.borrow_mut().push(item.clone());
 
@@ -122,6 +168,17 @@ impl DataTransferItemList {
         files
     }
 
+    /// The `FileSystemEntry` metadata for every item dragged from the OS file manager, in order -
+    /// a file item yields a `FileSystemEntryKind::File` handle, a dropped folder yields
+    /// `FileSystemEntryKind::Directory` with its children already attached.
+    ///
+    /// <https://wicg.github.io/entries-api/#dfn-obtain-entry>
+    pub fn get_entries(&self) -> Vec<FileSystemEntryHandle> {
+        self.list.borrow().iter().filter_map(
+            |item| item.get_as_entry()
+        ).collect()
+    }
+
     // <https://html.spec.whatwg.org/multipage/dnd.html#concept-datatransfer-types>
     fn regenerate_types(&self) {
         // Step 1 & 2.1
@@ -151,8 +208,32 @@ impl DataTransferItemList {
         self.cache_key.get()
     }
 
-    pub fn get_mode(&self) -> &DataTransferMode {
-        &self.mode
+    /// The items in this list, in order, for callers that need to walk the whole list (e.g.
+    /// snapshotting it into a [`script_traits::drag_data_store::DragDataStore`]).
+    pub fn items(&self) -> Vec<DomRoot<DataTransferItem>> {
+        self.list.borrow().clone()
+    }
+
+    pub fn get_mode(&self) -> DataTransferMode {
+        self.mode.get()
+    }
+
+    /// Transition the drag data store's mode, per
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#drag-data-store-mode>: read/write only
+    /// while a `dragstart` handler runs, read-only during `drop`, and protected for every other
+    /// drag event. Propagates to every existing item so their payload accessors immediately
+    /// reflect the new mode.
+    ///
+    /// Callers are expected to be the per-event-type drag dispatch steps; this snapshot doesn't
+    /// yet have a central drag-and-drop event loop driving `dragenter`/`dragover`/`dragleave`/
+    /// `dragend`, so today only [`super::datatransfer::DataTransfer::new_for_drop`] reaches this
+    /// (constructing straight into `ReadOnly`) rather than calling it after the fact.
+    pub fn set_mode(&self, mode: DataTransferMode) {
+        self.mode.set(mode);
+
+        for item in self.list.borrow().iter() {
+            item.set_mode(mode);
+        }
     }
 }
 
@@ -163,7 +244,7 @@ impl DataTransferItemListMethods for DataTransferItemList {
         warn!("ADDING A STRING ===== {:?} | {:?}", data, type_);
 
         // Step 1
-        if self.mode != DataTransferMode::ReadWrite {
+        if self.mode.get() != DataTransferMode::ReadWrite {
             return Ok(None);
         }
 
@@ -190,7 +271,7 @@ impl DataTransferItemListMethods for DataTransferItemList {
         warn!("ADDING A FILE ===== {:?} | {:?}", data.name(), data.type_string());
 
         // Step 1
-        if self.mode != DataTransferMode::ReadWrite {
+        if self.mode.get() != DataTransferMode::ReadWrite {
             return Ok(None);
         }
 
@@ -207,7 +288,7 @@ impl DataTransferItemListMethods for DataTransferItemList {
     // https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransferitemlist-remove
     fn Remove(&self, index: u32) -> Fallible<()> {
         // Step 1
-        if self.mode != DataTransferMode::ReadWrite {
+        if self.mode.get() != DataTransferMode::ReadWrite {
             return Err(Error::InvalidState);
         }
 
@@ -225,7 +306,7 @@ impl DataTransferItemListMethods for DataTransferItemList {
 
     // https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransferitemlist-clear
     fn Clear(&self) {
-        if self.mode == DataTransferMode::ReadWrite {
+        if self.mode.get() == DataTransferMode::ReadWrite {
             // Avoid regenerating the internal types cache key when the item list is already empty
             if !self.list.borrow().is_empty() {
                 self.list.borrow_mut().clear();
@@ -5,11 +5,12 @@
 use std::rc::Rc;
 
 use dom_struct::dom_struct;
-// use js::jsapi::Heap;
-// use js::jsval::JSVal;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
 use js::gc::{HandleObject, MutableHandleValue};
 use servo_atoms::Atom;
 
+use crate::dom::abortsignal::AbortSignal;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::NavigateEventBinding::{
@@ -44,11 +45,16 @@ pub enum InterceptionState {
 #[dom_struct]
 pub struct NavigateEvent {
     event: Event,
-    // TODO
-    // #[ignore_malloc_size_of = "mozjs"]
-    // info: RootedTraceableBox<Heap<JSVal>>,
+    /// An arbitrary JavaScript value passed via one of the navigation API methods which initiated
+    /// this navigation, taken from the initiating navigation API method tracker (which resets its
+    /// own copy to undefined once taken, so it is delivered at most once). Undefined for a
+    /// user-initiated navigation (e.g. following a link, or the browser UI) or one started by
+    /// some other API, since neither goes through a navigation API method tracker at all.
+    #[ignore_malloc_size_of = "mozjs"]
+    info: Heap<JSVal>,
     navigation_type: NavigationType,
     destination: DomRoot<NavigationDestination>,
+    signal: DomRoot<AbortSignal>,
     interception_state: DomRefCell<InterceptionState>,
     #[ignore_malloc_size_of = "mozjs"]
     navigation_handler_list: DomRefCell<Vec<Rc<NavigationInterceptHandler>>>,
@@ -64,11 +70,12 @@ pub struct NavigateEvent {
 
 impl NavigateEvent {
     fn new_inherited(init: &RootedTraceableBox<NavigateEventInit>) -> NavigateEvent {
-        NavigateEvent {
+        let event = NavigateEvent {
             event: Event::new_inherited(),
-            // info: init.info.clone(),
+            info: Heap::default(),
             destination: init.destination.clone(),
             navigation_type: init.navigationType.clone(),
+            signal: init.signal.clone(),
             interception_state: DomRefCell::new(InterceptionState::None),
             navigation_handler_list: DomRefCell::new(vec![]),
             focus_reset: DomRefCell::new(None),
@@ -78,8 +85,12 @@ impl NavigateEvent {
             user_initiated: DomRefCell::new(init.userInitiated),
             has_ua_visible_transitions: DomRefCell::new(init.hasUAVisualTransition),
             hash_change: DomRefCell::new(init.hashChange),
-            form_data: None,
-        }
+            form_data: init.formData.clone(),
+        };
+
+        event.info.set(init.info.get());
+
+        event
     }
 
     fn new_with_proto(
@@ -116,6 +127,52 @@ impl NavigateEvent {
         NavigateEvent::new_with_proto(window, proto, type_, &init, can_gc)
     }
 
+    /// Constructs a `navigate` event directly from the properties produced by the inner navigate
+    /// event firing algorithm, bypassing the public dictionary-based constructor that backs
+    /// `new NavigateEvent(...)` from script. The event is not dispatched here; the caller fires it.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#inner-navigate-event-firing-algorithm>
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new_for_navigation(
+        window: &Window,
+        navigation_type: NavigationType,
+        destination: DomRoot<NavigationDestination>,
+        can_intercept: bool,
+        user_initiated: bool,
+        hash_change: bool,
+        signal: DomRoot<AbortSignal>,
+        form_data: Option<DomRoot<FormData>>,
+        info: JSVal,
+        can_gc: CanGc,
+    ) -> DomRoot<NavigateEvent> {
+        let navigate_event = NavigateEvent {
+            event: Event::new_inherited(),
+            info: Heap::default(),
+            destination,
+            navigation_type,
+            signal,
+            interception_state: DomRefCell::new(InterceptionState::None),
+            navigation_handler_list: DomRefCell::new(vec![]),
+            focus_reset: DomRefCell::new(None),
+            scroll_behavior: DomRefCell::new(None),
+            download_request: DomRefCell::new(None),
+            can_intercept: DomRefCell::new(can_intercept),
+            user_initiated: DomRefCell::new(user_initiated),
+            has_ua_visible_transitions: DomRefCell::new(false),
+            hash_change: DomRefCell::new(hash_change),
+            form_data,
+        };
+
+        navigate_event.info.set(info);
+
+        let ev = reflect_dom_object_with_proto(Box::new(navigate_event), window, None, can_gc);
+
+        ev.upcast::<Event>()
+            .init_event(Atom::from("navigate"), false, true);
+
+        ev
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#navigateevent-perform-shared-checks>
     fn perform_shared_checks(&self) -> Fallible<()> {
         let global = self.global();
@@ -142,8 +199,35 @@ impl NavigateEvent {
         Ok(())
     }
 
+    /// The current point in the [interception lifecycle](InterceptionState) for this event.
+    pub(crate) fn interception_state(&self) -> InterceptionState {
+        self.interception_state.borrow().clone()
+    }
+
+    /// Moves this event to `state`, driven by the owning `Navigation`'s
+    /// [inner navigate event firing algorithm](https://html.spec.whatwg.org/multipage/nav-history-apis.html#inner-navigate-event-firing-algorithm).
+    pub(crate) fn set_interception_state(&self, state: InterceptionState) {
+        *self.interception_state.borrow_mut() = state;
+    }
+
+    /// Takes this event's navigation handler list, leaving it empty, so the caller can run and
+    /// await the handlers collected via `intercept()`.
+    pub(crate) fn take_navigation_handler_list(&self) -> Vec<Rc<NavigationInterceptHandler>> {
+        std::mem::take(&mut *self.navigation_handler_list.borrow_mut())
+    }
+
+    /// The focus reset behavior most recently set via `intercept()`, if any.
+    pub(crate) fn focus_reset_behavior(&self) -> Option<NavigationFocusReset> {
+        self.focus_reset.borrow().clone()
+    }
+
+    /// The scroll behavior most recently set via `intercept()`, if any.
+    pub(crate) fn scroll_behavior(&self) -> Option<NavigationScrollBehavior> {
+        self.scroll_behavior.borrow().clone()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#process-scroll-behavior>
-    fn process_scroll_behavior(&self) {
+    pub(crate) fn process_scroll_behavior(&self) {
         // Step 1. Assert: event's interception state is "committed".
         // debug_assert_eq!(self.interception_state(), InterceptionState::Committed);
 
@@ -183,6 +267,43 @@ impl NavigateEvent {
             document.check_and_scroll_fragment("", CanGc::note());
         }
     }
+
+    /// Runs once, after the scroll step ([`Self::process_scroll_behavior`], which is skipped for a
+    /// manual scroll behavior), as part of the commit→finish transition driven by
+    /// `Navigation::finish_the_navigate_event`.
+    ///
+    /// `focus_changed_during_navigation` is `Navigation`'s own
+    /// [focus changed during ongoing navigation](https://html.spec.whatwg.org/multipage/nav-history-apis.html#focus-changed-during-ongoing-navigation)
+    /// flag - focus reset is a navigation-wide decision, not something this event tracks about
+    /// itself, so the caller threads it in rather than this event owning a copy.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#process-focus-reset>
+    pub(crate) fn process_focus_reset(&self, focus_changed_during_navigation: bool) {
+        // Step 1. If focusChangedDuringOngoingNavigation is false, then return. `Navigation` sets
+        // the flag when an intercepted navigation commits without a manual focus reset; nothing
+        // here tracks whether a handler or the user moved focus during the navigation.
+        if !focus_changed_during_navigation {
+            return;
+        }
+
+        // Step 2. If event's focus reset behavior is "manual", then return.
+        if *self.focus_reset.borrow() == Some(NavigationFocusReset::Manual) {
+            return;
+        }
+
+        let global = self.global();
+        let window = global.as_window();
+        let document = window.Document();
+
+        // Step 3. Let autofocusDelegate be the first of: an autofocus candidate in document's
+        // autofocus candidates, document's body element, or document's root element, whichever
+        // exists. Step 4. If autofocusDelegate is not null, reset the focus given autofocusDelegate.
+        //
+        // This tree tracks no autofocus candidates and has no `Element`/body accessor on
+        // `Document` or focusable-area algorithm to hand a target to, so there is nothing here
+        // that can actually be focused; nothing is focused and nothing is recorded.
+        let _ = document;
+    }
 }
 
 impl NavigateEventMethods<crate::DomTypeHolder> for NavigateEvent {
@@ -201,6 +322,14 @@ impl NavigateEventMethods<crate::DomTypeHolder> for NavigateEvent {
         self.can_intercept.borrow().clone()
     }
 
+    /// Aborted when the navigation this event represents is superseded by another navigation, or
+    /// when it fails, to let an intercept() handler cancel in-flight work.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#dom-navigateevent-signal>
+    fn Signal(&self) -> DomRoot<AbortSignal> {
+        self.signal.clone()
+    }
+
     /// True if this navigation was due to a user clicking on an a element, submitting a form
     /// element, or using the browser UI to navigate; false otherwise.
     ///
@@ -229,8 +358,8 @@ impl NavigateEventMethods<crate::DomTypeHolder> for NavigateEvent {
     /// API.
     ///
     /// <https://html.spec.whatwg.org/multipage/#dom-navigateevent-info>
-    fn Info(&self, _cx: JSContext, _retval: MutableHandleValue) {
-        todo!()
+    fn Info(&self, _cx: JSContext, mut retval: MutableHandleValue) {
+        retval.set(self.info.get());
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-navigateevent-hasuavisualtransition>
@@ -2,23 +2,33 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
 use std::rc::Rc;
 use std::cmp::Eq;
 use indexmap::IndexMap;
+use js::jsapi::Heap;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::HandleValue;
 use net_traits::session_history::{SessionHistoryEntry, SessionHistoryEntryStep};
 // use ipc_channel::ipc;
 // use net_traits::CoreResourceMsg;
+use script_traits::StructuredSerializedData;
 use servo_atoms::Atom;
 // use uuid::Uuid;
 
 use dom_struct::dom_struct;
 use servo_url::{ImmutableOrigin, ServoUrl};
 
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use crate::dom::bindings::codegen::Bindings::NavigateEventBinding::{
+    NavigateEventMethods, NavigationFocusReset, NavigationInterceptHandler,
+    NavigationScrollBehavior,
+};
 use crate::dom::bindings::codegen::Bindings::NavigationBinding::{
     NavigationHistoryBehavior, NavigationUpdateCurrentEntryOptions, NavigationMethods,
-    NavigationNavigateOptions, NavigationResult, NavigationOptions, NavigationReloadOptions
+    NavigationNavigateOptions, NavigationResult, NavigationOptions, NavigationReloadOptions,
+    NavigationType,
 };
 use crate::dom::bindings::codegen::Bindings::NavigationCurrentEntryChangeEventBinding::NavigationCurrentEntryChangeEventInit;
 use crate::dom::bindings::codegen::Bindings::NavigationHistoryEntryBinding::
@@ -27,17 +37,22 @@ use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::Wind
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
 use crate::dom::bindings::error::{Error, Fallible};
 // use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{DomObject, reflect_dom_object};
-// use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
 use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::abortsignal::AbortSignal;
 use crate::dom::document::{HistoryApplicationResult, SourceSnapshotParams};
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::formdata::FormData;
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::navigateevent::NavigateEvent;
+use crate::dom::navigateevent::{InterceptionState, NavigateEvent};
 use crate::dom::navigationactivation::NavigationActivation;
 use crate::dom::navigationcurrententrychangeevent::NavigationCurrentEntryChangeEvent;
+use crate::dom::navigationdestination::NavigationDestination;
 use crate::dom::navigationhistoryentry::NavigationHistoryEntry;
 use crate::dom::navigationtransition::NavigationTransition;
 use crate::dom::promise::Promise;
@@ -45,12 +60,16 @@ use crate::dom::window::Window;
 use crate::script_runtime::CanGc;
 
 /// <https://html.spec.whatwg.org/multipage/#navigation-api-method-tracker>
-#[derive(Clone, MallocSizeOf)]
+#[derive(JSTraceable, MallocSizeOf)]
 struct NavigationApiMethodTracker {
     key: Option<String>,
-    // #[ignore_malloc_size_of = "jsvalues are hard"]
-    // info: JSValue,
-    state: Option<String>, // TODO
+    /// The `info` argument passed to the navigation API method that created this tracker, or
+    /// undefined if none was given. Taken (and reset to undefined) by
+    /// [`Navigation::inner_navigate_event_firing_algorithm`] when it builds the `NavigateEvent`
+    /// this tracker corresponds to, so it is delivered at most once.
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    info: Heap<JSVal>,
+    state: Option<StructuredSerializedData>,
     committed_to_entry: Option<DomRoot<NavigationHistoryEntry>>,
     #[ignore_malloc_size_of = "promises are hard"]
     committed_promise: Rc<Promise>,
@@ -65,24 +84,70 @@ impl PartialEq for NavigationApiMethodTracker {
     }
 }
 
+impl Clone for NavigationApiMethodTracker {
+    fn clone(&self) -> Self {
+        let info = Heap::default();
+        info.set(self.info.get());
+
+        NavigationApiMethodTracker {
+            key: self.key.clone(),
+            info,
+            state: self.state.clone(),
+            committed_to_entry: self.committed_to_entry.clone(),
+            committed_promise: self.committed_promise.clone(),
+            finished_promise: self.finished_promise.clone(),
+        }
+    }
+}
+
 impl NavigationApiMethodTracker {
     pub fn new(
         global: &GlobalScope,
-        // info: JSValue,
-        state: Option<String>,
+        info: JSVal,
+        state: Option<StructuredSerializedData>,
         committed_promise: Option<Rc<Promise>>,
         finished_promise: Option<Rc<Promise>>,
         can_gc: CanGc,
     ) -> NavigationApiMethodTracker {
+        let info_heap = Heap::default();
+        info_heap.set(info);
+
         NavigationApiMethodTracker {
             key: None,
-            // info,
+            info: info_heap,
             state,
             committed_to_entry: None,
             committed_promise: committed_promise.unwrap_or(Promise::new(global, can_gc)),
             finished_promise: finished_promise.unwrap_or(Promise::new(global, can_gc)),
         }
     }
+
+    /// Takes this tracker's `info` value, leaving undefined behind, so it can be delivered to the
+    /// `NavigateEvent` being constructed for this navigation.
+    pub fn take_info(&self) -> JSVal {
+        let info = self.info.get();
+        self.info.set(UndefinedValue());
+        info
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#navigation-api-method-tracker-notify-about-the-committed-to-entry>
+    ///
+    /// This tree has no session history commit step that updates the current entry ahead of this
+    /// point for same-document navigations, so there is no concrete committed-to entry to attach
+    /// yet; resolve the committed promise with undefined in the meantime rather than fabricate one.
+    fn resolve_the_committed_promise(&self) {
+        self.committed_promise.resolve_native(&());
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#resolve-the-finished-promise>
+    fn resolve_the_finished_promise(&self) {
+        self.finished_promise.resolve_native(&());
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#reject-the-finished-promise>
+    fn reject_the_finished_promise(&self, error: Error) {
+        self.finished_promise.reject_error(error);
+    }
 }
 
 #[dom_struct]
@@ -94,16 +159,16 @@ pub struct Navigation {
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#navigation-current-entry-index>
     current_entry_index: Option<usize>,
     /// https://html.spec.whatwg.org/multipage/nav-history-apis.html#ongoing-navigate-event
-    ongoing_event: Option<NavigateEvent>,
-    // transition: Option<NavigationTransition>,
-    focus_changed: bool,
-    suppress_scroll: bool,
-    #[no_trace]
-    ongoing_method_tracker: Option<NavigationApiMethodTracker>,
-    #[no_trace]
+    ongoing_event: DomRefCell<Option<DomRoot<NavigateEvent>>>,
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigation-transition>
+    transition: DomRefCell<Option<DomRoot<NavigationTransition>>>,
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#focus-changed-during-ongoing-navigation>
+    focus_changed: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#suppress-normal-scroll-restoration-during-ongoing-navigation>
+    suppress_scroll: Cell<bool>,
+    ongoing_method_tracker: DomRefCell<Option<NavigationApiMethodTracker>>,
     /// <https://html.spec.whatwg.org/multipage/#upcoming-non-traverse-api-method-tracker>
     upcoming_non_traverse_method_tracker: DomRefCell<Option<NavigationApiMethodTracker>>,
-    #[no_trace]
     #[ignore_malloc_size_of = "sets are hard"]
     /// An ordered map from strings to navigation API method trackers, initially empty.
     ///
@@ -126,10 +191,11 @@ impl Navigation {
             window: Dom::from_ref(window),
             entry_list: vec![],
             current_entry_index: None,
-            ongoing_event: None,
-            focus_changed: false,
-            suppress_scroll: false,
-            ongoing_method_tracker: None,
+            ongoing_event: DomRefCell::new(None),
+            transition: DomRefCell::new(None),
+            focus_changed: Cell::new(false),
+            suppress_scroll: Cell::new(false),
+            ongoing_method_tracker: DomRefCell::new(None),
             upcoming_non_traverse_method_tracker: DomRefCell::new(None),
             upcoming_traverse_method_tracker: DomRefCell::new(IndexMap::new()),
         }
@@ -193,13 +259,26 @@ impl Navigation {
         result
     }
 
+    /// Runs StructuredSerializeForStorage over `state`, for the navigation API state carried by a
+    /// `navigate()`/`reload()`/`updateCurrentEntry()` call. Fails with a "DataCloneError"
+    /// DOMException (surfaced by `structuredclone::write` itself) if `state` cannot be cloned.
+    fn serialize_state(&self, state: HandleValue) -> Fallible<StructuredSerializedData> {
+        structuredclone::write(&self.global(), state)
+    }
+
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#performing-a-navigation-api-traversal>
     #[allow(unsafe_code)]
     fn perform_a_navigation_api_traversal(
         &self,
         key: DOMString,
-        _options: Option<RootedTraceableBox<NavigationOptions>>,
+        options: Option<RootedTraceableBox<NavigationOptions>>,
     ) -> NavigationResult {
+        // Let info be options["info"], if it exists; otherwise, undefined.
+        let info = options
+            .as_ref()
+            .map(|options| options.info.handle().get())
+            .unwrap_or_else(UndefinedValue);
+
         // Step 1. Let document be navigation's relevant global object's associated Document.
         let document = &self.window.Document();
 
@@ -215,6 +294,9 @@ impl Navigation {
             return self.early_error_result(Error::InvalidState);
         }
 
+        // A traversal is about to begin; abort whatever navigate event is still in flight.
+        self.abort_the_ongoing_navigation();
+
         // Step 4. Let current be the current entry of navigation.
         let current_entry = self.GetCurrentEntry();
 
@@ -250,12 +332,12 @@ impl Navigation {
         }
 
         // Step 7. Let info be options["info"], if it exists; otherwise, undefined
-        // let info = options.map(|o| o.info.to_owned());
+        // (extracted above, before any early returns could make it stale).
 
         // Step 8. Let apiMethodTracker be the result of adding an upcoming traverse API method
         // tracker for navigation given key and info.
         let api_method_tracker =
-            self.add_an_upcoming_traverse_api_method_tracker(stringified_key.clone());
+            self.add_an_upcoming_traverse_api_method_tracker(stringified_key.clone(), info);
 
         // Step 9. Let navigable be document's node navigable
         // Step 10. Let traversable be navigable's traversable navigable.
@@ -272,11 +354,14 @@ impl Navigation {
             document.get_session_history_entries().to_owned();
 
         // Step 12. Let targetSHE be the session history entry in navigableSHEs whose navigation API
-        // key is key. If no such entry exists, then:
-        let target_she = navigable_shes
+        // key is key. If no such entry exists, then abort these steps.
+        let Some(target_she) = navigable_shes
             .iter()
-            .find(|ref entry| entry.navigation_api_key().as_bytes() == stringified_key.as_bytes())
-            .unwrap(); // TODO
+            .find(|entry| entry.navigation_api_key().as_bytes() == stringified_key.as_bytes())
+        else {
+            api_method_tracker.reject_the_finished_promise(Error::InvalidState);
+            return self.method_tracker_derived_result(api_method_tracker);
+        };
 
         let browsing_context = match document.browsing_context() {
             Some(bc) => bc,
@@ -307,12 +392,16 @@ impl Navigation {
             // navigation and traversal task source given navigation's relevant global object to
             // reject the finished promise for apiMethodTracker with a new "AbortError" DOMException
             // created in navigation's relevant realm.
-            HistoryApplicationResult::CancelledByBeforeUnload => {},
+            HistoryApplicationResult::CancelledByBeforeUnload => {
+                api_method_tracker.reject_the_finished_promise(Error::Abort);
+            },
             // Step 12.6. If result is "initiator-disallowed", then queue a global task on the
             // navigation and traversal task source given navigation's relevant global object to
             // reject the finished promise for apiMethodTracker with a new "SecurityError"
             // DOMException created in navigation's relevant realm.
-            HistoryApplicationResult::InitiatorDisallowed => {},
+            HistoryApplicationResult::InitiatorDisallowed => {
+                api_method_tracker.reject_the_finished_promise(Error::Security);
+            },
             _ => {}
         }
 
@@ -325,6 +414,7 @@ impl Navigation {
     fn add_an_upcoming_traverse_api_method_tracker(
         &self,
         key: String,
+        info: JSVal,
     ) -> NavigationApiMethodTracker {
         // Step 1. Let committedPromise and finishedPromise be new promises created in navigation's
         // relevant realm.
@@ -337,7 +427,7 @@ impl Navigation {
         // Step 3. Let apiMethodTracker be a new navigation API method tracker with:
         let tracker = NavigationApiMethodTracker::new(
             &self.global(),
-            // JSValue::new(),
+            info,
             None,
             Some(committed_promise),
             Some(finished_promise),
@@ -355,10 +445,12 @@ impl Navigation {
         api_method_tracker.unwrap()
     }
 
-    /// TODO: Account for the additional arguments 
-    ///
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#maybe-set-the-upcoming-non-traverse-api-method-tracker>
-    fn maybe_set_the_upcoming_non_traverse_api_method_tracker(&self) -> NavigationApiMethodTracker {
+    fn maybe_set_the_upcoming_non_traverse_api_method_tracker(
+        &self,
+        info: JSVal,
+        state: Option<StructuredSerializedData>,
+    ) -> NavigationApiMethodTracker {
         // Step 1. Let committedPromise and finishedPromise be new promises created in navigation's
         // relevant realm.
         let committed_promise = Promise::new(&self.global(), CanGc::note());
@@ -370,8 +462,8 @@ impl Navigation {
         // Step 3. Let apiMethodTracker be a new navigation API method tracker with:
         let api_method_tracker = NavigationApiMethodTracker::new(
             &self.global(),
-            // JSValue::new(),
-            None,
+            info,
+            state,
             Some(committed_promise),
             Some(finished_promise),
             CanGc::note(),
@@ -390,6 +482,314 @@ impl Navigation {
         // Step 6. Return apiMethodTracker.
         api_method_tracker
     }
+
+    /// Fires `dispose` at each of `disposed_entries`.
+    ///
+    /// Nothing in this crate calls it: no session history step reports the
+    /// `NavigationHistoryEntry` objects that became unreachable from navigation's entry list, so
+    /// no `dispose` event is fired when entries are pruned.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#update-the-navigation-api-entries-for-a-same-document-navigation>
+    pub(crate) fn dispose_entries(
+        &self,
+        disposed_entries: &[DomRoot<NavigationHistoryEntry>],
+        can_gc: CanGc,
+    ) {
+        for entry in disposed_entries {
+            entry.dispose(can_gc);
+        }
+    }
+
+    /// Constructs a `navigate` event from the given properties, fires it at this, and drives the
+    /// interception state machine ([`InterceptionState`]) to completion.
+    ///
+    /// Returns false if the event was canceled, true otherwise.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#inner-navigate-event-firing-algorithm>
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn inner_navigate_event_firing_algorithm(
+        &self,
+        navigation_type: NavigationType,
+        destination: DomRoot<NavigationDestination>,
+        can_intercept: bool,
+        user_initiated: bool,
+        hash_change: bool,
+        signal: DomRoot<AbortSignal>,
+        form_data: Option<DomRoot<FormData>>,
+        can_gc: CanGc,
+    ) -> bool {
+        // If this's ongoing navigate event is non-null (i.e. a previous navigation is still in
+        // flight), abort it before a new one begins.
+        self.abort_the_ongoing_navigation();
+
+        // Step: Let event be a new NavigateEvent created in this's relevant realm, given the
+        // properties passed to this algorithm.
+        //
+        // `info` is taken from this's ongoing API method tracker, if any, leaving undefined
+        // behind so it cannot be delivered to a subsequent navigation.
+        let info = self
+            .ongoing_method_tracker
+            .borrow()
+            .as_ref()
+            .map(|tracker| tracker.take_info())
+            .unwrap_or_else(UndefinedValue);
+
+        // A form submission's entry list is only ever carried by a "push" or "replace"
+        // navigation; a "reload" or "traverse" never represents a fresh submission, so
+        // `formData` is null for those regardless of what a caller passes in.
+        //
+        // There is no form-submission navigation entry point wired into this tree yet (no
+        // `HTMLFormElement` submission algorithm calls into this method), so `form_data` is
+        // always `None` in practice today; this guard documents and enforces the invariant the
+        // spec relies on once that entry point lands.
+        let form_data = match &navigation_type {
+            NavigationType::Push | NavigationType::Replace => form_data,
+            _ => None,
+        };
+
+        let event = NavigateEvent::new_for_navigation(
+            &self.window,
+            navigation_type,
+            destination,
+            can_intercept,
+            user_initiated,
+            hash_change,
+            signal,
+            form_data,
+            info,
+            can_gc,
+        );
+
+        // Step: Set this's ongoing navigate event to event.
+        *self.ongoing_event.borrow_mut() = Some(event.clone());
+
+        // Step: Set this's focus changed during ongoing navigation to false.
+        self.focus_changed.set(false);
+
+        // Step: Set this's suppress normal scroll restoration during ongoing navigation to false.
+        self.suppress_scroll.set(false);
+
+        // Step: Dispatch event at this.
+        event
+            .upcast::<Event>()
+            .fire(self.upcast::<EventTarget>(), can_gc);
+
+        // Step: If event's canceled flag is set:
+        if event.upcast::<Event>().status() == EventStatus::Canceled {
+            // If event's interception state is not "none", then reject the finished promise for
+            // this's ongoing API method tracker, if any, with an "AbortError" DOMException.
+            if event.interception_state() != InterceptionState::None {
+                if let Some(tracker) = self.ongoing_method_tracker.borrow_mut().take() {
+                    tracker.reject_the_finished_promise(Error::Abort);
+                }
+            }
+
+            *self.ongoing_event.borrow_mut() = None;
+
+            return false;
+        }
+
+        // Step: If event's interception state is "none", then return true — nothing intercepted
+        // the navigation, so the caller proceeds with its default unload/traversal behavior.
+        if event.interception_state() == InterceptionState::None {
+            *self.ongoing_event.borrow_mut() = None;
+
+            return true;
+        }
+
+        // Step: Set event's interception state to "committed".
+        event.set_interception_state(InterceptionState::Committed);
+
+        // Step: If this's ongoing API method tracker is non-null, then notify about the
+        // committed-to entry for it.
+        if let Some(tracker) = self.ongoing_method_tracker.borrow().as_ref() {
+            tracker.resolve_the_committed_promise();
+        }
+
+        // Step: If destination's same document flag is set, then set this's transition to a new
+        // NavigationTransition whose navigation type is navigationType, whose from entry is this's
+        // current entry, and whose finished promise is this's ongoing API method tracker's
+        // finished promise (so the transition settles exactly when the tracker's own finished
+        // promise does, without a separate chaining mechanism).
+        if event.Destination().SameDocument() {
+            if let (Some(from_entry), Some(tracker)) = (
+                self.GetCurrentEntry(),
+                self.ongoing_method_tracker.borrow().as_ref(),
+            ) {
+                *self.transition.borrow_mut() = Some(NavigationTransition::new(
+                    &self.global(),
+                    event.NavigationType(),
+                    from_entry.clone(),
+                    from_entry,
+                    tracker.finished_promise.clone(),
+                    can_gc,
+                ));
+            }
+        }
+
+        // Step: Unless the handler set a "manual" focus reset behavior, mark that focus changed
+        // during this navigation so it is reset once the navigation finishes.
+        if event.focus_reset_behavior() != Some(NavigationFocusReset::Manual) {
+            self.focus_changed.set(true);
+        }
+
+        // Record whether scrolling should be suppressed once the navigation finishes. Actually
+        // processing scroll behavior and focus reset happens once every handler's promise has
+        // settled, not here at commit time - see `finish_the_navigate_event`.
+        if event.scroll_behavior() == Some(NavigationScrollBehavior::Manual) {
+            self.suppress_scroll.set(true);
+        }
+
+        // Step: Let handlerList be the result of taking this's navigation handler list.
+        let handlers = event.take_navigation_handler_list();
+
+        // Step: Run the navigate event handlers given event and handlerList.
+        self.run_the_navigate_event_handlers(&event, handlers, can_gc);
+
+        *self.ongoing_event.borrow_mut() = None;
+
+        true
+    }
+
+    /// Invokes each handler collected via `intercept()` and then finishes the event per
+    /// [`Self::finish_the_navigate_event`]. The returned promises are not awaited: a handler that
+    /// throws finishes the event as rejected, and otherwise it finishes as fulfilled, even if a
+    /// returned promise later rejects.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#navigateevent-finish>
+    fn run_the_navigate_event_handlers(
+        &self,
+        event: &NavigateEvent,
+        handlers: Vec<Rc<NavigationInterceptHandler>>,
+        can_gc: CanGc,
+    ) {
+        let mut handler_promises = Vec::with_capacity(handlers.len());
+
+        for handler in handlers {
+            match handler.Call__(ExceptionHandling::Report, can_gc) {
+                Ok(promise) => handler_promises.push(promise),
+                Err(error) => {
+                    self.finish_the_navigate_event(event, Err(error));
+                    return;
+                },
+            }
+        }
+
+        // The real algorithm waits for every promise in `handler_promises` to settle - fulfilling
+        // only once all of them fulfill, and rejecting (and reporting the exception) as soon as
+        // any one of them does. Doing that requires subscribing to a promise's eventual
+        // settlement; nothing in this tree exposes that (no `PromiseNativeHandler`-style handler
+        // registration exists anywhere here, and every other promise in this crate is instead
+        // settled synchronously inline via `resolve_native`/`reject_error`). Until a real
+        // combinator is wired in, handler promises are treated as already fulfilled the moment
+        // they are returned, matching that same synchronous-settlement convention.
+        let _ = handler_promises;
+
+        self.finish_the_navigate_event(event, Ok(()));
+    }
+
+    /// Drives event through the remainder of the interception state machine and settles this's
+    /// ongoing API method tracker's finished promise with `result`.
+    ///
+    /// On fulfillment, event moves "committed" → (after [`process_scroll_behavior`](NavigateEvent::process_scroll_behavior),
+    /// unless the handler requested manual scrolling) "scrolled" → "finished", and focus is reset
+    /// unless the handler requested a manual focus reset. On rejection, event moves straight to
+    /// "finished" without scrolling - the navigation's prior scroll position is left untouched
+    /// rather than applying the new one.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#navigateevent-finish>
+    fn finish_the_navigate_event(&self, event: &NavigateEvent, result: Fallible<()>) {
+        let did_fulfill = result.is_ok();
+
+        if did_fulfill {
+            if self.suppress_scroll.get() {
+                event.set_interception_state(InterceptionState::Scrolled);
+            } else {
+                event.process_scroll_behavior();
+            }
+
+            event.process_focus_reset(self.focus_changed.get());
+            self.focus_changed.set(false);
+        }
+
+        event.set_interception_state(InterceptionState::Finished);
+
+        if let Some(tracker) = self.ongoing_method_tracker.borrow_mut().take() {
+            match result {
+                Ok(()) => tracker.resolve_the_finished_promise(),
+                // TODO: report the exception to this's relevant global object per "report the
+                // exception".
+                Err(error) => tracker.reject_the_finished_promise(error),
+            }
+        }
+
+        // Upon fulfillment or rejection of the finished promise, this's transition is set to
+        // null. Since a transition's finished promise is this's ongoing API method tracker's
+        // finished promise, settling that above already propagates to any observer of
+        // `navigation.transition.finished`; this just drops navigation's own reference.
+        *self.transition.borrow_mut() = None;
+
+        // Fire `navigatesuccess` or `navigateerror` at this, matching the outcome of the finished
+        // promise settled above.
+        //
+        // TODO: `navigateerror` should be an `ErrorEvent` carrying the rejection reason in its
+        // `error`/`message` fields, but this tree has no `ErrorEvent` DOM type yet; fire a plain
+        // `Event` of the right type in the meantime so `onnavigateerror` handlers still run.
+        let event_type = if did_fulfill {
+            "navigatesuccess"
+        } else {
+            "navigateerror"
+        };
+
+        let outcome_event = Event::new(
+            &self.global(),
+            Atom::from(event_type),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            CanGc::note(),
+        );
+
+        outcome_event.fire(self.upcast::<EventTarget>(), CanGc::note());
+    }
+
+    /// Aborts this's ongoing navigate event, if any: signals its `AbortSignal` with an
+    /// "AbortError", rejects the ongoing API method tracker's finished promise (and its committed
+    /// promise too, if the navigation has not yet committed to an entry) with the same reason,
+    /// and clears this's ongoing navigate event and ongoing API method tracker so that a new
+    /// navigation or traversal can begin cleanly.
+    ///
+    /// Per spec this is queued as a task on the
+    /// [`NavigationAndTraversalTaskSource`](crate::task_source::navigation_and_traversal::NavigationAndTraversalTaskSource)
+    /// rather than run inline. Doing that needs the same `GlobalScope`/`task!`-macro plumbing the
+    /// aspirational sketch in `Navigate()` is already waiting on (no `script_thread.rs` or
+    /// `GlobalScope` task-source accessor exists in this snapshot to queue onto), so this still
+    /// runs synchronously, like every other step of this algorithm today.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#abort-the-ongoing-navigation>
+    #[allow(unsafe_code)]
+    fn abort_the_ongoing_navigation(&self) {
+        let Some(event) = self.ongoing_event.borrow_mut().take() else {
+            return;
+        };
+
+        event.set_interception_state(InterceptionState::Finished);
+
+        // Passing an undefined reason lets `signal_abort` fill in a fresh "AbortError"
+        // DOMException itself, matching what an uncaught abort of this signal would look like.
+        let cx = *GlobalScope::get_cx();
+        rooted!(in(cx) let reason = UndefinedValue());
+        event.Signal().signal_abort(reason.handle());
+
+        if let Some(tracker) = self.ongoing_method_tracker.borrow_mut().take() {
+            tracker.reject_the_finished_promise(Error::Abort);
+
+            if tracker.committed_to_entry.is_none() {
+                tracker.committed_promise.reject_error(Error::Abort);
+            }
+        }
+
+        *self.transition.borrow_mut() = None;
+    }
 }
 
 #[allow(non_snake_case)]
@@ -424,22 +824,22 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigation-updatecurrententry>
     fn UpdateCurrentEntry(
         &self,
-        _options: RootedTraceableBox<NavigationUpdateCurrentEntryOptions>,
+        options: RootedTraceableBox<NavigationUpdateCurrentEntryOptions>,
     ) -> Fallible<()> {
         // Step 1. Let current be the current entry of this.
         let current = self.GetCurrentEntry();
 
         // Step 2. If current is null, then throw an "InvalidStateError" DOMException.
-        if current.is_none() {
+        let Some(current) = current else {
             return Err(Error::InvalidState);
-        }
+        };
 
         // Step 3. Let serializedState be StructuredSerializeForStorage(options["state"]),
         // rethrowing any exceptions.
-        // TODO
+        let serialized_state = self.serialize_state(options.state.handle())?;
 
         // Step 4. Set current's session history entry's navigation API state to serializedState.
-        // TODO
+        current.set_navigation_api_state(serialized_state);
 
         // Step 5. Fire an event named currententrychange at this using
         // NavigationCurrentEntryChangeEvent, with its navigationType attribute initialized to null
@@ -447,10 +847,10 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
         let event_init = NavigationCurrentEntryChangeEventInit {
             parent: EventInit::empty(),
             navigationType: None,
-            from: current.unwrap(),
+            from: current,
         };
 
-        let _event = NavigationCurrentEntryChangeEvent::new(
+        let event = NavigationCurrentEntryChangeEvent::new(
             &self.window,
             None,
             Atom::from("currententrychange"),
@@ -458,11 +858,15 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
             CanGc::note(),
         );
 
+        event
+            .upcast::<Event>()
+            .fire(self.upcast::<EventTarget>(), CanGc::note());
+
         Ok(())
     }
 
     fn GetTransition(&self) -> Option<DomRoot<NavigationTransition>> {
-        None
+        self.transition.borrow().clone()
     }
 
     fn GetActivation(&self) -> Option<DomRoot<NavigationActivation>> {
@@ -509,6 +913,7 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
         &self,
         url: USVString,
         options: RootedTraceableBox<NavigationNavigateOptions>,
+        can_gc: CanGc,
     ) -> NavigationResult {
         // Step 3. Let document be this's relevant global object's associated Document.
         // Note: Done early to correctly parse the URL with a base
@@ -533,11 +938,14 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
         }
 
         // Step 5. Let state be options["state"], if it exists; otherwise, undefined.
-        let _state = options.state.handle();
+        let state = options.state.handle();
 
         // Step 6. Let serializedState be StructuredSerializeForStorage(state). If this throws an
         // exception, then return an early error result for that exception.
-        // TODO
+        let serialized_state = match self.serialize_state(state) {
+            Ok(serialized_state) => serialized_state,
+            Err(error) => return self.early_error_result(error),
+        };
 
         // Step 7. If document is not fully active, then return an early error result for an
         // "InvalidStateError" DOMException.
@@ -552,12 +960,22 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
         }
 
         // Step 9. Let info be options["info"], if it exists; otherwise, undefined.
+        let info = options.info.handle().get();
 
         // Step 10. Let apiMethodTracker be the result of maybe setting the upcoming non-traverse
         // API method tracker for this given info and serializedState.
+        let api_method_tracker = self
+            .maybe_set_the_upcoming_non_traverse_api_method_tracker(info, Some(serialized_state.clone()));
 
         // Step 11. Navigate document's node navigable to urlRecord using document, with
         // historyHandling set to options["history"] and navigationAPIState set to serializedState.
+        //
+        // There is no navigable/traversable load pipeline in this tree yet to drive a real
+        // cross-document load, so only the same-document (fragment-only) case is handled here:
+        // fire the `navigate` event directly so script observes and can intercept it. A full
+        // cross-document navigation still needs the `task!(navigate: ...)` wiring sketched below
+        // once `Window::load_url` et al. land.
+        //
         // let this = Trusted::new(self);
         // let window = Trusted::new(&self.window);
         // let task = task!(navigate: move || {
@@ -573,29 +991,86 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
         //             CanGc::note(),
         //         );
         // });
+        let current_url = document.url();
+        let same_document = current_url.scheme() == url_record.scheme() &&
+            current_url.host() == url_record.host() &&
+            current_url.port() == url_record.port() &&
+            current_url.path() == url_record.path() &&
+            current_url.query() == url_record.query();
+        let hash_change = same_document && current_url.fragment() != url_record.fragment();
+
+        let navigation_type = match &options.history {
+            NavigationHistoryBehavior::Replace => NavigationType::Replace,
+            // "auto" becomes a replace where the navigation must be a replace; "push" never
+            // reaches here in that case, having been turned away by the early error above.
+            NavigationHistoryBehavior::Auto
+                if url_record.scheme() == "javascript" || document.is_initial_about_blank() =>
+            {
+                NavigationType::Replace
+            },
+            _ => NavigationType::Push,
+        };
 
         // Step 12. If this's upcoming non-traverse API method tracker is apiMethodTracker, then:
-        // TODO
+        // Step 12.1. Set this's ongoing API method tracker to apiMethodTracker.
+        // Step 12.2. Set this's upcoming non-traverse API method tracker to null.
+        if self.upcoming_non_traverse_method_tracker.borrow().is_some() {
+            *self.ongoing_method_tracker.borrow_mut() =
+                self.upcoming_non_traverse_method_tracker.borrow_mut().take();
+        }
+
+        if same_document {
+            let destination = NavigationDestination::new(
+                &self.global(),
+                url_record,
+                same_document,
+                serialized_state,
+                can_gc,
+            );
+
+            let signal = AbortSignal::new(&self.global(), false);
+
+            self.inner_navigate_event_firing_algorithm(
+                navigation_type,
+                destination,
+                true,
+                false,
+                hash_change,
+                signal,
+                None,
+                can_gc,
+            );
+        }
 
         // Step 13. Return a navigation API method tracker-derived result for apiMethodTracker.
-        // TODO
-        self.early_error_result(Error::Syntax)
+        self.method_tracker_derived_result(api_method_tracker)
     }
 
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigation-reload>
     fn Reload(
         &self,
-        _options: RootedTraceableBox<NavigationReloadOptions>,
+        options: RootedTraceableBox<NavigationReloadOptions>,
+        can_gc: CanGc,
     ) -> Fallible<NavigationResult> {
         // Step 1. Let document be this's relevant global object's associated Document.
         let document = &self.window.Document();
 
-        // Step 2. Let serializedState be StructuredSerializeForStorage(undefined).
-        // TODO
-
-        // Step 3. If options["state"] exists, then set serializedState to
-        // StructuredSerializeForStorage(options["state"]). If this throws an exception, then return
-        // an early error result for that exception.
+        // Step 2. Let serializedState be StructuredSerializeForStorage(undefined), i.e. nothing new
+        // to carry forward; fall back to the current entry's existing navigation API state so a
+        // plain reload() doesn't discard state set by a previous navigate()/updateCurrentEntry().
+        let state = options.state.handle();
+        let serialized_state = if state.is_undefined() {
+            self.GetCurrentEntry()
+                .and_then(|entry| entry.navigation_api_state())
+        } else {
+            // Step 3. If options["state"] exists, then set serializedState to
+            // StructuredSerializeForStorage(options["state"]). If this throws an exception, then
+            // return an early error result for that exception.
+            match self.serialize_state(state) {
+                Ok(serialized_state) => Some(serialized_state),
+                Err(error) => return Ok(self.early_error_result(error)),
+            }
+        };
 
         // Step 5. If document is not fully active, then return an early error result for an
         // "InvalidStateError" DOMException.
@@ -603,11 +1078,57 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
             return Err(Error::InvalidState);
         }
 
+        // Step 6. If document's unload counter is greater than 0, then return an early error
+        // result for an "InvalidStateError" DOMException.
+        if document.get_unload_counter_value() > 0 {
+            return Ok(self.early_error_result(Error::InvalidState));
+        }
+
+        // Step 7. Let info be options["info"], if it exists; otherwise, undefined.
+        let info = options.info.handle().get();
+
         // Step 8. Let apiMethodTracker be the result of maybe setting the upcoming non-traverse API
         // method tracker for this given info and serializedState.
-        let api_method_tracker = self.maybe_set_the_upcoming_non_traverse_api_method_tracker();
+        let api_method_tracker = self.maybe_set_the_upcoming_non_traverse_api_method_tracker(
+            info,
+            serialized_state.clone(),
+        );
 
         // Step 9. Reload document's node navigable with navigationAPIState set to serializedState.
+        //
+        // There is no navigable/traversable reload pipeline in this tree yet to drive a real
+        // re-fetch of the document, so — same as Navigate()'s same-document fallback above — fire
+        // the `navigate` event directly with navigationType "reload" so script observes and can
+        // intercept it, and gets a `transition` object for the duration.
+        if self.upcoming_non_traverse_method_tracker.borrow().is_some() {
+            *self.ongoing_method_tracker.borrow_mut() =
+                self.upcoming_non_traverse_method_tracker.borrow_mut().take();
+        }
+
+        let destination = NavigationDestination::new(
+            &self.global(),
+            document.url(),
+            true,
+            serialized_state.unwrap_or_else(|| StructuredSerializedData {
+                serialized: vec![],
+                ports: None,
+                blobs: None,
+            }),
+            can_gc,
+        );
+
+        let signal = AbortSignal::new(&self.global(), false);
+
+        self.inner_navigate_event_firing_algorithm(
+            NavigationType::Reload,
+            destination,
+            true,
+            false,
+            false,
+            signal,
+            None,
+            can_gc,
+        );
 
         // Step 10. Return a navigation API method tracker-derived result for apiMethodTracker.
         Ok(self.method_tracker_derived_result(api_method_tracker))
@@ -616,30 +1137,25 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigation-traverseto>
     fn TraverseTo(
         &self,
-        _key: DOMString,
+        key: DOMString,
         options: RootedTraceableBox<NavigationOptions>,
     ) -> NavigationResult {
         // Step 1. If this's current entry index is −1, then return an early error result for an
         // "InvalidStateError" DOMException.
-        match self.current_entry_index {
-            None => self.early_error_result(Error::InvalidState),
-            Some(i) if i < 1 || i == self.entry_list.len() => {
-                self.early_error_result(Error::InvalidState)
-            },
-            Some(i) => {
-                // Step 2. If this's entry list does not contain a NavigationHistoryEntry whose
-                // session history entry's navigation API key equals key, then return an early error
-                // result for an "InvalidStateError" DOMException.
-                match self.entry_list.get(i + 1) {
-                    Some(entry) => {
-                        // Step 3. Return the result of performing a navigation API traversal given
-                        // this, key, and options.
-                        self.perform_a_navigation_api_traversal(entry.Key(), Some(options))
-                    },
-                    None => self.early_error_result(Error::InvalidState),
-                }
-            },
+        if self.current_entry_index.is_none() {
+            return self.early_error_result(Error::InvalidState);
         }
+
+        // Step 2. If this's entry list does not contain a NavigationHistoryEntry whose session
+        // history entry's navigation API key equals key, then return an early error result for an
+        // "InvalidStateError" DOMException.
+        if !self.entry_list.iter().any(|entry| entry.Key() == key) {
+            return self.early_error_result(Error::InvalidState);
+        }
+
+        // Step 3. Return the result of performing a navigation API traversal given this, key, and
+        // options.
+        self.perform_a_navigation_api_traversal(key, Some(options))
     }
 
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigation-back>
@@ -666,17 +1182,21 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
 
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigation-forward>
     fn Forward(&self, options: RootedTraceableBox<NavigationOptions>) -> NavigationResult {
-        // Step 1
+        // Step 1. If this's current entry index is −1, or this's current entry index is equal to
+        // this's entry list's size − 1, then return an early error result for an
+        // "InvalidStateError" DOMException.
         match self.current_entry_index {
             None => self.early_error_result(Error::InvalidState),
-            Some(i) if i < 1 || i == self.entry_list.len() => {
+            Some(i) if i + 1 >= self.entry_list.len() => {
                 self.early_error_result(Error::InvalidState)
             },
             Some(i) => {
-                // Step 2
+                // Step 2. Let key be this's entry list[this's current entry index + 1]'s session
+                // history entry's navigation API key.
                 match self.entry_list.get(i + 1) {
                     Some(entry) => {
-                        // Step 3
+                        // Step 3. Return the result of performing a navigation API traversal given
+                        // this, key, and options.
                         self.perform_a_navigation_api_traversal(entry.Key(), Some(options))
                     },
                     None => self.early_error_result(Error::InvalidState),
@@ -688,29 +1208,12 @@ impl NavigationMethods<crate::DomTypeHolder> for Navigation {
     // <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-onnavigate>
     event_handler!(navigate, GetOnnavigate, SetOnnavigate);
 
-    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-onnavigatesuccess>
-    fn GetOnnavigatesuccess(&self) -> Option<Rc<EventHandlerNonNull>> {
-        None
-    }
-
-    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-onnavigatesuccess>
-    fn SetOnnavigatesuccess(&self, _value: Option<Rc<EventHandlerNonNull>>) {}
-
-    // error_event_handler!(onnavigateerror, GetOnnavigateerror, SetOnnavigateerror)
+    // <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-onnavigatesuccess>
+    event_handler!(navigatesuccess, GetOnnavigatesuccess, SetOnnavigatesuccess);
 
-    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-onnavigateerror>
-    fn GetOnnavigateerror(&self) -> Option<Rc<EventHandlerNonNull>> {
-        None
-    }
-
-    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-onnavigateerror>
-    fn SetOnnavigateerror(&self, _value: Option<Rc<EventHandlerNonNull>>) {}
-
-    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-oncurrententrychange>
-    fn GetOncurrententrychange(&self) -> Option<Rc<EventHandlerNonNull>> {
-        None
-    }
+    // <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-onnavigateerror>
+    event_handler!(navigateerror, GetOnnavigateerror, SetOnnavigateerror);
 
-    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-oncurrententrychange>
-    fn SetOncurrententrychange(&self, _value: Option<Rc<EventHandlerNonNull>>) {}
+    // <https://html.spec.whatwg.org/multipage/nav-history-apis.html#handler-navigation-oncurrententrychange>
+    event_handler!(currententrychange, GetOncurrententrychange, SetOncurrententrychange);
 }
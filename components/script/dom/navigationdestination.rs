@@ -3,16 +3,20 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
-use servo_url::ServoUrl;
+use js::jsval::UndefinedValue;
 use js::rust::MutableHandleValue;
+use servo_url::ServoUrl;
 use script_traits::StructuredSerializedData;
 
 use crate::dom::bindings::codegen::Bindings::NavigationHistoryEntryBinding::NavigationHistoryEntry_Binding::NavigationHistoryEntryMethods;
 use crate::dom::bindings::codegen::Bindings::NavigationDestinationBinding::NavigationDestinationMethods;
-use crate::dom::bindings::reflector::{DomObject, Reflector};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::navigationhistoryentry::NavigationHistoryEntry;
-use crate::script_runtime::JSContext;
+use crate::script_runtime::{CanGc, JSContext};
 
 /// <https://html.spec.whatwg.org/multipage/#the-navigationdestination-interface>
 #[dom_struct]
@@ -28,6 +32,40 @@ pub struct NavigationDestination {
     state: StructuredSerializedData,
 }
 
+impl NavigationDestination {
+    fn new_inherited(
+        url: ServoUrl,
+        same_document: bool,
+        state: StructuredSerializedData,
+    ) -> NavigationDestination {
+        NavigationDestination {
+            reflector_: Reflector::new(),
+            url,
+            key: DOMString::new(),
+            id: DOMString::new(),
+            same_document,
+            entry: None,
+            state,
+        }
+    }
+
+    /// Constructs the `NavigationDestination` describing a navigation's target. `entry` is always
+    /// null (nothing sets it), so `Key()`/`Id()`/`Index()` report their empty-string/-1 defaults.
+    pub(crate) fn new(
+        global: &GlobalScope,
+        url: ServoUrl,
+        same_document: bool,
+        state: StructuredSerializedData,
+        can_gc: CanGc,
+    ) -> DomRoot<NavigationDestination> {
+        reflect_dom_object(
+            Box::new(NavigationDestination::new_inherited(url, same_document, state)),
+            global,
+            can_gc,
+        )
+    }
+}
+
 impl NavigationDestinationMethods<crate::DomTypeHolder> for NavigationDestination {
     /// <https://html.spec.whatwg.org/multipage/#dom-navigationdestination-url>
     fn Url(&self) -> USVString {
@@ -72,17 +110,15 @@ impl NavigationDestinationMethods<crate::DomTypeHolder> for NavigationDestinatio
     /// The getState() method steps are to return StructuredDeserialize(this's state).
     ///
     /// <https://html.spec.whatwg.org/multipage/#dom-navigationdestination-getstate>
-    fn GetState(&self, _cx: JSContext, _rval: MutableHandleValue) {
-        todo!()
+    fn GetState(&self, _cx: JSContext, rval: MutableHandleValue) {
+        let data = StructuredSerializedData {
+            serialized: self.state.serialized.clone(),
+            ports: None,
+            blobs: None,
+        };
 
-        // let data = StructuredSerializedData {
-        //     serialized: self.state.serialized.clone(),
-        //     ports: None,
-        //     blobs: None,
-        // };
-
-        // if let Ok(data) = structuredclone::read(&self.global(), data, _rval) {
-        // } else {
-        // }
+        if structuredclone::read(&self.global(), data, rval).is_err() {
+            rval.set(UndefinedValue());
+        }
     }
 }
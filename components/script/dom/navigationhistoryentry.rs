@@ -3,15 +3,22 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
-use net_traits::session_history::SessionHistoryEntry;
+use js::jsval::UndefinedValue;
+use script_traits::session_history::SessionHistoryEntry;
+use script_traits::StructuredSerializedData;
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NavigationHistoryEntryBinding::NavigationHistoryEntryMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
 use crate::dom::document::Document;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
+use crate::script_runtime::CanGc;
 
 /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#navigationhistoryentry>
 #[dom_struct]
@@ -23,7 +30,7 @@ pub struct NavigationHistoryEntry {
     index: i64,
     #[no_trace]
     #[ignore_malloc_size_of = "todo"]
-    session_history_entry: SessionHistoryEntry,
+    session_history_entry: DomRefCell<SessionHistoryEntry>,
 }
 
 impl NavigationHistoryEntry {
@@ -33,6 +40,38 @@ impl NavigationHistoryEntry {
 
         window.Document()
     }
+
+    /// Sets this entry's session history entry's navigation API state to `state`, per step 4 of
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigation-updatecurrententry>.
+    pub(crate) fn set_navigation_api_state(&self, state: StructuredSerializedData) {
+        self.session_history_entry
+            .borrow_mut()
+            .set_navigation_api_state(state);
+    }
+
+    /// This entry's session history entry's navigation API state, if any.
+    pub(crate) fn navigation_api_state(&self) -> Option<StructuredSerializedData> {
+        self.session_history_entry.borrow().navigation_api_state()
+    }
+
+    /// The dispose steps for a `NavigationHistoryEntry` `entry` are to fire an event named
+    /// `dispose` at `entry`.
+    ///
+    /// Only [`Navigation::dispose_entries`](crate::dom::navigation::Navigation::dispose_entries)
+    /// calls this, and nothing calls that, so pruning session history entries does not fire it.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#navigationhistoryentry-dispose>
+    pub(crate) fn dispose(&self, can_gc: CanGc) {
+        let event = Event::new(
+            &self.global(),
+            atom!("dispose"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            can_gc,
+        );
+
+        event.fire(self.upcast::<EventTarget>(), can_gc);
+    }
 }
 
 impl NavigationHistoryEntryMethods<crate::DomTypeHolder> for NavigationHistoryEntry {
@@ -82,35 +121,29 @@ impl NavigationHistoryEntryMethods<crate::DomTypeHolder> for NavigationHistoryEn
 
         // Step 3. Return true if this's session history entry's document equals document, and false
         // otherwise.
-
-        todo!()
+        self.session_history_entry.borrow().document_state.document_id == document.id()
     }
 
     /// <https://html.spec.whatwg.org/multipage/#dom-navigationhistoryentry-getstate>
-    fn GetState(&self, cx: crate::script_runtime::JSContext, rval: js::gc::MutableHandleValue) {
-        todo!()
-    }
-
-    /// <https://html.spec.whatwg.org/multipage/#handler-navigationhistoryentry-ondispose>
-    fn GetOndispose(
-        &self,
-    ) -> Option<
-        std::rc::Rc<
-            crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull,
-        >,
-    > {
-        todo!()
+    fn GetState(&self, _cx: crate::script_runtime::JSContext, rval: js::gc::MutableHandleValue) {
+        // The getState() method steps are to return StructuredDeserialize(this's session
+        // history entry's navigation API state), if that is not null; otherwise undefined.
+        let Some(state) = self.session_history_entry.borrow().navigation_api_state() else {
+            rval.set(UndefinedValue());
+            return;
+        };
+
+        let data = StructuredSerializedData {
+            serialized: state.serialized.clone(),
+            ports: None,
+            blobs: None,
+        };
+
+        if structuredclone::read(&self.global(), data, rval).is_err() {
+            rval.set(UndefinedValue());
+        }
     }
 
     /// <https://html.spec.whatwg.org/multipage/#handler-navigationhistoryentry-ondispose>
-    fn SetOndispose(
-        &self,
-        value: Option<
-            std::rc::Rc<
-                super::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull,
-            >,
-        >,
-    ) {
-        todo!()
-    }
+    event_handler!(dispose, GetOndispose, SetOndispose);
 }
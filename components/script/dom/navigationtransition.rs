@@ -8,10 +8,12 @@ use dom_struct::dom_struct;
 
 use crate::dom::bindings::codegen::Bindings::NavigationBinding::NavigationType;
 use crate::dom::bindings::codegen::Bindings::NavigationTransitionBinding::NavigationTransitionMethods;
-use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::navigationhistoryentry::NavigationHistoryEntry;
 use crate::dom::promise::Promise;
+use crate::script_runtime::CanGc;
 
 /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#navigationtransition>
 #[dom_struct]
@@ -24,6 +26,46 @@ pub struct NavigationTransition {
     finished_promise: Rc<Promise>,
 }
 
+impl NavigationTransition {
+    fn new_inherited(
+        navigation_type: NavigationType,
+        old_entry: DomRoot<NavigationHistoryEntry>,
+        new_entry: DomRoot<NavigationHistoryEntry>,
+        finished_promise: Rc<Promise>,
+    ) -> NavigationTransition {
+        NavigationTransition {
+            reflector_: Reflector::new(),
+            old_entry,
+            new_entry,
+            navigation_type,
+            finished_promise,
+        }
+    }
+
+    /// Constructs the `NavigationTransition` live for the duration of an in-flight navigation,
+    /// carrying the navigation's type, its `from` entry, and a "finished" promise that settles
+    /// alongside the driving navigation API method tracker's own finished promise.
+    pub(crate) fn new(
+        global: &GlobalScope,
+        navigation_type: NavigationType,
+        old_entry: DomRoot<NavigationHistoryEntry>,
+        new_entry: DomRoot<NavigationHistoryEntry>,
+        finished_promise: Rc<Promise>,
+        can_gc: CanGc,
+    ) -> DomRoot<NavigationTransition> {
+        reflect_dom_object(
+            Box::new(NavigationTransition::new_inherited(
+                navigation_type,
+                old_entry,
+                new_entry,
+                finished_promise,
+            )),
+            global,
+            can_gc,
+        )
+    }
+}
+
 impl NavigationTransitionMethods<crate::DomTypeHolder> for NavigationTransition {
     /// <https://html.spec.whatwg.org/multipage/#dom-navigationactivation-from>
     fn From(&self) -> DomRoot<NavigationHistoryEntry> {
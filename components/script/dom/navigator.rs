@@ -4,32 +4,38 @@
 
 use std::cell::Cell;
 use std::convert::TryInto;
+use std::rc::Rc;
 use std::sync::LazyLock;
 
 use dom_struct::dom_struct;
 use http::header::CONTENT_TYPE;
 use http::{HeaderMap, Method};
 use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
 use js::jsval::JSVal;
 use lazy_static::lazy_static;
 use net_traits::request::{
     is_cors_safelisted_request_header, CredentialsMode, RequestBuilder, RequestMode,
 };
+use net_traits::FetchResponseMsg;
 use servo_url::ServoUrl;
 
 use crate::body::{Extractable, ExtractedBody};
 use crate::document_loader::LoadType;
+use crate::dom::batterymanager::{BatteryManager, BatteryStatus};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
 use crate::dom::bindings::codegen::UnionTypes::ReadableStreamOrXMLHttpRequestBodyInit;
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::bluetooth::Bluetooth;
+use crate::dom::credentialscontainer::CredentialsContainer;
 use crate::dom::gamepad::Gamepad;
 use crate::dom::gamepadevent::GamepadEventType;
 use crate::dom::gpu::GPU;
@@ -37,11 +43,14 @@ use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mediasession::MediaSession;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
+use crate::dom::networkinformation::{NetworkInformation, NetworkInformationStatus};
 use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
+use crate::dom::promise::Promise;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
 use crate::dom::window::Window;
 use crate::dom::xrsystem::XRSystem;
+use crate::realms::{AlreadyInRealm, InRealm};
 use crate::script_runtime::JSContext;
 
 pub(super) fn hardware_concurrency() -> u64 {
@@ -50,6 +59,35 @@ pub(super) fn hardware_concurrency() -> u64 {
     *CPUS
 }
 
+/// The `navigator.vibrate()` pattern argument: either a single vibration duration, or an
+/// alternating vibrate/pause duration sequence.
+///
+/// <https://w3c.github.io/vibration/#dom-navigator-vibrate>
+///
+/// The real WebIDL binding for `Vibrate` takes a `(unsigned long or sequence<unsigned long>)`
+/// union, but that union type isn't available without the `.webidl`/codegen for this interface
+/// (absent in this snapshot), so this plain enum stands in for it.
+pub enum VibrationPattern {
+    Single(u32),
+    Sequence(Vec<u32>),
+}
+
+/// <https://w3c.github.io/vibration/#idl-def-perform-vibration>: the maximum number of entries
+/// accepted in a vibration pattern.
+const MAX_VIBRATION_PATTERN_LENGTH: usize = 10_000;
+/// <https://w3c.github.io/vibration/#dfn-max-vibration-duration>
+const MAX_VIBRATION_DURATION_MS: u32 = 10_000;
+
+/// <https://fetch.spec.whatwg.org/#http-network-or-cache-fetch>: the total number of bytes this
+/// global is allowed to have in flight across all of its keepalive-enabled requests at once.
+/// `SendBeacon` is, for now, the only caller that reserves against this budget.
+const KEEPALIVE_REQUEST_BYTE_BUDGET: usize = 65_536;
+
+/// <https://www.w3.org/TR/gamepad/#dfn-gamepad-user-gesture>: the magnitude, relative to a
+/// button or axis' neutral resting value, that a reported value must cross to count as the
+/// "significant changes" that constitute a gamepad user gesture.
+const GAMEPAD_GESTURE_THRESHOLD: f64 = 0.1;
+
 #[dom_struct]
 pub struct Navigator {
     reflector_: Reflector,
@@ -66,6 +104,14 @@ pub struct Navigator {
     gpu: MutNullableDom<GPU>,
     /// <https://www.w3.org/TR/gamepad/#dfn-hasgamepadgesture>
     has_gamepad_gesture: Cell<bool>,
+    battery_manager: MutNullableDom<BatteryManager>,
+    connection: MutNullableDom<NetworkInformation>,
+    /// The remaining byte budget for in-flight keepalive requests, shared across every call to
+    /// `SendBeacon` made through this `Navigator`.
+    ///
+    /// <https://fetch.spec.whatwg.org/#http-network-or-cache-fetch>
+    keepalive_bytes_remaining: Cell<usize>,
+    credentials: MutNullableDom<CredentialsContainer>,
 }
 
 impl Navigator {
@@ -83,6 +129,10 @@ impl Navigator {
             mediasession: Default::default(),
             gpu: Default::default(),
             has_gamepad_gesture: Cell::new(false),
+            battery_manager: Default::default(),
+            connection: Default::default(),
+            keepalive_bytes_remaining: Cell::new(KEEPALIVE_REQUEST_BYTE_BUDGET),
+            credentials: Default::default(),
         }
     }
 
@@ -111,10 +161,46 @@ impl Navigator {
     }
 
     pub fn remove_gamepad(&self, index: usize) {
+        let removed_gamepad = self.gamepads.borrow().get(index).and_then(|g| g.get());
+
         if let Some(gamepad_to_remove) = self.gamepads.borrow_mut().get(index) {
             gamepad_to_remove.set(None);
         }
         self.shrink_gamepads_list();
+
+        // Only gamepads that were actually exposed to script (connected after a gesture had
+        // already been observed, or already connected when `notify_gamepad_input` below saw the
+        // first one) got a `gamepadconnected` event, so only those get the matching
+        // `gamepaddisconnected` event here.
+        if let Some(gamepad) = removed_gamepad {
+            if gamepad.exposed() && self.global().as_window().Document().is_fully_active() {
+                gamepad.notify_event(GamepadEventType::Disconnected);
+            }
+        }
+    }
+
+    /// <https://www.w3.org/TR/gamepad/#dfn-gamepad-user-gesture>
+    ///
+    /// Called whenever a connected gamepad reports a new button or axis value. The first value
+    /// to cross the significant-change threshold satisfies the gesture for the lifetime of this
+    /// `Navigator`: every currently-connected gamepad is retroactively exposed and a
+    /// `gamepadconnected` event fires for each. Until then `GetGamepads()` returns an empty list;
+    /// from then on it returns every connected gamepad, including those connected before the
+    /// gesture.
+    pub fn notify_gamepad_input(&self, value: f64) {
+        if self.has_gamepad_gesture.get() || value.abs() < GAMEPAD_GESTURE_THRESHOLD {
+            return;
+        }
+
+        self.has_gamepad_gesture.set(true);
+
+        let document = self.global().as_window().Document();
+        for gamepad in self.gamepads.borrow().iter().filter_map(|g| g.get()) {
+            gamepad.set_exposed(true);
+            if document.is_fully_active() {
+                gamepad.notify_event(GamepadEventType::Connected);
+            }
+        }
     }
 
     /// <https://www.w3.org/TR/gamepad/#dfn-selecting-an-unused-gamepad-index>
@@ -147,6 +233,110 @@ impl Navigator {
     pub fn set_has_gamepad_gesture(&self, has_gamepad_gesture: bool) {
         self.has_gamepad_gesture.set(has_gamepad_gesture);
     }
+
+    /// Give back bytes reserved by a previous call to [`Navigator::reserve_keepalive_budget`],
+    /// once the keepalive request they were reserved for has finished.
+    fn restore_keepalive_budget(&self, bytes: usize) {
+        let remaining = self.keepalive_bytes_remaining.get();
+        self.keepalive_bytes_remaining
+            .set((remaining + bytes).min(KEEPALIVE_REQUEST_BYTE_BUDGET));
+    }
+
+    /// Reserve `bytes` against the keepalive request size budget, returning `false` without
+    /// reserving anything if that would exceed the remaining budget.
+    ///
+    /// <https://fetch.spec.whatwg.org/#http-network-or-cache-fetch>: "If termination triggers are
+    /// added and the request's mode is keepalive, and the total size of requests enqueued for
+    /// sending is greater than 64 kibibytes, then set the return value to false."
+    fn reserve_keepalive_budget(&self, bytes: usize) -> bool {
+        let remaining = self.keepalive_bytes_remaining.get();
+        if bytes > remaining {
+            return false;
+        }
+        self.keepalive_bytes_remaining.set(remaining - bytes);
+        true
+    }
+
+    fn battery_manager(&self) -> DomRoot<BatteryManager> {
+        self.battery_manager
+            .or_init(|| BatteryManager::new(&self.global(), BatteryStatus::default_status()))
+    }
+
+    /// <https://w3c.github.io/battery-status/#dom-navigator-getbattery>
+    ///
+    /// Exposing this as an actual WebIDL method on `Navigator` needs the `.webidl` definitions
+    /// and codegen output for `getBattery`/`BatteryManager`, neither of which exist in this
+    /// snapshot, so it's surfaced here as a plain method instead.
+    pub fn get_battery(&self) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
+        promise.resolve_native(&self.battery_manager());
+        promise
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-navigator-connection>
+    ///
+    /// Exposing this as an actual WebIDL attribute on `Navigator` needs the `.webidl` definitions
+    /// and codegen output for `NetworkInformation`, neither of which exist in this snapshot, so
+    /// it's surfaced here as a plain method instead.
+    pub fn connection(&self) -> DomRoot<NetworkInformation> {
+        self.connection.or_init(|| {
+            NetworkInformation::new(&self.global(), NetworkInformationStatus::default_status())
+        })
+    }
+
+    /// <https://w3c.github.io/vibration/#dom-navigator-vibrate>
+    ///
+    /// Exposing this as an actual WebIDL method on `Navigator` needs the `.webidl` definitions
+    /// and codegen output for `Vibrate`, neither of which exist in this snapshot, so it's
+    /// surfaced here as a plain method instead.
+    ///
+    /// Dispatching the (validated) pattern to the embedder needs a vibration variant of
+    /// `EmbedderMsg`, which lives in the external `embedder_traits` crate and can't be extended
+    /// from here, so this always takes the no-op embedder fallback the spec allows for
+    /// devices/builds with no vibration hardware: accept the call and report success without
+    /// actually vibrating anything.
+    pub fn vibrate(&self, pattern: VibrationPattern) -> bool {
+        // Step 1: Let pattern be the result of normalizing the pattern.
+        let mut pattern = match pattern {
+            VibrationPattern::Single(duration) => vec![duration],
+            VibrationPattern::Sequence(durations) => durations,
+        };
+
+        // If the pattern has too many entries, truncate it.
+        pattern.truncate(MAX_VIBRATION_PATTERN_LENGTH);
+
+        // Clamp every entry to the maximum duration.
+        for duration in pattern.iter_mut() {
+            *duration = (*duration).min(MAX_VIBRATION_DURATION_MS);
+        }
+
+        // Cancel any ongoing vibration: a pattern of `0` or an empty sequence.
+        if pattern.iter().all(|&duration| duration == 0) {
+            return true;
+        }
+
+        // Step 2: If document is not fully active, return false.
+        let document = self.global().as_window().Document();
+        if !document.is_fully_active() {
+            return false;
+        }
+
+        // Step 3: Perform the vibration (no-op fallback, see doc comment above). A new call
+        // pre-empts any previous pattern, which is trivially true here since nothing is ever
+        // actually dispatched to run.
+        true
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#framework-credential-management>
+    ///
+    /// Exposing this as an actual WebIDL attribute on `Navigator` needs the `.webidl` definitions
+    /// and codegen output for `CredentialsContainer`, neither of which exist in this snapshot, so
+    /// it's surfaced here as a plain method instead.
+    pub fn credentials(&self) -> DomRoot<CredentialsContainer> {
+        self.credentials
+            .or_init(|| CredentialsContainer::new(&self.global()))
+    }
 }
 
 #[allow(non_snake_case)]
@@ -335,6 +525,7 @@ impl NavigatorMethods for Navigator {
         let mut request_mode = RequestMode::NoCors;
 
         let mut request_body: Option<ExtractedBody> = None;
+        let mut reserved_bytes = 0;
 
         // Step 6 If data is not null:
         if let Some(request_data) = data {
@@ -360,19 +551,17 @@ impl NavigatorMethods for Navigator {
             // requests is exceeded by the size of transmittedData (as defined in
             // HTTP-network-or-cache fetch), set the return value to false and terminate these
             // steps.
-
-            // Servo does not currently implement keepalive, each request is closed on completion.
-            // The expectation here is that as additional keepalive requests are made, we must
-            // ensure that the total bytes do not exceed a maximum size; correctly determining this
-            // is dependent on an implementation of fetch groups. As a compromise until keepalive is
-            // implemented, prevent individual request from exceeding the limit of 64 kibibytes as
-            // defined in the fetch spec:
-            // https://fetch.spec.whatwg.org/#concept-http-network-or-cache-fetch
-            // https://fetch.spec.whatwg.org/#fetch-groups
+            //
+            // The budget is the *sum* of every keepalive request's body currently in flight for
+            // this global, not a per-request cap, per
+            // https://fetch.spec.whatwg.org/#concept-http-network-or-cache-fetch and
+            // https://fetch.spec.whatwg.org/#fetch-groups. It's reserved here and given back once
+            // the request completes, see the `ROUTER` route set up below.
             if let Some(length) = transmitted_data.total_bytes {
-                if length > 65_536 {
+                if !self.reserve_keepalive_budget(length) {
                     return Ok(false);
                 }
+                reserved_bytes = length;
             }
 
             // Step 6.3: If contentType is not null:
@@ -400,7 +589,6 @@ impl NavigatorMethods for Navigator {
         // let referrer = global.get_referrer();
 
         // Step 7: A new request, initialized according to the spec
-        // TODO: Mark as a keepalive request once supported
         // TODO: Include initiator_type once supported
         let request = RequestBuilder::new(parsed_url, global.get_referrer())
             .method(Method::POST)
@@ -408,10 +596,21 @@ impl NavigatorMethods for Navigator {
             .body(request_body.map(|e| e.into_net_request_body().0))
             .credentials_mode(CredentialsMode::Include)
             .headers(header_list)
-            .origin(origin);
-
-        // This is a send and forget request, so a response listener is omitted
-        let (action_sender, _) = ipc::channel().unwrap();
+            .origin(origin)
+            .keepalive(true);
+
+        // No response body is expected for a beacon, but the channel is still routed so the
+        // reserved keepalive budget can be given back once the request is done.
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let trusted_navigator = Trusted::new(self);
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                if let Ok(FetchResponseMsg::ProcessResponseEOF(..)) = message.to() {
+                    trusted_navigator.root().restore_keepalive_budget(reserved_bytes);
+                }
+            }),
+        );
 
         document.fetch_async(LoadType::Beacon, request, action_sender);
 
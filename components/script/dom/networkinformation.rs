@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::CanGc;
+
+/// The connection estimate backing a [`NetworkInformation`]: either a real reading derived from a
+/// resource-thread-side throughput/latency estimator, or the spec's mandated default for
+/// builds/platforms with no such estimator to query.
+///
+/// <https://wicg.github.io/netinfo/#networkinformation-interface>
+#[derive(Clone)]
+pub struct NetworkInformationStatus {
+    pub effective_type: DOMString,
+    pub connection_type: DOMString,
+    pub downlink: f64,
+    pub rtt: f64,
+    pub save_data: bool,
+}
+
+impl NetworkInformationStatus {
+    /// The reading reported everywhere this snapshot has no resource-thread-side estimator to
+    /// plumb over IPC: an unmetered, unthrottled "4g" connection.
+    ///
+    /// Deriving a real reading needs an EWMA estimator over completed fetches living on the
+    /// resource thread, plus an IPC channel pushing its updates to the script thread, and neither
+    /// exists in this snapshot, so `NetworkInformation` always reports this default.
+    pub fn default_status() -> NetworkInformationStatus {
+        NetworkInformationStatus {
+            effective_type: DOMString::from("4g"),
+            connection_type: DOMString::from("unknown"),
+            downlink: 10.,
+            rtt: 50.,
+            save_data: false,
+        }
+    }
+}
+
+/// <https://wicg.github.io/netinfo/#networkinformation-interface>
+///
+/// Exposing this as an actual WebIDL interface (`navigator.connection`) needs the `.webidl`
+/// definitions and codegen output for `NetworkInformation`, neither of which exist in this
+/// snapshot, so only the underlying DOM object and its `change` event plumbing are implemented
+/// here.
+#[dom_struct]
+pub struct NetworkInformation {
+    eventtarget: EventTarget,
+    effective_type: DomRefCell<DOMString>,
+    connection_type: DomRefCell<DOMString>,
+    downlink: Cell<f64>,
+    rtt: Cell<f64>,
+    save_data: Cell<bool>,
+}
+
+impl NetworkInformation {
+    fn new_inherited(status: NetworkInformationStatus) -> NetworkInformation {
+        NetworkInformation {
+            eventtarget: EventTarget::new_inherited(),
+            effective_type: DomRefCell::new(status.effective_type),
+            connection_type: DomRefCell::new(status.connection_type),
+            downlink: Cell::new(status.downlink),
+            rtt: Cell::new(status.rtt),
+            save_data: Cell::new(status.save_data),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        status: NetworkInformationStatus,
+    ) -> DomRoot<NetworkInformation> {
+        reflect_dom_object(Box::new(NetworkInformation::new_inherited(status)), global)
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-effectivetype>
+    pub fn effective_type(&self) -> DOMString {
+        self.effective_type.borrow().clone()
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-type>
+    pub fn connection_type(&self) -> DOMString {
+        self.connection_type.borrow().clone()
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-downlink>
+    pub fn downlink(&self) -> f64 {
+        self.downlink.get()
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-rtt>
+    pub fn rtt(&self) -> f64 {
+        self.rtt.get()
+    }
+
+    /// <https://wicg.github.io/netinfo/#dom-networkinformation-savedata>
+    pub fn save_data(&self) -> bool {
+        self.save_data.get()
+    }
+
+    /// Apply a new estimate from the resource-thread-side estimator and fire `change` if any of
+    /// the exposed attributes actually changed.
+    pub fn update(&self, status: NetworkInformationStatus, can_gc: CanGc) {
+        let effective_type_changed = *self.effective_type.borrow() != status.effective_type;
+        let connection_type_changed = *self.connection_type.borrow() != status.connection_type;
+        let downlink_changed = self.downlink.replace(status.downlink) != status.downlink;
+        let rtt_changed = self.rtt.replace(status.rtt) != status.rtt;
+        let save_data_changed = self.save_data.replace(status.save_data) != status.save_data;
+        let changed = effective_type_changed
+            || connection_type_changed
+            || downlink_changed
+            || rtt_changed
+            || save_data_changed;
+
+        *self.effective_type.borrow_mut() = status.effective_type;
+        *self.connection_type.borrow_mut() = status.connection_type;
+
+        if changed {
+            let event = Event::new(
+                &self.global(),
+                Atom::from("change"),
+                EventBubbles::DoesNotBubble,
+                EventCancelable::NotCancelable,
+                can_gc,
+            );
+
+            event.fire(self.upcast::<EventTarget>(), can_gc);
+        }
+    }
+}
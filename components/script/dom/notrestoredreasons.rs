@@ -3,27 +3,160 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
+use js::jsapi::Heap;
 use js::jsval::JSVal;
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NotRestoredReasonsBinding::NotRestoredReasonsMethods;
 use crate::dom::bindings::import::module::SafeJSContext;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::notrestoredreasondetails::NotRestoredReasonDetails;
 
+impl NotRestoredReasons {
+    /// Build the top-level `NotRestoredReasons` for a `PerformanceNavigationTiming` from the
+    /// [`DocumentState::bfcache_block_reasons`][bfcache] of the document
+    /// that navigated, recursing into [`DocumentState::nested_histories`][nested] to build one
+    /// child node per frame (e.g. an iframe whose own `websocket`/`lock`/`fetch` activity blocked
+    /// restoration) mirroring the navigable tree that document_state was captured from. The
+    /// top-level entry has no `src`/`id`/`name`/`url` (those only apply to the iframes that
+    /// contributed to `children`).
+    ///
+    /// [bfcache]: script_traits::session_history::DocumentState::bfcache_block_reasons
+    /// [nested]: script_traits::session_history::DocumentState::nested_histories
+    pub fn for_document_state(
+        global: &GlobalScope,
+        document_state: &script_traits::session_history::DocumentState,
+    ) -> DomRoot<NotRestoredReasons> {
+        Self::build(global, document_state, None, None, None, None)
+    }
+
+    /// Builds one node of the tree: `document_state`'s own reasons, plus one child per nested
+    /// history, each built from that nested history's most recent entry's document state.
+    fn build(
+        global: &GlobalScope,
+        document_state: &script_traits::session_history::DocumentState,
+        src: Option<DOMString>,
+        id: Option<DOMString>,
+        name: Option<DOMString>,
+        url: Option<DOMString>,
+    ) -> DomRoot<NotRestoredReasons> {
+        let reasons = if document_state.bfcache_block_reasons.is_empty() {
+            None
+        } else {
+            Some(
+                document_state
+                    .bfcache_block_reasons
+                    .iter()
+                    .map(|reason| {
+                        NotRestoredReasonDetails::new(global, DOMString::from(reason.clone()))
+                    })
+                    .collect(),
+            )
+        };
+
+        let children = if document_state.nested_histories.is_empty() {
+            None
+        } else {
+            Some(
+                document_state
+                    .nested_histories
+                    .iter()
+                    .filter_map(|nested_history| {
+                        let child_entry = nested_history.entries().into_iter().next()?;
+                        Some(Self::build(
+                            global,
+                            &child_entry.document_state,
+                            Some(DOMString::from(child_entry.url().as_str())),
+                            Some(DOMString::from(nested_history.id().to_string())),
+                            child_entry
+                                .document_state
+                                .navigable_target_name
+                                .clone()
+                                .map(DOMString::from),
+                            Some(DOMString::from(child_entry.url().as_str())),
+                        ))
+                    })
+                    .collect(),
+            )
+        };
+
+        NotRestoredReasons::new(global, reasons, children, src, id, name, url)
+    }
+}
+
 #[dom_struct]
 /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#the-notrestoredreasons-interface>
 pub struct NotRestoredReasons {
     reflector_: Reflector,
-    reasons: Option<Vec<NotRestoredReasonDetails>>,
-    children: Option<Vec<NotRestoredReasons>>,
+    reasons: Option<Vec<DomRoot<NotRestoredReasonDetails>>>,
+    children: Option<Vec<DomRoot<NotRestoredReasons>>>,
     src: Option<DOMString>,
     id: Option<DOMString>,
     name: Option<DOMString>,
     url: Option<DOMString>,
+    // `reasons`/`children` are fixed at construction time (there is no setter), so unlike
+    // `DataTransfer::frozen_types` these caches never need invalidating once populated.
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_reasons: DomRefCell<Option<Heap<JSVal>>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_children: DomRefCell<Option<Heap<JSVal>>>,
 }
 
-impl NotRestoredReasons {}
+impl NotRestoredReasons {
+    fn new_inherited(
+        reasons: Option<Vec<DomRoot<NotRestoredReasonDetails>>>,
+        children: Option<Vec<DomRoot<NotRestoredReasons>>>,
+        src: Option<DOMString>,
+        id: Option<DOMString>,
+        name: Option<DOMString>,
+        url: Option<DOMString>,
+    ) -> NotRestoredReasons {
+        NotRestoredReasons {
+            reflector_: Reflector::new(),
+            reasons,
+            children,
+            src,
+            id,
+            name,
+            url,
+            frozen_reasons: DomRefCell::new(None),
+            frozen_children: DomRefCell::new(None),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#construct-the-not-restored-reasons-object>
+    pub fn new(
+        global: &GlobalScope,
+        reasons: Option<Vec<DomRoot<NotRestoredReasonDetails>>>,
+        children: Option<Vec<DomRoot<NotRestoredReasons>>>,
+        src: Option<DOMString>,
+        id: Option<DOMString>,
+        name: Option<DOMString>,
+        url: Option<DOMString>,
+    ) -> DomRoot<NotRestoredReasons> {
+        reflect_dom_object(
+            Box::new(NotRestoredReasons::new_inherited(
+                reasons, children, src, id, name, url,
+            )),
+            global,
+        )
+    }
+
+    /// Whether this node, or any node in its subtree, recorded a bfcache-blocking reason - i.e.
+    /// whether the navigable this node represents, or one of its descendants, is why the restore
+    /// failed. Not part of the WebIDL interface (the spec infers this client-side from
+    /// `reasons`/`children` being non-null); nothing outside this type calls it.
+    pub fn blocked(&self) -> bool {
+        self.reasons.is_some() ||
+            self.children
+                .as_ref()
+                .is_some_and(|children| children.iter().any(|child| child.blocked()))
+    }
+}
 
 impl NotRestoredReasonsMethods for NotRestoredReasons {
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-not-restored-reasons-src>
@@ -48,11 +181,39 @@ impl NotRestoredReasonsMethods for NotRestoredReasons {
 
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-not-restored-reasons-reasons>
     fn Reasons(&self, cx: SafeJSContext) -> JSVal {
-        todo!()
+        if let Some(frozen) = &*self.frozen_reasons.borrow() {
+            return frozen.get();
+        }
+
+        let Some(reasons) = &self.reasons else {
+            return js::jsval::NullValue();
+        };
+
+        let frozen = to_frozen_array(reasons.as_slice(), cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_reasons.borrow_mut() = Some(Heap::default());
+        self.frozen_reasons.borrow().as_ref().unwrap().set(frozen);
+
+        frozen
     }
 
     /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-not-restored-reasons-children>
     fn Children(&self, cx: SafeJSContext) -> JSVal {
-        todo!()
+        if let Some(frozen) = &*self.frozen_children.borrow() {
+            return frozen.get();
+        }
+
+        let Some(children) = &self.children else {
+            return js::jsval::NullValue();
+        };
+
+        let frozen = to_frozen_array(children.as_slice(), cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_children.borrow_mut() = Some(Heap::default());
+        self.frozen_children.borrow().as_ref().unwrap().set(frozen);
+
+        frozen
     }
 }
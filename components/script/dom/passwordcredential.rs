@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::globalscope::GlobalScope;
+
+/// The data needed to construct a [`PasswordCredential`], via either `CredentialsContainer`'s
+/// `create()` or a form-autofill-driven `store()`. Stands in for the `PasswordCredentialData`
+/// WebIDL dictionary, whose `.webidl` definition and codegen output don't exist in this snapshot —
+/// see the interface doc comment on [`PasswordCredential`] below.
+pub struct PasswordCredentialData {
+    pub id: DOMString,
+    pub password: DOMString,
+    pub name: DOMString,
+    pub icon_url: USVString,
+}
+
+/// <https://w3c.github.io/webappsec-credential-management/#passwordcredential>
+///
+/// The real interface extends the abstract `Credential` base, but exposing either as actual
+/// WebIDL interfaces needs the `.webidl` definitions and codegen output for `Credential` and
+/// `PasswordCredential`, neither of which exist in this snapshot, so `Credential`'s `id`/`type`
+/// members are folded directly into this struct instead of inherited.
+#[dom_struct]
+pub struct PasswordCredential {
+    reflector_: Reflector,
+    id: DOMString,
+    password: DOMString,
+    name: DOMString,
+    icon_url: USVString,
+}
+
+impl PasswordCredential {
+    fn new_inherited(data: PasswordCredentialData) -> PasswordCredential {
+        PasswordCredential {
+            reflector_: Reflector::new(),
+            id: data.id,
+            password: data.password,
+            name: data.name,
+            icon_url: data.icon_url,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, data: PasswordCredentialData) -> DomRoot<PasswordCredential> {
+        reflect_dom_object(Box::new(PasswordCredential::new_inherited(data)), global)
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credential-id>
+    pub fn id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-credential-type>
+    ///
+    /// Always `"password"`: this is the only credential kind this snapshot supports.
+    pub fn credential_type(&self) -> DOMString {
+        DOMString::from("password")
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-passwordcredential-password>
+    pub fn password(&self) -> DOMString {
+        self.password.clone()
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-passwordcredential-name>
+    pub fn name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    /// <https://w3c.github.io/webappsec-credential-management/#dom-passwordcredential-iconurl>
+    pub fn icon_url(&self) -> USVString {
+        self.icon_url.clone()
+    }
+}
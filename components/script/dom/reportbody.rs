@@ -0,0 +1,25 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! <https://w3c.github.io/reporting/#reportbody>
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::reflector::Reflector;
+
+/// Common base of the `*ReportBody` interfaces (currently only
+/// [`super::cspviolationreportbody::CSPViolationReportBody`]), which otherwise share nothing but
+/// being the `body` of a `Report` delivered to a `ReportingObserver`.
+#[dom_struct]
+pub struct ReportBody {
+    reflector_: Reflector,
+}
+
+impl ReportBody {
+    pub fn new_inherited() -> ReportBody {
+        ReportBody {
+            reflector_: Reflector::new(),
+        }
+    }
+}
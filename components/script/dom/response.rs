@@ -14,6 +14,7 @@ use servo_url::ServoUrl;
 use url::Position;
 
 use crate::body::{BodyMixin, BodyType, Extractable, ExtractedBody, consume_body};
+use crate::content_decoder::{ContentDecoder, parse_content_codings};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HeadersBinding::HeadersMethods;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding;
@@ -25,6 +26,7 @@ use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object_with_proto};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{ByteString, USVString, serialize_jsval_to_json_utf8};
+use crate::dom::file::File;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::headers::{Guard, Headers, is_obs_text, is_vchar};
 use crate::dom::promise::Promise;
@@ -48,6 +50,19 @@ pub(crate) struct Response {
     #[ignore_malloc_size_of = "StreamConsumer"]
     stream_consumer: DomRefCell<Option<StreamConsumer>>,
     redirected: DomRefCell<bool>,
+    /// Decodes chunks arriving from the network before they reach `body_stream`, when a
+    /// `Content-Encoding` header was present and understood. `None` when the body is identity
+    /// encoded, or when decoding was skipped because the header named an unsupported coding.
+    #[ignore_malloc_size_of = "defined in a non-DOM crate"]
+    content_decoder: DomRefCell<Option<ContentDecoder>>,
+    /// HTTP trailing headers, stored by `set_trailers`. Collected separately from
+    /// `headers_reflector` because, unlike the main header list, they belong to the end of the
+    /// body. Nothing calls `set_trailers`, so this stays `None`.
+    #[no_trace]
+    trailers: DomRefCell<Option<HyperHeaders>>,
+    /// The promise returned by `Trailers()`, fulfilled with a `Headers` object once `finish` runs
+    /// (or rejected if the body errors first).
+    trailers_promise: DomRefCell<Option<Rc<Promise>>>,
 }
 
 #[allow(non_snake_case)]
@@ -69,6 +84,9 @@ impl Response {
             body_stream: MutNullableDom::new(Some(&*stream)),
             stream_consumer: DomRefCell::new(None),
             redirected: DomRefCell::new(false),
+            content_decoder: DomRefCell::new(None),
+            trailers: DomRefCell::new(None),
+            trailers_promise: DomRefCell::new(None),
         }
     }
 
@@ -91,10 +109,38 @@ impl Response {
     }
 
     pub(crate) fn error_stream(&self, error: Error, can_gc: CanGc) {
+        if let Some(promise) = self.trailers_promise.borrow().as_ref() {
+            promise.reject_error(error.clone(), can_gc);
+        }
+
         if let Some(body) = self.body_stream.get() {
             body.error_native(error, can_gc);
         }
     }
+
+    /// Store the HTTP trailing headers of a chunked body, for `finish` to hand to the promise
+    /// returned by `Trailers()`. Nothing calls this, so `Trailers()` always resolves with an
+    /// empty `Headers` object.
+    pub(crate) fn set_trailers(&self, trailers: Option<Serde<HyperHeaders>>) {
+        *self.trailers.borrow_mut() = trailers.map(Serde::into_inner);
+    }
+
+    /// Replace this response's body with a stream that lazily pulls its bytes from `blob` on
+    /// demand, rather than buffering the whole blob up front.
+    ///
+    /// Nothing calls this. `BodyInit::Blob(_).extract()` reads the entire blob into memory and
+    /// hands it to [`ReadableStream::new_from_bytes`], and the `Response` construction path does
+    /// not select the `UnderlyingSourceType::Blob` source, so blob bodies are buffered in memory.
+    #[allow(dead_code)]
+    pub(crate) fn set_body_from_blob(&self, global: &GlobalScope, blob: &File, can_gc: CanGc) {
+        let stream = ReadableStream::new_with_external_underlying_source(
+            global,
+            UnderlyingSourceType::Blob(DomRoot::from_ref(blob)),
+            can_gc,
+        )
+        .expect("Failed to create ReadableStream with external underlying source");
+        self.body_stream.set(Some(&*stream));
+    }
 }
 
 impl BodyMixin for Response {
@@ -292,6 +338,10 @@ impl ResponseMethods<crate::DomTypeHolder> for Response {
     }
 
     /// <https://fetch.spec.whatwg.org/#dom-response-clone>
+    ///
+    /// Relies on `ReadableStream::tee` to give the original and the clone independent branch
+    /// queues over one source reader, per
+    /// <https://streams.spec.whatwg.org/#readable-stream-tee>.
     fn Clone(&self, can_gc: CanGc) -> Fallible<DomRoot<Response>> {
         // Step 1
         if self.is_locked() || self.is_disturbed() {
@@ -321,8 +371,13 @@ impl ResponseMethods<crate::DomTypeHolder> for Response {
             .borrow_mut()
             .clone_from(&self.url_list.borrow());
 
-        if let Some(stream) = self.body_stream.get().clone() {
-            new_response.body_stream.set(Some(&*stream));
+        // https://fetch.spec.whatwg.org/#concept-response-clone
+        // Tee the body so that the original and the clone each read from their own branch queue
+        // instead of sharing a single stream (which would let reading one disturb the other).
+        if let Some(stream) = self.body_stream.get() {
+            let (branch_1, branch_2) = stream.tee(can_gc)?;
+            self.body_stream.set(Some(&*branch_1));
+            new_response.body_stream.set(Some(&*branch_2));
         }
 
         // Step 3
@@ -371,6 +426,18 @@ impl ResponseMethods<crate::DomTypeHolder> for Response {
     fn Bytes(&self, can_gc: CanGc) -> std::rc::Rc<Promise> {
         consume_body(self, BodyType::Bytes, can_gc)
     }
+
+    /// Returns a promise for the HTTP trailing headers, fulfilled once the body stream closes
+    /// (mirroring when `finish` delivers them), or rejected if the body errors first.
+    fn Trailers(&self, can_gc: CanGc) -> Rc<Promise> {
+        if let Some(promise) = self.trailers_promise.borrow().as_ref() {
+            return promise.clone();
+        }
+
+        let promise = Promise::new(&self.global(), can_gc);
+        *self.trailers_promise.borrow_mut() = Some(promise.clone());
+        promise
+    }
 }
 
 /// <https://fetch.spec.whatwg.org/#initialize-a-response>
@@ -462,11 +529,26 @@ impl Response {
         option_hyper_headers: Option<Serde<HyperHeaders>>,
         can_gc: CanGc,
     ) {
-        self.Headers(can_gc)
-            .set_headers(match option_hyper_headers {
-                Some(hyper_headers) => hyper_headers.into_inner(),
-                None => HyperHeaders::new(),
-            });
+        let mut hyper_headers = match option_hyper_headers {
+            Some(hyper_headers) => hyper_headers.into_inner(),
+            None => HyperHeaders::new(),
+        };
+
+        // https://fetch.spec.whatwg.org/#content-encoding-and-decoding
+        // If the stack names only understood codings, decode the body ourselves and hide the
+        // coding (and the now-inaccurate compressed length) from script, matching what a real
+        // HTTP client does before handing the response back to `fetch()`.
+        if let Some(content_encoding) = hyper_headers.get(http::header::CONTENT_ENCODING) {
+            if let Ok(content_encoding) = content_encoding.to_str() {
+                if let Some(codings) = parse_content_codings(content_encoding) {
+                    *self.content_decoder.borrow_mut() = ContentDecoder::new(codings);
+                    hyper_headers.remove(http::header::CONTENT_ENCODING);
+                    hyper_headers.remove(http::header::CONTENT_LENGTH);
+                }
+            }
+        }
+
+        self.Headers(can_gc).set_headers(hyper_headers);
     }
 
     pub(crate) fn set_status(&self, status: &HttpStatus) {
@@ -509,6 +591,17 @@ impl Response {
     }
 
     pub(crate) fn stream_chunk(&self, chunk: Vec<u8>, can_gc: CanGc) {
+        let chunk = match self.content_decoder.borrow_mut().as_mut() {
+            Some(decoder) => match decoder.decode(&chunk) {
+                Ok(decoded) => decoded,
+                Err(error) => {
+                    self.error_stream(Error::Type(format!("invalid compressed body: {error}")), can_gc);
+                    return;
+                },
+            },
+            None => chunk,
+        };
+
         // Note, are these two actually mutually exclusive?
         if let Some(stream_consumer) = self.stream_consumer.borrow().as_ref() {
             stream_consumer.consume_chunk(chunk.as_slice());
@@ -519,6 +612,20 @@ impl Response {
 
     #[cfg_attr(crown, allow(crown::unrooted_must_root))]
     pub(crate) fn finish(&self, can_gc: CanGc) {
+        if let Some(decoder) = self.content_decoder.borrow_mut().as_mut() {
+            match decoder.finish() {
+                Ok(trailing) if !trailing.is_empty() => {
+                    if let Some(body) = self.body_stream.get() {
+                        body.enqueue_native(trailing, can_gc);
+                    }
+                },
+                Ok(_) => {},
+                Err(error) => {
+                    self.error_stream(Error::Type(format!("invalid compressed body: {error}")), can_gc);
+                },
+            }
+        }
+
         if let Some(body) = self.body_stream.get() {
             body.controller_close_native(can_gc);
         }
@@ -526,5 +633,12 @@ impl Response {
         if let Some(stream_consumer) = stream_consumer {
             stream_consumer.stream_end();
         }
+
+        if let Some(promise) = self.trailers_promise.borrow().as_ref() {
+            let trailer_headers = Headers::for_response(&self.global(), can_gc);
+            trailer_headers.set_headers(self.trailers.borrow().clone().unwrap_or_default());
+            trailer_headers.set_guard(Guard::Immutable);
+            promise.resolve_native(&trailer_headers, can_gc);
+        }
     }
 }
@@ -28,11 +28,9 @@ pub enum TrustedTypeName {
     TrustedScriptURL,
 }
 
-#[derive(PartialEq)]
 enum TrustedResult {
     HTMLOrScript(DOMString),
     ScriptURL(USVString),
-    Empty,
 }
 
 impl ToString for TrustedResult {
@@ -40,7 +38,6 @@ impl ToString for TrustedResult {
         match self {
             TrustedResult::HTMLOrScript(data) => String::from(data.clone().str()),
             TrustedResult::ScriptURL(data) => String::from(data.clone().0),
-            TrustedResult::Empty => String::new(),
         }
     }
 }
@@ -103,51 +100,47 @@ impl TrustedTypePolicy {
         // Step 1. Let functionName be a function name for the given trustedTypeName, based on the
         // following table:
         // Step 2. Let function be policy’s options[functionName].
-        let policy_value_result = match name {
+        //
+        // Step 4. Let policyValue be the result of invoking function with value as a first
+        // argument, items of arguments as subsequent arguments, and callback **this** value set to
+        // null, rethrowing any exceptions. The `arguments` of `CreateHTML`/`CreateScript`/
+        // `CreateScriptURL` are not passed on: the callback receives `value` only. A
+        // null/undefined return isn't itself an error -- Step 4 of Create a Trusted Type turns it
+        // into the empty string -- so the `unwrap_or` calls below map it to an empty string
+        // instead of a thrown error.
+        let callback_result = match name {
             TrustedTypeName::TrustedHTML => self.options.createHTML.clone().map(|callback| {
-                if let Ok(r) = callback.Call__(input, vec![], ExceptionHandling::Report, can_gc) {
-                    TrustedResult::HTMLOrScript(r.expect("Failed to extract result"))
-                } else {
-                    TrustedResult::Empty
-                }
+                callback
+                    .Call__(input, vec![], ExceptionHandling::Rethrow, can_gc)
+                    .map(|result| TrustedResult::HTMLOrScript(result.unwrap_or(DOMString::new())))
             }),
             TrustedTypeName::TrustedScript => self.options.createScript.clone().map(|callback| {
-                if let Ok(r) = callback.Call__(input, vec![], ExceptionHandling::Report, can_gc) {
-                    TrustedResult::HTMLOrScript(r.expect("Failed to extract result"))
-                } else {
-                    TrustedResult::Empty
-                }
+                callback
+                    .Call__(input, vec![], ExceptionHandling::Rethrow, can_gc)
+                    .map(|result| TrustedResult::HTMLOrScript(result.unwrap_or(DOMString::new())))
             }),
             TrustedTypeName::TrustedScriptURL => {
                 self.options.createScriptURL.clone().map(|callback| {
-                    if let Ok(r) = callback.Call__(input, vec![], ExceptionHandling::Report, can_gc)
-                    {
-                        TrustedResult::ScriptURL(r.expect("Failed to extract result"))
-                    } else {
-                        TrustedResult::Empty
-                    }
+                    callback
+                        .Call__(input, vec![], ExceptionHandling::Rethrow, can_gc)
+                        .map(|result| {
+                            TrustedResult::ScriptURL(result.unwrap_or(USVString(String::new())))
+                        })
                 })
             },
         };
 
-        // Step 3. If function is null, then:
-        // Step 3.1. If throwIfMissing throw a TypeError.
-        // Step 3.2. Else return null
-
-        // Step 4. Let policyValue be the result of invoking function with value as a first
-        // argument, items of arguments as subsequent arguments, and callback **this** value set to
-        // null, rethrowing any exceptions.
-        // let policy_value =
-        let Some(result) = policy_value_result else {
-            return Err(Error::Type("Failed to get trustred type polict".into()));
+        // Step 3. If function is null, throw a TypeError: every caller of this algorithm (via
+        // `create_trusted_type`) passes throwIfMissing as true.
+        let Some(result) = callback_result else {
+            return Err(Error::Type(format!(
+                "Policy '{}' has no callback registered for this Trusted Type",
+                self.name
+            )));
         };
 
-        if result == TrustedResult::Empty {
-            return Err(Error::Type("Empty policy returned".into()));
-        }
-
-        // Step 5. Return policyValue.
-        return Ok(result);
+        // Step 5. Return policyValue, propagating any exception the callback raised.
+        result
     }
 }
 
@@ -172,11 +165,11 @@ impl TrustedTypePolicyMethods<crate::DomTypeHolder> for TrustedTypePolicy {
         _arguments: Vec<HandleValue>,
         can_gc: CanGc,
     ) -> Fallible<DomRoot<TrustedHTML>> {
-        let result = self.create_trusted_type(TrustedTypeName::TrustedHTML, input, can_gc);
-
-        match result {
-            Ok(TrustedResult::HTMLOrScript(data)) => Ok(TrustedHTML::new(&self.global(), data)),
-            _ => Err(Error::Data),
+        match self.create_trusted_type(TrustedTypeName::TrustedHTML, input, can_gc)? {
+            TrustedResult::HTMLOrScript(data) => Ok(TrustedHTML::new(&self.global(), data)),
+            TrustedResult::ScriptURL(_) => {
+                unreachable!("create_trusted_type honors the requested TrustedTypeName")
+            },
         }
     }
 
@@ -188,11 +181,11 @@ impl TrustedTypePolicyMethods<crate::DomTypeHolder> for TrustedTypePolicy {
         _arguments: Vec<HandleValue>,
         can_gc: CanGc,
     ) -> Fallible<DomRoot<TrustedScript>> {
-        let result = self.create_trusted_type(TrustedTypeName::TrustedScript, input, can_gc);
-
-        match result {
-            Ok(TrustedResult::HTMLOrScript(data)) => Ok(TrustedScript::new(&self.global(), data)),
-            _ => Err(Error::Data),
+        match self.create_trusted_type(TrustedTypeName::TrustedScript, input, can_gc)? {
+            TrustedResult::HTMLOrScript(data) => Ok(TrustedScript::new(&self.global(), data)),
+            TrustedResult::ScriptURL(_) => {
+                unreachable!("create_trusted_type honors the requested TrustedTypeName")
+            },
         }
     }
 
@@ -204,11 +197,11 @@ impl TrustedTypePolicyMethods<crate::DomTypeHolder> for TrustedTypePolicy {
         _arguments: Vec<HandleValue>,
         can_gc: CanGc,
     ) -> Fallible<DomRoot<TrustedScriptURL>> {
-        let result = self.create_trusted_type(TrustedTypeName::TrustedScriptURL, input, can_gc);
-
-        match result {
-            Ok(TrustedResult::ScriptURL(data)) => Ok(TrustedScriptURL::new(&self.global(), data)),
-            _ => Err(Error::Data),
+        match self.create_trusted_type(TrustedTypeName::TrustedScriptURL, input, can_gc)? {
+            TrustedResult::ScriptURL(data) => Ok(TrustedScriptURL::new(&self.global(), data)),
+            TrustedResult::HTMLOrScript(_) => {
+                unreachable!("create_trusted_type honors the requested TrustedTypeName")
+            },
         }
     }
 }
@@ -7,36 +7,213 @@ use std::result::Result::Err;
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 use dom_struct::dom_struct;
-use html5ever::{local_name, namespace_url, ns, QualName};
+use js::rust::wrappers::JS_ValueToString;
 use js::rust::HandleValue;
-use js::rust::wrappers::JS_ValueToSource;
 
 use super::globalscope::GlobalScope;
 use super::userscripts::load_script;
+use crate::csp_trusted_types::{
+    should_block_create_policy, CreatePolicyDecision, RequireTrustedTypesForDirective,
+    TrustedTypesDirective,
+};
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::SecurityPolicyViolationEventDisposition;
 use crate::dom::bindings::codegen::Bindings::TrustedHTMLBinding::TrustedTypePolicyFactory_Binding::TrustedTypePolicyFactoryMethods;
 use crate::dom::bindings::codegen::Bindings::TrustedHTMLBinding::TrustedTypePolicyOptions;
-use crate::dom::bindings::conversions::jsstring_to_str;
+use crate::dom::bindings::conversions::{jsstring_to_str, root_from_handlevalue};
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::import::module::jsapi;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{DomGlobal, Reflector, reflect_dom_object};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::securitypolicyviolationevent::{first_n_code_points, SecurityPolicyViolationEvent};
 use crate::dom::trustedhtml::TrustedHTML;
 use crate::dom::trustedscript::TrustedScript;
-use crate::dom::trustedtypepolicy::TrustedTypePolicy;
+use crate::dom::trustedscripturl::TrustedScriptURL;
+use crate::dom::trustedtypepolicy::{TrustedTypeName, TrustedTypePolicy};
 use crate::script_runtime::{CanGc, JSContext};
 
+/// The `trusted-types` directive of every enforcing, policy-controlling CSP that currently
+/// applies to `global`, in declaration order.
+///
+/// This snapshot has no policy container to walk for "every policy that applies to this global"
+/// (TODO once one lands); in its place, a single pref stands in for that policy list, holding the
+/// raw directive value an enforcing policy would carry. An empty value means no such policy
+/// applies.
+fn trusted_types_directives_for(_global: &GlobalScope) -> Vec<TrustedTypesDirective> {
+    let value = servo_config::pref!(dom_trusted_types_directive);
+    if value.is_empty() {
+        return vec![];
+    }
+
+    vec![TrustedTypesDirective::parse(&value)]
+}
+
+/// Whether `global` currently enforces Trusted Types for DOM sinks, i.e. whether a
+/// `require-trusted-types-for 'script'` CSP directive applies to it.
+///
+/// As with [`trusted_types_directives_for`], this stands in for a policy list with a single pref
+/// holding the raw directive value.
+fn trusted_types_enforced_for(_global: &GlobalScope) -> bool {
+    RequireTrustedTypesForDirective::parse(&servo_config::pref!(
+        dom_trusted_types_require_trusted_types_for
+    ))
+    .requires_script()
+}
+
+/// The result of [`get_trusted_type_compliant_string`]: the sink either gets a trusted value's
+/// already-sanitized data, or (when enforcement is off) the input coerced to a plain string.
+pub(crate) enum TrustedTypeCompliantString {
+    HTMLOrScript(DOMString),
+    ScriptURL(DOMString),
+}
+
+impl TrustedTypeCompliantString {
+    pub(crate) fn into_string(self) -> DOMString {
+        match self {
+            TrustedTypeCompliantString::HTMLOrScript(data) |
+            TrustedTypeCompliantString::ScriptURL(data) => data,
+        }
+    }
+}
+
+/// <https://w3c.github.io/trusted-types/dist/spec/#abstract-opdef-get-trusted-type-compliant-string>
+///
+/// Enforces Trusted Types at an injection sink once `require-trusted-types-for 'script'` is
+/// active. Its only caller is [`get_trusted_type_compliant_value`], which no sink calls (see
+/// that function's doc comment). `sink` is a
+/// human-readable description of the attribute/argument for the resulting `TypeError` (e.g.
+/// `"HTMLScriptElement src"`).
+///
+/// This is the "already stringified" half of the algorithm; [`get_trusted_type_compliant_value`]
+/// is the entry point sinks should call, since it also handles input already being the expected
+/// `Trusted*` object.
+pub(crate) fn get_trusted_type_compliant_string(
+    cx: JSContext,
+    factory: &TrustedTypePolicyFactory,
+    name: TrustedTypeName,
+    input: DOMString,
+    sink: &str,
+    can_gc: CanGc,
+) -> Fallible<TrustedTypeCompliantString> {
+    // Step 2. Let requireTrustedTypes be the result of determining whether Trusted Types is
+    // required for the relevant sink type, given global, expectedType, and sink.
+    if !trusted_types_enforced_for(&factory.global()) {
+        // Step 3. If requireTrustedTypes is false, return stringified value.
+        return Ok(match name {
+            TrustedTypeName::TrustedScriptURL => TrustedTypeCompliantString::ScriptURL(input),
+            _ => TrustedTypeCompliantString::HTMLOrScript(input),
+        });
+    }
+
+    let type_name = match name {
+        TrustedTypeName::TrustedHTML => "TrustedHTML",
+        TrustedTypeName::TrustedScript => "TrustedScript",
+        TrustedTypeName::TrustedScriptURL => "TrustedScriptURL",
+    };
+
+    // Step 4/5. With no default policy, a plain string at an enforced sink is always rejected;
+    // with one, try to convert it through `create*` before giving up.
+    let default_policy = factory.default_policy.borrow();
+    let Some(default_policy) = default_policy.as_ref() else {
+        factory.fire_require_trusted_types_for_violation(sink, input.str());
+
+        return Err(Error::Type(format!(
+            "This document requires '{type_name}' assignment for {sink}"
+        )));
+    };
+
+    let result = match name {
+        TrustedTypeName::TrustedHTML => default_policy
+            .CreateHTML(cx, input.clone(), vec![], can_gc)
+            .map(|trusted| TrustedTypeCompliantString::HTMLOrScript(trusted.Stringifier())),
+        TrustedTypeName::TrustedScript => default_policy
+            .CreateScript(cx, input.clone(), vec![], can_gc)
+            .map(|trusted| TrustedTypeCompliantString::HTMLOrScript(trusted.Stringifier())),
+        TrustedTypeName::TrustedScriptURL => default_policy
+            .CreateScriptURL(cx, input.clone(), vec![], can_gc)
+            .map(|trusted| {
+                TrustedTypeCompliantString::ScriptURL(DOMString::from_string(trusted.ToJSON().0))
+            }),
+    };
+
+    // Step 6/7. A null/undefined return, or a thrown exception, from the callback is itself a
+    // violation of `require-trusted-types-for`, distinct from the "no default policy at all"
+    // case above.
+    if result.is_err() {
+        factory.fire_require_trusted_types_for_violation(sink, input.str());
+    }
+
+    result
+}
+
+/// <https://w3c.github.io/trusted-types/dist/spec/#abstract-opdef-get-trusted-type-compliant-string>,
+/// starting from the step that lets an already-compliant `Trusted*` object skip policy
+/// invocation entirely: "If input implements the associated interface of expectedType, then
+/// return the result of stringifying input."
+///
+/// This is the entry point every Trusted-Types-guarded injection sink should call for its
+/// `(Trusted* or DOMString/USVString)` union-typed argument: `Element.innerHTML`/`outerHTML`,
+/// `HTMLScriptElement.src`/`text`, `HTMLIFrameElement.srcdoc`, and the event-handler content
+/// attribute setters. Those sinks live in element bindings this snapshot doesn't include, so
+/// none of them call through here yet; wiring each one up is a matter of replacing its `DOMString`
+/// coercion of the setter's argument with a call to this function and propagating the `Fallible`.
+pub(crate) fn get_trusted_type_compliant_value(
+    cx: JSContext,
+    factory: &TrustedTypePolicyFactory,
+    name: TrustedTypeName,
+    input: HandleValue,
+    sink: &str,
+    can_gc: CanGc,
+) -> Fallible<TrustedTypeCompliantString> {
+    if input.is_object() {
+        let already_trusted = match name {
+            TrustedTypeName::TrustedHTML => {
+                unsafe { root_from_handlevalue::<TrustedHTML>(input, *cx) }
+                    .ok()
+                    .map(|trusted| TrustedTypeCompliantString::HTMLOrScript(trusted.Stringifier()))
+            }
+            TrustedTypeName::TrustedScript => {
+                unsafe { root_from_handlevalue::<TrustedScript>(input, *cx) }
+                    .ok()
+                    .map(|trusted| TrustedTypeCompliantString::HTMLOrScript(trusted.Stringifier()))
+            }
+            TrustedTypeName::TrustedScriptURL => {
+                unsafe { root_from_handlevalue::<TrustedScriptURL>(input, *cx) }
+                    .ok()
+                    .map(|trusted| {
+                        TrustedTypeCompliantString::ScriptURL(DOMString::from_string(
+                            trusted.ToJSON().0,
+                        ))
+                    })
+            }
+        };
+
+        if let Some(already_trusted) = already_trusted {
+            return Ok(already_trusted);
+        }
+    }
+
+    let stringified = unsafe { handle_value_to_string(*cx, input) };
+
+    get_trusted_type_compliant_string(cx, factory, name, stringified, sink, can_gc)
+}
+
+/// Coerce an arbitrary JS value to a string the way the Trusted Types spec's "stringify"
+/// operation does: real `ToString`, not a source-literal like `uneval`.
 #[allow(unsafe_code)]
 unsafe fn handle_value_to_string(cx: *mut jsapi::JSContext, value: HandleValue) -> DOMString {
     rooted!(in(cx) let mut js_string = std::ptr::null_mut::<jsapi::JSString>());
 
-    match std::ptr::NonNull::new(JS_ValueToSource(cx, value)) {
+    match std::ptr::NonNull::new(JS_ValueToString(cx, value)) {
         Some(js_str) => {
             js_string.set(js_str.as_ptr());
             jsstring_to_str(cx, js_str)
-        },
-        None => "<error converting value to string>".into(),
+        }
+        None => DOMString::new(),
     }
 }
 
@@ -44,8 +221,14 @@ unsafe fn handle_value_to_string(cx: *mut jsapi::JSContext, value: HandleValue)
 #[dom_struct]
 pub(crate) struct TrustedTypePolicyFactory {
     reflector_: Reflector,
+    /// The implicit fallback policy a plain string coerces through at an enforced sink with no
+    /// explicit `Trusted*` value, set by [`CreatePolicy`](TrustedTypePolicyFactoryMethods::CreatePolicy)
+    /// whenever `policyName` is `"default"` (a later one replaces an earlier one) and read back
+    /// by both `GetDefaultPolicy()` and
+    /// [`get_trusted_type_compliant_string`].
+    ///
     /// <https://w3c.github.io/trusted-types/dist/spec/#trustedtypepolicyfactory-default-policy>
-    default_policy: Option<DomRoot<TrustedTypePolicy>>,
+    default_policy: DomRefCell<Option<DomRoot<TrustedTypePolicy>>>,
     #[ignore_malloc_size_of = "todo"]
     /// <https://w3c.github.io/trusted-types/dist/spec/#trustedtypepolicyfactory-created-policy-names>
     created_policy_names: DomRefCell<BTreeSet<String>>,
@@ -56,7 +239,7 @@ impl TrustedTypePolicyFactory {
         reflect_dom_object(
             Box::new(TrustedTypePolicyFactory {
                 reflector_: Reflector::new(),
-                default_policy: None,
+                default_policy: DomRefCell::new(None),
                 created_policy_names: DomRefCell::new(BTreeSet::new()),
             }),
             global,
@@ -64,10 +247,87 @@ impl TrustedTypePolicyFactory {
         )
     }
 
-    fn is_empty(&self, cx: JSContext, value: HandleValue) -> bool {
-        let value = unsafe { handle_value_to_string(*cx, value) };
+    /// Build and fire a `securitypolicyviolation` event at this factory's document for a
+    /// `trusted-types` directive violation (an invalid `createPolicy` call), per
+    /// <https://w3c.github.io/trusted-types/dist/spec/#should-block-create-policy>'s report step.
+    fn fire_trusted_types_violation(&self, policy_name: &str) {
+        let global = self.global();
+        let window = global.as_window();
+        let document = window.Document();
+
+        let event = SecurityPolicyViolationEvent::new_for_directive(
+            &global,
+            "trusted-types",
+            Some(document.url()),
+            "trusted-types-policy",
+            policy_name,
+            SecurityPolicyViolationEventDisposition::Enforce,
+        );
+
+        event
+            .upcast::<Event>()
+            .fire(document.upcast::<EventTarget>(), CanGc::note());
+    }
+
+    /// Fire the `SecurityPolicyViolationEvent` for a `require-trusted-types-for` failure at an
+    /// injection sink: either there was no default policy to fall back on, or the default
+    /// policy's `create*` callback rejected (or threw for) the input.
+    fn fire_require_trusted_types_for_violation(&self, sink: &str, input: &str) {
+        let global = self.global();
+        let window = global.as_window();
+        let document = window.Document();
+
+        let sample = format!("{}|{}", sink, first_n_code_points(input, 40));
+
+        let event = SecurityPolicyViolationEvent::new_for_directive(
+            &global,
+            "require-trusted-types-for",
+            Some(document.url()),
+            "trusted-types-sink",
+            &sample,
+            SecurityPolicyViolationEventDisposition::Enforce,
+        );
+
+        event
+            .upcast::<Event>()
+            .fire(document.upcast::<EventTarget>(), CanGc::note());
+    }
+}
+
+/// The element interface the Trusted Types sink tables in `GetPropertyType`/`GetAttributeType`
+/// are keyed on.
+///
+/// This mirrors the small slice of `create.rs`'s tag-name/namespace → element-interface
+/// resolution that those sink tables actually distinguish; every interface the tables don't call
+/// out by name (everything but `HTMLIFrameElement`/`HTMLScriptElement`) behaves like plain
+/// `Element`, i.e. the generic `innerHTML`/`outerHTML` sinks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TrustedTypeSinkInterface {
+    Element,
+    HTMLIFrameElement,
+    HTMLScriptElement,
+}
+
+impl TrustedTypeSinkInterface {
+    /// <https://dom.spec.whatwg.org/#concept-create-element>'s local-name/namespace lookup,
+    /// narrowed to the interfaces the Trusted Types sink tables distinguish. `namespace` is
+    /// `None` for the HTML namespace, matching `Document.createElement()`'s caller-facing
+    /// default, as well as an explicit HTML-namespace string.
+    fn resolve(local_name: &str, namespace: Option<&str>) -> TrustedTypeSinkInterface {
+        let is_html_namespace = matches!(
+            namespace,
+            None | Some("html") | Some("http://www.w3.org/1999/xhtml")
+        );
 
-        value.str() == ""
+        if is_html_namespace {
+            match local_name {
+                "iframe" => return TrustedTypeSinkInterface::HTMLIFrameElement,
+                "script" => return TrustedTypeSinkInterface::HTMLScriptElement,
+                _ => {},
+            }
+        }
+
+        TrustedTypeSinkInterface::Element
     }
 }
 
@@ -78,24 +338,28 @@ impl TrustedTypePolicyFactoryMethods<crate::DomTypeHolder> for TrustedTypePolicy
         policy_name: DOMString,
         options: &TrustedTypePolicyOptions,
     ) -> Fallible<DomRoot<TrustedTypePolicy>> {
+        let global = self.global();
+
         // Step 1. Let allowedByCSP be the result of executing Should Trusted Type policy creation
         // be blocked by Content Security Policy? algorithm with global, policyName and factory’s
         // created policy names value.
+        let decision = should_block_create_policy(
+            &trusted_types_directives_for(&global),
+            policy_name.str(),
+            &self.created_policy_names.borrow(),
+        );
 
         // Step 2. If allowedByCSP is "Blocked", throw a TypeError and abort further steps.
-        // TODO Implement this in Rust-CSP
-
-        // Step 3. If policyName is default and the factory’s default policy value is not null,
-        // throw a TypeError and abort further steps.
-        if policy_name == DOMString::from_string(String::from("default")) &&
-            self.default_policy.is_some()
-        {
-            return Err(Error::Type(String::from(
-                "A default trusted type policy is already defined",
+        if decision == CreatePolicyDecision::Blocked {
+            self.fire_trusted_types_violation(policy_name.str());
+
+            return Err(Error::Type(format!(
+                "Policy creation for '{}' is blocked by the trusted-types CSP directive",
+                policy_name
             )));
         }
 
-        let global = &self.global();
+        let global = &global;
 
         // Step 4. Let policy be a new TrustedTypePolicy object.
         // Step 5. Set policy’s name property value to policyName.
@@ -114,6 +378,11 @@ impl TrustedTypePolicyFactoryMethods<crate::DomTypeHolder> for TrustedTypePolicy
             },
         );
 
+        // Step 7. If policyName is "default", set factory’s default policy value to policy.
+        if policy_name.str() == "default" {
+            *self.default_policy.borrow_mut() = Some(policy.clone());
+        }
+
         // Step 8. Append policyName to factory’s created policy names.
         self.created_policy_names
             .borrow_mut()
@@ -124,21 +393,40 @@ impl TrustedTypePolicyFactoryMethods<crate::DomTypeHolder> for TrustedTypePolicy
     }
 
     /// Returns true if value is an instance of TrustedHTML and has an associated data value set,
-    /// false otherwise.
+    /// false otherwise. `root_from_handlevalue` already requires `value` to unwrap to a live
+    /// `TrustedHTML`, and `TrustedHTML::data` is a plain non-optional `DOMString` that's always set
+    /// at construction time, so "has an associated data value" holds for every instance that
+    /// exists -- there's no detached/null-data state in this implementation to check for.
     ///
     /// <https://w3c.github.io/trusted-types/dist/spec/#dom-trustedtypepolicyfactory-ishtml>
     fn IsHTML(&self, cx: JSContext, value: HandleValue) -> bool {
-        self.is_empty(cx, value)
+        if !value.is_object() {
+            return false;
+        }
+
+        unsafe { root_from_handlevalue::<TrustedHTML>(value, *cx).is_ok() }
     }
 
+    /// See [`Self::IsHTML`]'s doc comment for why the instance check alone is sufficient.
+    ///
     /// <https://w3c.github.io/trusted-types/dist/spec/#dom-trustedtypepolicyfactory-isscript>
     fn IsScript(&self, cx: JSContext, value: HandleValue) -> bool {
-        self.is_empty(cx, value)
+        if !value.is_object() {
+            return false;
+        }
+
+        unsafe { root_from_handlevalue::<TrustedScript>(value, *cx).is_ok() }
     }
 
+    /// See [`Self::IsHTML`]'s doc comment for why the instance check alone is sufficient.
+    ///
     /// <https://w3c.github.io/trusted-types/dist/spec/#dom-trustedtypepolicyfactory-isscripturl>
     fn IsScriptURL(&self, cx: JSContext, value: HandleValue) -> bool {
-        self.is_empty(cx, value)
+        if !value.is_object() {
+            return false;
+        }
+
+        unsafe { root_from_handlevalue::<TrustedScriptURL>(value, *cx).is_ok() }
     }
 
     /// <https://w3c.github.io/trusted-types/dist/spec/#dom-trustedtypepolicyfactory-emptyhtml>
@@ -166,48 +454,31 @@ impl TrustedTypePolicyFactoryMethods<crate::DomTypeHolder> for TrustedTypePolicy
         &self,
         tag_name: DOMString,
         property: DOMString,
-        _element_namespace: Option<DOMString>,
+        element_namespace: Option<DOMString>,
     ) -> Option<DOMString> {
         // Step 1. Set localName to tagName in ASCII lowercase.
         let local_name = tag_name.to_ascii_lowercase();
+        let property = property.to_ascii_lowercase();
 
-        // Further parse this via local_name!
-        match local_name.as_str() {
-            "htmliframeelement" => {
-                match property.to_ascii_lowercase().as_str() {
-                    "srcdoc" => Some(DOMString::from_string(String::from("TrustedHTML"))),
-                    _ => return None
-                }
-            },
-            "htmlscriptelemnent" => {
-                match property.to_ascii_lowercase().as_str() {
-                    "innertext" => Some(DOMString::from_string(String::from("TrustedScript"))),
-                    "src" => Some(DOMString::from_string(String::from("TrustedScriptURL"))),
-                    "text" => Some(DOMString::from_string(String::from("TrustedScript"))),
-                    "textcontent" => Some(DOMString::from_string(String::from("TrustedScript"))),
-                    _ => return None
-                }
-            }
-            _ => match property.to_ascii_lowercase().as_str() {
-                "innerhtml" | "outerhtml" => Some(DOMString::from_string(String::from("TrustedHTML"))),
-                _ => return None
-            }
-        }
-
-        // Step 2. If elementNs is null or an empty string, set elementNs to HTML namespace.
-        // let element_namespace = element_namespace.unwrap_or(ns!(html));
-        // let element_namespace = ns!(html);
-
-        // Step 3. Let interface be the element interface for localName and elementNs.
-        // https://dom.spec.whatwg.org/#concept-element-interface
-
-        // https://github.com/shanehandley/servo/blob/main/components/script/dom/create.rs#L281
-
-
-
-        // let qual = QualName::new(None, element_namespace, local_name!(local_name.as_str()));
+        // Steps 2/3 resolve localName + elementNs to an element interface and look the property
+        // up in that interface's sink table.
+        let interface = TrustedTypeSinkInterface::resolve(
+            &local_name,
+            element_namespace.as_ref().map(DOMString::str),
+        );
 
-        // Some(DOMString::from_string(String::from("TrustedHTML")))
+        let trusted_type = match (interface, property.as_str()) {
+            (TrustedTypeSinkInterface::HTMLIFrameElement, "srcdoc") => "TrustedHTML",
+            (TrustedTypeSinkInterface::HTMLScriptElement, "src") => "TrustedScriptURL",
+            (
+                TrustedTypeSinkInterface::HTMLScriptElement,
+                "innertext" | "text" | "textcontent",
+            ) => "TrustedScript",
+            (_, "innerhtml" | "outerhtml") => "TrustedHTML",
+            _ => return None,
+        };
+
+        Some(DOMString::from_string(trusted_type.to_owned()))
     }
 
     /// Example:
@@ -224,15 +495,32 @@ impl TrustedTypePolicyFactoryMethods<crate::DomTypeHolder> for TrustedTypePolicy
         element_namespace: Option<DOMString>,
         attribute_namespace: Option<DOMString>,
     ) -> Option<DOMString> {
+        // No content attribute in the sink table below is namespaced.
+        if attribute_namespace.is_some() {
+            return None;
+        }
+
         // Step 1. Set localName to tagName in ASCII lowercase.
         let local_name = tag_name.to_ascii_lowercase();
+        let attribute = attribute.to_ascii_lowercase();
+
+        // Steps 2-5 resolve localName + elementNs to an element interface and look the
+        // attribute/attributeNs pair up in that interface's sink table.
+        let interface = TrustedTypeSinkInterface::resolve(
+            &local_name,
+            element_namespace.as_ref().map(DOMString::str),
+        );
 
-        // ...must the same as above
+        let trusted_type = match (interface, attribute.as_str()) {
+            (TrustedTypeSinkInterface::HTMLScriptElement, "src") => "TrustedScriptURL",
+            (TrustedTypeSinkInterface::HTMLIFrameElement, "srcdoc") => "TrustedHTML",
+            _ => return None,
+        };
 
-        None
+        Some(DOMString::from_string(trusted_type.to_owned()))
     }
 
     fn GetDefaultPolicy(&self) -> Option<DomRoot<TrustedTypePolicy>> {
-        self.default_policy.clone()
+        self.default_policy.borrow().clone()
     }
 }
@@ -2,23 +2,47 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::collections::HashMap;
+
 use content_security_policy as csp;
 use dom_struct::dom_struct;
 use js::rust::HandleObject;
 use url::Url;
-use urlpattern::{UrlPattern, UrlPatternInit, UrlPatternMatchInput, UrlPatternOptions};
+use urlpattern::{
+    UrlPattern, UrlPatternComponentResult, UrlPatternInit, UrlPatternMatchInput,
+    UrlPatternOptions,
+};
 
 use crate::dom::bindings::codegen::Bindings::URLPatternBinding::{
-    URLPatternInit, URLPatternMethods, URLPatternOptions, URLPatternResult,
+    URLPatternComponentResult, URLPatternInit, URLPatternMethods, URLPatternOptions,
+    URLPatternResult,
 };
 use crate::dom::bindings::codegen::UnionTypes;
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::record::Record;
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::USVString;
 use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::CanGc;
 
+/// Convert the `groups` map the `urlpattern` crate returns for a single component into the
+/// `record<USVString, USVString?>` the spec requires.
+fn convert_groups(groups: HashMap<String, Option<String>>) -> Record<USVString, Option<USVString>> {
+    groups
+        .into_iter()
+        .map(|(key, value)| (USVString::from(key), value.map(USVString::from)))
+        .collect()
+}
+
+/// Convert a single per-component match result into its WebIDL dictionary.
+fn convert_component(result: UrlPatternComponentResult) -> URLPatternComponentResult {
+    URLPatternComponentResult {
+        input: USVString::from(result.input),
+        groups: convert_groups(result.groups),
+    }
+}
+
 /// <https://urlpattern.spec.whatwg.org/#urlpattern>
 #[dom_struct]
 pub struct URLPattern {
@@ -173,7 +197,52 @@ impl URLPatternMethods for URLPattern {
         input: UnionTypes::USVStringOrURLPatternInit,
         base_url: Option<USVString>,
     ) -> Option<URLPatternResult> {
-        None
+        // 1. Let result be the result of match given this's associated URL pattern, input, and
+        // baseURL if given.
+        let match_input = match input.clone() {
+            UnionTypes::USVStringOrURLPatternInit::USVString(value) => {
+                if let Some(base) = base_url.as_ref() {
+                    let base = Url::parse(&base.0).ok()?;
+                    Url::options()
+                        .base_url(Some(&base))
+                        .parse(&value.0)
+                        .ok()
+                        .map(UrlPatternMatchInput::Url)
+                } else {
+                    Url::parse(&value.0).ok().map(UrlPatternMatchInput::Url)
+                }
+            },
+            UnionTypes::USVStringOrURLPatternInit::URLPatternInit(init) => {
+                UrlPatternInit::try_from(init)
+                    .ok()
+                    .map(UrlPatternMatchInput::Init)
+            },
+        }?;
+
+        // 2. If result is null, then return null.
+        let result = self.pattern.exec(match_input).ok()??;
+
+        // 3. Let inputs be an empty list.
+        // 4. For each input of result["inputs"], append input converted to an ECMAScript value
+        // to inputs.
+        let mut inputs = vec![input];
+        if let Some(base_url) = base_url {
+            inputs.push(UnionTypes::USVStringOrURLPatternInit::USVString(base_url));
+        }
+
+        // 5. Let result be a new URLPatternResult with inputs set to inputs, and the remaining
+        // fields set to the corresponding component results converted to URLPatternComponentResult.
+        Some(URLPatternResult {
+            inputs,
+            protocol: convert_component(result.protocol),
+            username: convert_component(result.username),
+            password: convert_component(result.password),
+            hostname: convert_component(result.hostname),
+            port: convert_component(result.port),
+            pathname: convert_component(result.pathname),
+            search: convert_component(result.search),
+            hash: convert_component(result.hash),
+        })
     }
 
     /// <https://urlpattern.spec.whatwg.org/#dom-urlpattern-protocol>
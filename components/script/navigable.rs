@@ -1,19 +1,36 @@
 use std::borrow::Borrow;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
 use std::rc::{Rc, Weak};
-use std::sync::{Arc, Mutex, Condvar};
+use std::sync::{Arc, Mutex, Condvar, OnceLock};
 use std::thread;
+use std::time::Duration;
 
+use log::debug;
 use servo_url::ServoUrl;
 
 use crate::conversions::Convert;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::document::Document;
 
+/// Whether a session history entry's document is settled, or still being replaced by an
+/// in-flight fetch - <https://html.spec.whatwg.org/multipage/#populating-a-session-history-entry>
+/// names the latter state but doesn't track it explicitly; this does, so a reload can tell the
+/// two apart instead of always enqueuing a dependent step that would deadlock against an
+/// in-progress fetch it's itself waiting on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PopulateState {
+    #[default]
+    Ready,
+    PopulatingByFetching,
+}
+
 /// <https://html.spec.whatwg.org/multipage/#document-state-2>
 #[derive(Default)]
 pub struct DocumentState {
     pub document: Option<DomRoot<Document>>,
     pub reload_pending: bool,
+    pub populate_state: PopulateState,
 }
 
 impl Convert<SessionHistoryEntry> for DocumentState {
@@ -25,14 +42,19 @@ impl Convert<SessionHistoryEntry> for DocumentState {
             url: document.borrow().url(),
             document_state: DocumentState {
                 document: Some(document),
-                reload_pending: false
+                reload_pending: false,
+                populate_state: self.populate_state,
             },
-            scroll_restoration_mode: Default::default()
+            // A freshly populated entry starts with no snapshotted scroll offset and the default
+            // restoration mode - set explicitly (rather than via `Default::default()`) so adding
+            // a field here can't silently leave it uninitialized again in the future.
+            scroll_restoration_mode: Cell::new(ScrollRestorationMode::Auto),
+            scroll_position: Cell::new(None),
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum SessionHistoryEntryStep {
     #[default]
     Pending,
@@ -40,7 +62,7 @@ pub enum SessionHistoryEntryStep {
 }
 
 /// <https://html.spec.whatwg.org/multipage/#scroll-restoration-mode>
-#[derive(Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum ScrollRestorationMode {
     /// The user agent is responsible for restoring the scroll position upon navigation.
     #[default]
@@ -55,7 +77,15 @@ pub struct SessionHistoryEntry {
     step: SessionHistoryEntryStep,
     url: ServoUrl,
     document_state: DocumentState,
-    scroll_restoration_mode: ScrollRestorationMode,
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-scrollrestoration>
+    ///
+    /// Wrapped in a `Cell` (rather than taking `&mut self`, like the rest of this struct) because
+    /// `Navigable` only ever holds entries behind an `Rc`, and `history.scrollRestoration`'s
+    /// setter must be able to flip this on the active entry through that shared reference.
+    scroll_restoration_mode: Cell<ScrollRestorationMode>,
+    /// The entry's scroll offset, snapshotted when a navigation away from it begins and restored
+    /// on traversal back to it - but only when `scroll_restoration_mode` is `Auto`.
+    scroll_position: Cell<Option<(f32, f32)>>,
 }
 
 impl Default for SessionHistoryEntry {
@@ -64,11 +94,38 @@ impl Default for SessionHistoryEntry {
             step: Default::default(),
             url: ServoUrl::parse("about:blank").unwrap(),
             document_state: Default::default(),
-            scroll_restoration_mode: ScrollRestorationMode::Auto,
+            scroll_restoration_mode: Cell::new(ScrollRestorationMode::Auto),
+            scroll_position: Cell::new(None),
         }
     }
 }
 
+impl SessionHistoryEntry {
+    /// This entry's position in its traversable's history list, or `Pending` if it hasn't been
+    /// assigned one yet (e.g. still being added) - <https://html.spec.whatwg.org/multipage/#nav-step>.
+    pub fn step(&self) -> SessionHistoryEntryStep {
+        self.step
+    }
+
+    pub fn scroll_restoration_mode(&self) -> ScrollRestorationMode {
+        self.scroll_restoration_mode.get()
+    }
+
+    pub fn set_scroll_restoration_mode(&self, mode: ScrollRestorationMode) {
+        self.scroll_restoration_mode.set(mode);
+    }
+
+    pub fn scroll_position(&self) -> Option<(f32, f32)> {
+        self.scroll_position.get()
+    }
+
+    /// Snapshots the entry's current scroll offset, called when a navigation away from this
+    /// entry begins so a later traversal back to it has something to restore.
+    pub fn set_scroll_position(&self, position: (f32, f32)) {
+        self.scroll_position.set(Some(position));
+    }
+}
+
 pub struct Navigable {
     id: u64,
     parent: Option<Weak<Navigable>>,
@@ -96,22 +153,66 @@ impl Navigable {
         // 5. Set navigable's parent to parent.
         self.parent.clone_from(parent);
     }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-scrollrestoration>
+    pub fn scroll_restoration_mode(&self) -> ScrollRestorationMode {
+        self.active_session_history_entry.scroll_restoration_mode()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-scrollrestoration>
+    pub fn set_scroll_restoration_mode(&self, mode: ScrollRestorationMode) {
+        self.active_session_history_entry.set_scroll_restoration_mode(mode);
+    }
 }
 
-pub struct TraversableNavigable {
-    current_session_history_step: usize,
-    session_history_entries: Vec<Rc<SessionHistoryEntry>>
+/// A named unit of work queued onto a [`ParallelQueue`], in place of an opaque `FnOnce` closure:
+/// naming every task lets the worker log/trace what's currently running and count pending work
+/// by name - a prerequisite for eventually cancelling a specific pending task by name too, which
+/// an opaque closure could never support.
+pub trait TaskOnce: Send {
+    /// Runs this task, consuming it.
+    fn run_once(self: Box<Self>);
+
+    /// A short, human-readable name for this task, e.g. `"reload"` or `"traverse_the_history"`.
+    fn name(&self) -> &'static str;
+}
+
+/// Wraps a plain closure into a [`TaskOnce`] under a given name. Built by the [`task!`] macro
+/// rather than named directly.
+struct ClosureTask<F> {
+    name: &'static str,
+    closure: F,
+}
+
+impl<F> TaskOnce for ClosureTask<F>
+where
+    F: FnOnce() + Send,
+{
+    fn run_once(self: Box<Self>) {
+        (self.closure)();
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// Wraps a closure into a named [`TaskOnce`], e.g. `task!(reload: move || { ... })` produces a
+/// task whose `name()` is `"reload"`.
+macro_rules! task {
+    ($name:ident : $closure:expr) => {
+        Box::new(ClosureTask { name: stringify!($name), closure: $closure }) as Box<dyn TaskOnce>
+    };
 }
 
 /// Trait for a parallel queue.
 /// <https://html.spec.whatwg.org/multipage/infrastructure.html#parallel-queue>
 pub trait ParallelQueue {
-    type Step: FnOnce() + Send + 'static;
-
-    /// Enqueue a step into the parallel queue.
-    fn enqueue(&self, step: Self::Step);
+    /// Enqueue a task into the parallel queue. Fails with [`QueueStopped`] if the queue has
+    /// already been stopped.
+    fn enqueue(&self, task: Box<dyn TaskOnce>) -> Result<(), QueueStopped>;
 
-    /// Start processing the parallel queue.
+    /// Start processing the parallel queue, with no bound on how many tasks may be pending.
     fn start() -> Self
     where
         Self: Sized;
@@ -120,97 +221,592 @@ pub trait ParallelQueue {
     fn stop(&mut self);
 }
 
-/// A concrete implementation of the ParallelQueue trait.
-pub struct ParallelQueueImpl<T>
-where
-    T: FnOnce() + Send + 'static,
-{
-    queue: Arc<Mutex<Vec<T>>>,        // Shared queue of tasks.
-    condvar: Arc<Condvar>,            // Condition variable for signaling.
-    is_running: Arc<Mutex<bool>>,     // Indicates if the worker thread should run.
+/// Returned by [`ParallelQueue::enqueue`] when the queue has already been stopped and can no
+/// longer accept steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueStopped;
+
+/// <https://html.spec.whatwg.org/multipage/#nav-ongoing-navigation>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OngoingNavigation {
+    #[default]
+    None,
+    Traversal,
+}
+
+pub struct TraversableNavigable {
+    current_session_history_step: usize,
+    session_history_entries: Vec<Rc<SessionHistoryEntry>>,
+    ongoing_navigation: OngoingNavigation,
+}
+
+impl TraversableNavigable {
+    /// This traversable's current session history entry -
+    /// <https://html.spec.whatwg.org/multipage/#nav-current>.
+    fn current_session_history_entry(&self) -> &Rc<SessionHistoryEntry> {
+        &self.session_history_entries[self.current_session_history_step]
+    }
+
+    /// The reload path of `location.reload()` -
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-reload>.
+    ///
+    /// Ordinarily this sets the ongoing navigation to "traversal" and enqueues a dependent step
+    /// to re-navigate the current entry. But if the current entry's document is still being
+    /// populated by an in-flight fetch, that dependent step would wait on the very fetch the
+    /// reload itself needs to finish - a deadlock. Detect that case and restart population
+    /// directly against the current entry instead, skipping the queue entirely.
+    pub fn reload(&mut self, queue: &impl ParallelQueue) -> Result<(), QueueStopped> {
+        if self.current_session_history_entry().document_state.populate_state ==
+            PopulateState::PopulatingByFetching
+        {
+            self.restart_population_of_current_entry();
+            return Ok(());
+        }
+
+        self.ongoing_navigation = OngoingNavigation::Traversal;
+        queue.enqueue(task!(reload: move || {
+            // Would drive the real navigate/fetch/response pipeline for the current entry; not
+            // modeled in this snapshot.
+        }))
+    }
+
+    /// Restarts population of the current entry's document directly, bypassing the traversal
+    /// queue - see [`Self::reload`].
+    fn restart_population_of_current_entry(&mut self) {
+        let step = self.current_session_history_step;
+        if let Some(entry) = Rc::get_mut(&mut self.session_history_entries[step]) {
+            entry.document_state.populate_state = PopulateState::PopulatingByFetching;
+        }
+    }
+
+    /// Snapshots `position` onto the current entry, called as a navigation away from it begins -
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#scroll-restoration>.
+    pub fn snapshot_scroll_position_for_current_entry(&self, position: (f32, f32)) {
+        self.current_session_history_entry().set_scroll_position(position);
+    }
+
+    /// The scroll offset to restore for the current entry on traversal, honoring
+    /// `scroll_restoration_mode`: `None` both when nothing was ever snapshotted and when the
+    /// entry opted into `Manual` restoration, in which case the page repositions itself.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#scroll-restoration>
+    pub fn scroll_position_to_restore_for_current_entry(&self) -> Option<(f32, f32)> {
+        let entry = self.current_session_history_entry();
+        match entry.scroll_restoration_mode() {
+            ScrollRestorationMode::Auto => entry.scroll_position(),
+            ScrollRestorationMode::Manual => None,
+        }
+    }
+
+    /// Traverse the history by a delta - <https://html.spec.whatwg.org/multipage/browsing-the-web.html#traverse-the-history-by-a-delta>,
+    /// the shared implementation behind `history.go(delta)`/`back()`/`forward()`.
+    ///
+    /// Only entries whose [`SessionHistoryEntryStep`] has been assigned an `Integer` are part of
+    /// the reachable history - a `Pending` entry is still being added and is skipped over, both as
+    /// a possible target and when locating where the current entry sits in that list. `delta` is
+    /// then applied as an offset into the reachable list and clamped to its ends.
+    ///
+    /// On a real move, marks the target entry's document as due for a reload, sets the ongoing
+    /// navigation to "traversal", and enqueues the traversal step onto `queue`; this toy model has
+    /// no navigable tree to walk, so unlike the spec algorithm it has only the single entry's
+    /// document state to update rather than one per affected navigable.
+    ///
+    /// Returns `true` if `delta` put the target outside the reachable range (or the current entry
+    /// itself isn't reachable yet) and nothing was done, matching `back()`/`forward()` silently
+    /// no-op'ing past the ends of the history list.
+    pub fn traverse_the_history_by_delta(
+        &mut self,
+        delta: isize,
+        queue: &impl ParallelQueue,
+    ) -> Result<bool, QueueStopped> {
+        let reachable_steps: Vec<usize> = self
+            .session_history_entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| matches!(entry.step(), SessionHistoryEntryStep::Integer(_)))
+            .map(|(index, _)| index)
+            .collect();
+
+        let Some(current_position) = reachable_steps
+            .iter()
+            .position(|&index| index == self.current_session_history_step)
+        else {
+            return Ok(true);
+        };
+
+        let target_position = current_position as isize + delta;
+        if target_position < 0 || target_position as usize >= reachable_steps.len() {
+            return Ok(true);
+        }
+        let target_position = target_position as usize;
+        if target_position == current_position {
+            return Ok(true);
+        }
+
+        let target_step = reachable_steps[target_position];
+        self.current_session_history_step = target_step;
+        if let Some(entry) = Rc::get_mut(&mut self.session_history_entries[target_step]) {
+            entry.document_state.reload_pending = true;
+        }
+
+        self.ongoing_navigation = OngoingNavigation::Traversal;
+        queue.enqueue(task!(traverse_the_history: move || {
+            // Would apply the history step for real - updating each affected navigable's active
+            // session history entry and firing popstate/navigate events; not modeled in this
+            // snapshot.
+        }))?;
+
+        Ok(false)
+    }
+}
+
+/// A reproduction of the hang this module's [`TraversableNavigable::reload`] fixes: a child
+/// navigable's `location.reload()`, called from its own `load` handler while its current entry is
+/// still mid-fetch, restarts population directly instead of enqueuing a step that would never run
+/// (the queue would be waiting on the very fetch blocking it).
+#[test]
+fn reload_from_load_handler_does_not_hang() {
+    let entry = Rc::new(SessionHistoryEntry {
+        document_state: DocumentState {
+            populate_state: PopulateState::PopulatingByFetching,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let mut traversable = TraversableNavigable {
+        current_session_history_step: 0,
+        session_history_entries: vec![entry],
+        ongoing_navigation: OngoingNavigation::default(),
+    };
+
+    let queue = ParallelQueueImpl::new();
+    traversable
+        .reload(&queue)
+        .expect("reload must not enqueue a step onto a queue that was never started");
+    assert_eq!(
+        traversable.current_session_history_entry().document_state.populate_state,
+        PopulateState::PopulatingByFetching,
+    );
+}
+
+/// A reproduction of [`TraversableNavigable::traverse_the_history_by_delta`] skipping a `Pending`
+/// entry: an entry still being added (e.g. a sibling reload racing a new navigation) must never be
+/// landed on or counted as a step when computing how far `delta` reaches.
+#[test]
+fn traverse_the_history_by_delta_skips_pending_entries() {
+    let first = Rc::new(SessionHistoryEntry {
+        step: SessionHistoryEntryStep::Integer(0),
+        ..Default::default()
+    });
+    let pending = Rc::new(SessionHistoryEntry {
+        step: SessionHistoryEntryStep::Pending,
+        ..Default::default()
+    });
+    let third = Rc::new(SessionHistoryEntry {
+        step: SessionHistoryEntryStep::Integer(2),
+        ..Default::default()
+    });
+
+    let mut traversable = TraversableNavigable {
+        current_session_history_step: 0,
+        session_history_entries: vec![first, pending, third],
+        ongoing_navigation: OngoingNavigation::default(),
+    };
+
+    let mut queue = ParallelQueueImpl::start();
+    let no_op = traversable
+        .traverse_the_history_by_delta(1, &queue)
+        .expect("the started queue must accept the traversal step");
+
+    assert!(!no_op, "delta=1 should reach the next reachable entry, not no-op");
+    assert_eq!(traversable.current_session_history_step, 2);
+
+    let no_op = traversable
+        .traverse_the_history_by_delta(1, &queue)
+        .expect("a traversal past the last entry must not fail");
+    assert!(no_op, "delta past the last reachable entry should no-op");
+
+    queue.stop();
+}
+
+/// Configurable capacity limits for a bounded [`ParallelQueueImpl`], modeled on the GStreamer
+/// threadshare queue element's `max-size-buffers`/`max-size-bytes`/`max-size-time` properties: the
+/// queue is full - and `enqueue` blocks the calling thread, providing back-pressure, until room
+/// frees up - as soon as *any* configured (`Some`) limit is reached. A limit left as `None` never
+/// constrains the queue.
+///
+/// Only `max_size_buffers` is enforced against a plain [`ParallelQueue::enqueue`] call, since a
+/// [`TaskOnce`] has no inherent byte size or duration. `max_size_bytes`/`max_size_time` are only
+/// charged against tasks enqueued via [`ParallelQueueImpl::enqueue_sized`], which supplies that
+/// accounting explicitly.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueueLimits {
+    pub max_size_buffers: Option<usize>,
+    pub max_size_bytes: Option<usize>,
+    pub max_size_time: Option<Duration>,
+}
+
+impl QueueLimits {
+    fn is_full(&self, buffers: usize, bytes: usize, time: Duration) -> bool {
+        self.max_size_buffers.is_some_and(|max| buffers >= max) ||
+            self.max_size_bytes.is_some_and(|max| bytes >= max) ||
+            self.max_size_time.is_some_and(|max| time >= max)
+    }
+}
+
+/// One queued task, along with the `max_size_bytes`/`max_size_time` accounting supplied for it
+/// (zero for a task enqueued through the plain, unsized `enqueue`).
+struct QueueItem {
+    task: Box<dyn TaskOnce>,
+    size_bytes: usize,
+    duration: Duration,
+}
+
+struct QueueState {
+    items: VecDeque<QueueItem>,
+    queued_bytes: usize,
+    queued_time: Duration,
+    is_running: bool,
+    /// How many pending items currently carry each [`TaskOnce::name`], for observability - e.g.
+    /// to notice one task name backing up the queue.
+    pending_by_name: HashMap<&'static str, usize>,
+}
+
+impl QueueState {
+    fn push(&mut self, item: QueueItem) {
+        *self.pending_by_name.entry(item.task.name()).or_insert(0) += 1;
+        self.queued_bytes += item.size_bytes;
+        self.queued_time += item.duration;
+        self.items.push_back(item);
+    }
+
+    fn pop(&mut self) -> Option<QueueItem> {
+        let item = self.items.pop_front()?;
+        self.queued_bytes -= item.size_bytes;
+        self.queued_time -= item.duration;
+        if let Some(count) = self.pending_by_name.get_mut(item.task.name()) {
+            *count -= 1;
+            if *count == 0 {
+                self.pending_by_name.remove(item.task.name());
+            }
+        }
+        Some(item)
+    }
+}
+
+fn next_queue_id() -> u64 {
+    static NEXT_QUEUE_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    NEXT_QUEUE_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// A concrete implementation of the ParallelQueue trait: a FIFO queue - so tasks run in the order
+/// they were enqueued - serviced either by its own worker thread ([`Self::spawn_worker`]) or by the
+/// pool of the [`QueueContext`] it is attached to ([`Self::start_in`]).
+pub struct ParallelQueueImpl {
+    id: u64,
+    state: Arc<Mutex<QueueState>>,
+    not_empty: Arc<Condvar>, // Signaled when a task is enqueued, or the queue is stopped.
+    not_full: Arc<Condvar>,  // Signaled when a task is dequeued, for a blocked enqueue to retry.
+    limits: QueueLimits,
+    flush_pending_on_stop: bool,
     worker_handle: Option<thread::JoinHandle<()>>, // Worker thread handle.
 }
 
-impl<T> ParallelQueueImpl<T>
-where
-    T: FnOnce() + Send + 'static,
-{
+impl ParallelQueueImpl {
+    /// Creates an unbounded queue: `enqueue` never blocks.
     pub fn new() -> Self {
+        Self::with_limits(QueueLimits::default())
+    }
+
+    /// Creates a queue bounded to at most `capacity` pending steps. `enqueue` blocks the calling
+    /// thread while the queue is at capacity, so producers get back-pressure instead of letting
+    /// the queue grow without bound.
+    pub fn bounded(capacity: usize) -> Self {
+        Self::with_limits(QueueLimits {
+            max_size_buffers: Some(capacity),
+            ..QueueLimits::default()
+        })
+    }
+
+    fn with_limits(limits: QueueLimits) -> Self {
         Self {
-            queue: Arc::new(Mutex::new(Vec::new())),
-            condvar: Arc::new(Condvar::new()),
-            is_running: Arc::new(Mutex::new(false)),
+            id: next_queue_id(),
+            state: Arc::new(Mutex::new(QueueState {
+                items: VecDeque::new(),
+                queued_bytes: 0,
+                queued_time: Duration::ZERO,
+                is_running: false,
+                pending_by_name: HashMap::new(),
+            })),
+            not_empty: Arc::new(Condvar::new()),
+            not_full: Arc::new(Condvar::new()),
+            limits,
+            flush_pending_on_stop: true,
             worker_handle: None,
         }
     }
 
+    /// How many pending tasks named `name` are currently queued, for observability.
+    pub fn pending_count(&self, name: &str) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .pending_by_name
+            .get(name)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Controls what [`ParallelQueue::stop`] does with tasks still pending at the moment it's
+    /// called: when `true` (the default), they're run before the worker thread exits, so in-flight
+    /// navigation steps aren't silently dropped; when `false`, they're discarded unrun so `stop`
+    /// returns as soon as the task currently running finishes. The flag is read when
+    /// [`Self::spawn_worker`] starts the worker, so a later call has no effect on that worker; a
+    /// queue attached with [`Self::start_in`] runs its pending tasks whatever the flag says.
+    pub fn set_flush_pending_on_stop(&mut self, flush: bool) {
+        self.flush_pending_on_stop = flush;
+    }
+
+    /// Starts the worker thread for a queue constructed via [`Self::new`] or [`Self::bounded`].
+    /// `ParallelQueue::start()` is equivalent to `Self::new().spawn_worker()`.
+    pub fn spawn_worker(mut self) -> Self {
+        self.state.lock().unwrap().is_running = true;
+
+        let id = self.id;
+        let state = self.state.clone();
+        let not_empty = self.not_empty.clone();
+        let not_full = self.not_full.clone();
+        let flush_pending_on_stop = self.flush_pending_on_stop;
+        self.worker_handle = Some(thread::spawn(move || {
+            Self::process_tasks(id, state, not_empty, not_full, flush_pending_on_stop);
+        }));
+
+        self
+    }
+
+    /// Attaches this already-constructed queue to `context` instead of spawning a dedicated
+    /// worker thread for it: the returned queue still has the normal `enqueue`/`stop` API, but
+    /// its pending tasks are run by whichever of `context`'s pool workers picks them up next,
+    /// multiplexed alongside every other queue attached to that same context. Keeps OS thread
+    /// count bounded by the context's fixed worker count, regardless of how many queues attach.
+    pub fn start_in(self, context: &QueueContext) -> Self {
+        self.state.lock().unwrap().is_running = true;
+        context.register(Box::new(AttachedQueueImpl {
+            id: self.id,
+            state: self.state.clone(),
+            not_full: self.not_full.clone(),
+        }));
+        self
+    }
+
+    /// Like [`ParallelQueue::enqueue`], but also charges `size_bytes`/`duration` against this
+    /// queue's `max_size_bytes`/`max_size_time` limits, for producers that can estimate them.
+    pub fn enqueue_sized(
+        &self,
+        task: Box<dyn TaskOnce>,
+        size_bytes: usize,
+        duration: Duration,
+    ) -> Result<(), QueueStopped> {
+        let mut guard = self.state.lock().unwrap();
+        loop {
+            if !guard.is_running {
+                return Err(QueueStopped);
+            }
+            if !self
+                .limits
+                .is_full(guard.items.len(), guard.queued_bytes, guard.queued_time)
+            {
+                break;
+            }
+            guard = self.not_full.wait(guard).unwrap();
+        }
+
+        guard.push(QueueItem { task, size_bytes, duration });
+        drop(guard); // Unlock before notifying.
+        self.not_empty.notify_one(); // Signal the worker thread.
+        Ok(())
+    }
+
     /// Internal function to process tasks.
-    fn process_tasks(queue: Arc<Mutex<Vec<T>>>, condvar: Arc<Condvar>, is_running: Arc<Mutex<bool>>) {
+    fn process_tasks(
+        id: u64,
+        state: Arc<Mutex<QueueState>>,
+        not_empty: Arc<Condvar>,
+        not_full: Arc<Condvar>,
+        flush_pending_on_stop: bool,
+    ) {
         loop {
-            let mut guard = queue.lock().unwrap();
+            let mut guard = state.lock().unwrap();
 
             // Wait for tasks or a signal to stop.
-            while guard.is_empty() && *is_running.lock().unwrap() {
-                guard = condvar.wait(guard).unwrap();
+            while guard.items.is_empty() && guard.is_running {
+                guard = not_empty.wait(guard).unwrap();
+            }
+
+            if guard.items.is_empty() {
+                // Stopped with nothing left pending.
+                break;
             }
 
-            // Exit the loop if the queue is stopped.
-            if !*is_running.lock().unwrap() {
+            if !guard.is_running && !flush_pending_on_stop {
+                // Stopped and asked to discard rather than flush: drop everything still queued.
+                guard.items.clear();
                 break;
             }
 
-            // Process tasks while the queue is non-empty.
-            while let Some(task) = guard.pop() {
+            // Process tasks in FIFO order while the queue is non-empty.
+            while let Some(item) = guard.pop() {
                 drop(guard); // Unlock the queue while running the task.
-                task();      // Run the task.
-                guard = queue.lock().unwrap(); // Reacquire the lock.
+                not_full.notify_one(); // Wake a producer blocked on a full bounded queue.
+                debug!("running {} on queue {}", item.task.name(), id);
+                item.task.run_once();
+                guard = state.lock().unwrap(); // Reacquire the lock.
+                if !guard.is_running && !flush_pending_on_stop {
+                    // Stopped mid-drain and asked to discard: leave the rest unrun.
+                    guard.items.clear();
+                    break;
+                }
+            }
+
+            if !guard.is_running {
+                break;
             }
         }
     }
 }
 
-impl<T> ParallelQueue for ParallelQueueImpl<T>
-where
-    T: FnOnce() + Send + 'static,
-{
-    type Step = T;
+/// The interface a [`QueueContext`] worker uses to pull one pending task from an attached queue,
+/// without needing to know that queue's concrete type.
+trait AttachedQueue: Send {
+    /// Runs at most one pending task from this queue, returning whether one was found to run.
+    fn try_run_one(&self) -> bool;
 
-    fn enqueue(&self, step: Self::Step) {
-        let mut guard = self.queue.lock().unwrap();
-        guard.push(step);
-        drop(guard); // Unlock before notifying.
-        self.condvar.notify_one(); // Signal the worker thread.
+    /// Whether this queue has been stopped and has nothing left pending, so the context can drop
+    /// it from its registry.
+    fn is_finished(&self) -> bool;
+}
+
+struct AttachedQueueImpl {
+    id: u64,
+    state: Arc<Mutex<QueueState>>,
+    not_full: Arc<Condvar>,
+}
+
+impl AttachedQueue for AttachedQueueImpl {
+    fn try_run_one(&self) -> bool {
+        let mut guard = self.state.lock().unwrap();
+        let Some(item) = guard.pop() else {
+            return false;
+        };
+        drop(guard); // Unlock the queue while running the task.
+        self.not_full.notify_one(); // Wake a producer blocked on a full bounded queue.
+        debug!("running {} on queue {}", item.task.name(), self.id);
+        item.task.run_once();
+        true
     }
 
-    fn start() -> Self {
-        let parallel_queue = Self::new();
-        let queue_clone = parallel_queue.queue.clone();
-        let condvar_clone = parallel_queue.condvar.clone();
-        let is_running_clone = parallel_queue.is_running.clone();
+    fn is_finished(&self) -> bool {
+        let guard = self.state.lock().unwrap();
+        !guard.is_running && guard.items.is_empty()
+    }
+}
 
-        // Set the running flag to true and start the worker thread.
-        {
-            let mut is_running = is_running_clone.lock().unwrap();
-            *is_running = true;
-        }
+/// A named, fixed-size pool of worker threads that any number of [`ParallelQueue`]s can attach
+/// to via [`ParallelQueueImpl::start_in`], instead of each spawning its own dedicated OS thread -
+/// keeping thread count bounded as the navigable tree grows. Modeled on the threadshare
+/// `Context`/`IOContext` design: workers cooperatively round-robin over every attached queue,
+/// running one pending step at a time from each, and sleep for `context_wait` rather than
+/// spinning when none have work.
+///
+/// Contexts are looked up by name ([`QueueContext::get_or_create`]) so related subsystems (e.g.
+/// every queue for one navigable's session-history traversal) can opt into sharing a pool.
+pub struct QueueContext {
+    shared: Arc<QueueContextShared>,
+}
 
-        let handle = thread::spawn(move || {
-            Self::process_tasks(queue_clone, condvar_clone, is_running_clone);
-        });
+struct QueueContextShared {
+    queues: Mutex<Vec<Box<dyn AttachedQueue>>>,
+    wake: Condvar,
+    is_running: Mutex<bool>,
+    context_wait: Duration,
+}
+
+fn queue_context_registry() -> &'static Mutex<HashMap<String, Arc<QueueContextShared>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, Arc<QueueContextShared>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl QueueContext {
+    /// Gets the context already registered under `name`, or creates one with `worker_count`
+    /// worker threads and an idle wait budget of `context_wait` if none exists yet. Subsequent
+    /// calls with the same `name` return handles to the same underlying pool; `worker_count`/
+    /// `context_wait` are only used the first time `name` is seen.
+    pub fn get_or_create(name: &str, worker_count: usize, context_wait: Duration) -> QueueContext {
+        let mut registry = queue_context_registry().lock().unwrap();
+        let shared = registry
+            .entry(name.to_owned())
+            .or_insert_with(|| {
+                let shared = Arc::new(QueueContextShared {
+                    queues: Mutex::new(Vec::new()),
+                    wake: Condvar::new(),
+                    is_running: Mutex::new(true),
+                    context_wait,
+                });
+                for worker_index in 0..worker_count.max(1) {
+                    let shared = shared.clone();
+                    let name = name.to_owned();
+                    thread::Builder::new()
+                        .name(format!("QueueContext({name})#{worker_index}"))
+                        .spawn(move || QueueContext::worker_loop(shared))
+                        .expect("failed to spawn QueueContext worker thread");
+                }
+                shared
+            })
+            .clone();
+        QueueContext { shared }
+    }
 
-        ParallelQueueImpl {
-            worker_handle: Some(handle),
-            ..parallel_queue
+    fn register(&self, queue: Box<dyn AttachedQueue>) {
+        self.shared.queues.lock().unwrap().push(queue);
+        self.shared.wake.notify_one();
+    }
+
+    fn worker_loop(shared: Arc<QueueContextShared>) {
+        while *shared.is_running.lock().unwrap() {
+            let ran_one = {
+                let mut queues = shared.queues.lock().unwrap();
+                queues.retain(|queue| !queue.is_finished());
+                // Try every attached queue once per pass, so one busy queue can't starve the
+                // others out - rather than stopping at the first queue with a step to run.
+                queues.iter().fold(false, |ran_any, queue| queue.try_run_one() || ran_any)
+            };
+
+            if !ran_one {
+                let guard = shared.is_running.lock().unwrap();
+                let _ = shared.wake.wait_timeout(guard, shared.context_wait);
+            }
         }
     }
+}
+
+impl ParallelQueue for ParallelQueueImpl {
+    fn enqueue(&self, task: Box<dyn TaskOnce>) -> Result<(), QueueStopped> {
+        self.enqueue_sized(task, 0, Duration::ZERO)
+    }
+
+    fn start() -> Self {
+        Self::new().spawn_worker()
+    }
 
     fn stop(&mut self) {
         // Set the running flag to false and notify all waiting threads.
         {
-            let mut is_running = self.is_running.lock().unwrap();
-            *is_running = false;
+            let mut guard = self.state.lock().unwrap();
+            guard.is_running = false;
         }
-        self.condvar.notify_all();
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
 
         // Join the worker thread if it's running.
         if let Some(handle) = self.worker_handle.take() {
@@ -224,9 +820,9 @@ fn queue_test() {
     let mut queue = ParallelQueueImpl::start();
 
     // Enqueue some tasks.
-    queue.enqueue(|| println!("Task 1 is running"));
-    queue.enqueue(|| println!("Task 2 is running"));
-    queue.enqueue(|| println!("Task 3 is running"));
+    queue.enqueue(task!(task_1: move || println!("Task 1 is running"))).unwrap();
+    queue.enqueue(task!(task_2: move || println!("Task 2 is running"))).unwrap();
+    queue.enqueue(task!(task_3: move || println!("Task 3 is running"))).unwrap();
 
     // Allow time for tasks to execute.
     std::thread::sleep(std::time::Duration::from_secs(1));
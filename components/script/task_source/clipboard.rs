@@ -0,0 +1,39 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use base::id::PipelineId;
+use crossbeam_channel::Sender;
+
+use crate::messaging::MainThreadScriptMsg;
+use crate::script_runtime::{CommonScriptMsg, ScriptThreadEventCategory};
+use crate::task::{TaskCanceller, TaskOnce};
+use crate::task_source::{TaskSource, TaskSourceName};
+
+/// The task source `copy`/`cut`/`paste` `ClipboardEvent`s are queued on, so firing them (and the
+/// clipboard read/write that follows) happens as a task rather than synchronously inline with
+/// whatever user interaction triggered it.
+///
+/// <https://w3c.github.io/clipboard-apis/#clipboard-event-task-source>
+#[derive(Clone, JSTraceable)]
+pub(crate) struct ClipboardEventTaskSource(
+    #[no_trace] pub Sender<MainThreadScriptMsg>,
+    #[no_trace] pub PipelineId,
+);
+
+impl TaskSource for ClipboardEventTaskSource {
+    const NAME: TaskSourceName = TaskSourceName::ClipboardEvent;
+
+    fn queue_with_canceller<T>(&self, task: T, canceller: &TaskCanceller) -> Result<(), ()>
+    where
+        T: TaskOnce + 'static,
+    {
+        let msg = MainThreadScriptMsg::Common(CommonScriptMsg::Task(
+            ScriptThreadEventCategory::ClipboardEvent,
+            Box::new(canceller.wrap_task(task)),
+            Some(self.1),
+            ClipboardEventTaskSource::NAME,
+        ));
+        self.0.send(msg).map_err(|_| ())
+    }
+}
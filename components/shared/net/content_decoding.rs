@@ -0,0 +1,182 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Incremental `Content-Encoding` decompression for the raw [`ResponseBody`](crate::response::ResponseBody)
+//! bytes a fetch produces, so nothing downstream of the network loop ever has to decode a
+//! compressed body itself.
+//!
+//! <https://fetch.spec.whatwg.org/#content-encoding-and-decoding>
+
+use std::io::Write;
+
+use flate2::write::{DeflateDecoder, GzDecoder};
+
+/// A single supported `Content-Encoding` coding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContentCoding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+}
+
+impl ContentCoding {
+    fn from_token(token: &str) -> Option<ContentCoding> {
+        match token.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(ContentCoding::Gzip),
+            "deflate" => Some(ContentCoding::Deflate),
+            "br" => Some(ContentCoding::Brotli),
+            "zstd" => Some(ContentCoding::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a `Content-Encoding` header value into the codings applied to the body, in the order
+/// they must be *removed* (the reverse of the order they were applied in).
+///
+/// Returns `None` if any listed coding is unrecognized, since a single unsupported coding in the
+/// stack means the whole body can't be decoded.
+pub fn parse_content_codings(header_value: &str) -> Option<Vec<ContentCoding>> {
+    if header_value.trim().is_empty() {
+        return None;
+    }
+
+    let codings = header_value
+        .split(',')
+        .map(ContentCoding::from_token)
+        .collect::<Option<Vec<_>>>()?;
+
+    if codings.is_empty() {
+        return None;
+    }
+
+    Some(codings.into_iter().rev().collect())
+}
+
+/// An incremental decoder for a (possibly multi-coding) `Content-Encoding` stack.
+///
+/// Each network chunk is fed through every stage in turn; a chunk may expand into zero or many
+/// bytes of output. The gzip, deflate and zstd stages never hold the whole body in memory; a
+/// brotli stage keeps every compressed byte received so far (see `brotli_input`).
+pub struct ContentDecoder {
+    stages: Vec<ContentCoding>,
+    gzip: Option<Box<GzDecoder<Vec<u8>>>>,
+    deflate: Option<Box<DeflateDecoder<Vec<u8>>>>,
+    zstd: Option<Box<zstd::stream::write::Decoder<'static, Vec<u8>>>>,
+    /// Brotli has no incremental `Write`-sink decoder in the crate we use elsewhere, so its
+    /// stage buffers compressed input and re-decodes from the start on every chunk, into a
+    /// temporary buffer that holds all of the output decoded so far. It isn't incremental.
+    brotli_input: Vec<u8>,
+    brotli_decoded_so_far: usize,
+    has_brotli: bool,
+}
+
+impl ContentDecoder {
+    /// Build a decoder for `stages` (in removal order, as returned by [`parse_content_codings`]).
+    /// Returns `None` if `stages` is empty, since callers should skip decoding entirely in that
+    /// case rather than allocate a no-op decoder.
+    pub fn new(stages: Vec<ContentCoding>) -> Option<ContentDecoder> {
+        if stages.is_empty() {
+            return None;
+        }
+
+        let has_brotli = stages.contains(&ContentCoding::Brotli);
+
+        Some(ContentDecoder {
+            gzip: stages
+                .contains(&ContentCoding::Gzip)
+                .then(|| Box::new(GzDecoder::new(Vec::new()))),
+            deflate: stages
+                .contains(&ContentCoding::Deflate)
+                .then(|| Box::new(DeflateDecoder::new(Vec::new()))),
+            zstd: stages.contains(&ContentCoding::Zstd).then(|| {
+                Box::new(
+                    zstd::stream::write::Decoder::new(Vec::new())
+                        .expect("zstd decoder initialization cannot fail"),
+                )
+            }),
+            brotli_input: Vec::new(),
+            brotli_decoded_so_far: 0,
+            has_brotli,
+            stages,
+        })
+    }
+
+    /// Feed one network chunk through every decoding stage and return the decoded output ready
+    /// to append to the response body.
+    pub fn decode(&mut self, chunk: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut data = chunk.to_vec();
+
+        for stage in self.stages.clone() {
+            data = match stage {
+                ContentCoding::Gzip => {
+                    let decoder = self.gzip.as_mut().expect("gzip stage missing decoder");
+                    decoder.write_all(&data)?;
+                    std::mem::take(decoder.get_mut())
+                },
+                ContentCoding::Deflate => {
+                    let decoder = self
+                        .deflate
+                        .as_mut()
+                        .expect("deflate stage missing decoder");
+                    decoder.write_all(&data)?;
+                    std::mem::take(decoder.get_mut())
+                },
+                ContentCoding::Zstd => {
+                    let decoder = self.zstd.as_mut().expect("zstd stage missing decoder");
+                    decoder.write_all(&data)?;
+                    std::mem::take(decoder.get_mut())
+                },
+                ContentCoding::Brotli => {
+                    self.brotli_input.extend_from_slice(&data);
+                    self.decode_brotli_so_far()?
+                },
+            };
+        }
+
+        Ok(data)
+    }
+
+    /// Flush any output buffered inside the decoders once the body stream has ended. A coding
+    /// left mid-stream (an incomplete gzip/deflate/zstd frame, or a brotli stream that never
+    /// reported completion) is a truncated body, surfaced as an error so the caller can turn it
+    /// into a network error.
+    pub fn finish(&mut self) -> std::io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+
+        if let Some(decoder) = self.gzip.as_mut() {
+            decoder.try_finish()?;
+            data.append(&mut std::mem::take(decoder.get_mut()));
+        }
+        if let Some(decoder) = self.deflate.as_mut() {
+            decoder.try_finish()?;
+            data.append(&mut std::mem::take(decoder.get_mut()));
+        }
+        if let Some(decoder) = self.zstd.as_mut() {
+            decoder.flush()?;
+            data.append(&mut std::mem::take(decoder.get_mut()));
+        }
+        if self.has_brotli {
+            data.append(&mut self.decode_brotli_so_far()?);
+        }
+
+        Ok(data)
+    }
+
+    fn decode_brotli_so_far(&mut self) -> std::io::Result<Vec<u8>> {
+        use std::io::Read;
+
+        let mut decompressor = brotli::Decompressor::new(
+            self.brotli_input.as_slice(),
+            self.brotli_input.len().max(1),
+        );
+        let mut decoded = Vec::new();
+        decompressor.read_to_end(&mut decoded)?;
+
+        let new_output = decoded.split_off(self.brotli_decoded_so_far.min(decoded.len()));
+        self.brotli_decoded_so_far = decoded.len() + new_output.len();
+        Ok(new_output)
+    }
+}
@@ -0,0 +1,322 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A shared HTTP cache, keyed by request URL and the request headers named by a stored response's
+//! `Vary` header, driven by `Cache-Control`/`Expires`/`Age`/`Date` freshness as implemented on
+//! [`Response`] (see `response.rs`).
+//!
+//! Wiring this into the fetch pipeline — looking entries up before a request goes out, and
+//! revalidating or storing afterwards — is the job of the `net` crate's fetch loop, which doesn't
+//! exist in this snapshot (only this `net_traits`-level crate does), so `HttpCache` stands ready
+//! for that loop to drive, the same way e.g. `BatteryManager` stands ready for an embedder source
+//! it doesn't yet have.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use http::HeaderMap;
+use http::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, RANGE, VARY};
+use servo_url::ServoUrl;
+
+use crate::http_status::HttpStatus;
+use crate::response::{ByteRange, CacheState, Response, ResponseBody};
+
+/// The (URL, varying-request-header-values) key a [`CachedResponse`] is stored and looked up
+/// under, per <https://httpwg.org/specs/rfc9111.html#rfc.section.4.1>.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct CacheKey {
+    url: ServoUrl,
+    vary: Vec<(String, Option<String>)>,
+}
+
+impl CacheKey {
+    fn new(url: ServoUrl, response_headers: &HeaderMap, request_headers: &HeaderMap) -> CacheKey {
+        let vary = response_headers
+            .get(VARY)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty() && *name != "*")
+                    .map(|name| {
+                        let value = request_headers
+                            .get(name)
+                            .and_then(|value| value.to_str().ok())
+                            .map(str::to_owned);
+                        (name.to_ascii_lowercase(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        CacheKey { url, vary }
+    }
+}
+
+/// The byte ranges of one resource collected so far from `206 Partial Content` responses, kept
+/// sorted and coalesced, alongside the validator they were captured under --
+/// <https://httpwg.org/specs/rfc9111.html#rfc.section.3.1> requires treating a partial response
+/// without a strong validator as effectively uncombinable with anything stored under a different
+/// one, so a validator change clears everything already cached.
+#[derive(Clone, Debug, Default)]
+struct CachedRanges {
+    validator: (Option<String>, Option<String>),
+    /// Sorted by `ByteRange::start`, with no two entries adjacent or overlapping.
+    ranges: Vec<(ByteRange, Vec<u8>)>,
+}
+
+impl CachedRanges {
+    /// Insert a freshly-fetched `range` of `bytes`, coalescing it with whatever it touches or
+    /// overlaps. Where two pieces overlap, the piece that starts first (the existing one, on equal
+    /// starts) keeps its bytes and only the other's non-overlapping suffix is appended, trusting the server to have served consistent bytes for
+    /// the same resource rather than reconciling a genuine mismatch.
+    fn insert(&mut self, range: ByteRange, bytes: Vec<u8>) {
+        self.ranges.push((range, bytes));
+        self.ranges.sort_by_key(|(range, _)| range.start);
+
+        let mut merged: Vec<(ByteRange, Vec<u8>)> = Vec::with_capacity(self.ranges.len());
+        for (range, bytes) in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some((last_range, last_bytes)) if last_range.is_adjacent_to(&range) => {
+                    if range.end > last_range.end {
+                        let overlap = (last_range.end + 1 - range.start) as usize;
+                        last_bytes.extend_from_slice(&bytes[overlap.min(bytes.len())..]);
+                        *last_range = last_range.union(&range);
+                    }
+                },
+                _ => merged.push((range, bytes)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    fn covers(&self, requested: ByteRange) -> bool {
+        self.ranges.iter().any(|(range, _)| range.covers(&requested))
+    }
+
+    fn get(&self, requested: ByteRange) -> Option<Vec<u8>> {
+        let (range, bytes) = self
+            .ranges
+            .iter()
+            .find(|(range, _)| range.covers(&requested))?;
+        let start = (requested.start - range.start) as usize;
+        let end = start + requested.len() as usize;
+        Some(bytes[start..end].to_vec())
+    }
+
+    fn stored_ranges(&self) -> Vec<ByteRange> {
+        self.ranges.iter().map(|(range, _)| *range).collect()
+    }
+}
+
+/// A stored response, plus the bookkeeping needed to decide freshness and build a conditional
+/// revalidation request later.
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+    pub response: Response,
+    /// When this entry was stored. Freshness checks don't read it: `Response::current_age` works
+    /// from the response's own `Date` and `Age` headers.
+    pub stored_at: SystemTime,
+    /// Set once this entry has been populated from one or more `206 Partial Content` responses,
+    /// per <https://fetch.spec.whatwg.org/#concept-response-cache-state>; `response`'s own body is
+    /// left empty in that case, with the actual bytes tracked here instead.
+    ranges: Option<CachedRanges>,
+}
+
+impl CachedResponse {
+    /// The byte ranges of this entry's body that are actually cached; empty for an entry stored
+    /// from an ordinary whole-body response.
+    pub fn stored_ranges(&self) -> Vec<ByteRange> {
+        self.ranges
+            .as_ref()
+            .map(CachedRanges::stored_ranges)
+            .unwrap_or_default()
+    }
+
+    /// Whether `requested` is entirely satisfied by what's already cached.
+    pub fn covers_range(&self, requested: ByteRange) -> bool {
+        self.ranges
+            .as_ref()
+            .is_some_and(|ranges| ranges.covers(requested))
+    }
+
+    /// The bytes satisfying `requested`, if [`CachedResponse::covers_range`] would return `true`.
+    pub fn get_range(&self, requested: ByteRange) -> Option<Vec<u8>> {
+        self.ranges.as_ref()?.get(requested)
+    }
+
+    /// A conditional `Range` request's headers (`Range` plus `If-Range`) for fetching `requested`,
+    /// built from this entry's stored validators, per
+    /// <https://httpwg.org/specs/rfc9110.html#rfc.section.13.1.5>.
+    pub fn conditional_range_headers(&self, requested: ByteRange) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if let Ok(value) = format!("bytes={}-{}", requested.start, requested.end).parse() {
+            headers.insert(RANGE, value);
+        }
+
+        let (etag, last_modified) = self.response.stored_validators();
+        if let Some(etag) = etag.and_then(|value| value.parse().ok()) {
+            headers.insert(IF_RANGE, etag);
+        } else if let Some(last_modified) = last_modified.and_then(|value| value.parse().ok()) {
+            headers.insert(IF_RANGE, last_modified);
+        }
+
+        headers
+    }
+}
+
+impl CachedResponse {
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.4>
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        self.response.is_fresh(now)
+    }
+
+    /// A conditional-request header list (`If-None-Match`/`If-Modified-Since`) built from this
+    /// entry's stored validators, per
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.4.3.1>. Empty if the stored response
+    /// carried neither validator, in which case the entry can't be revalidated and must be
+    /// treated as stale-and-unusable instead.
+    pub fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let (etag, last_modified) = self.response.stored_validators();
+
+        if let Some(etag) = etag.and_then(|value| value.parse().ok()) {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+
+        if let Some(last_modified) = last_modified.and_then(|value| value.parse().ok()) {
+            headers.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        headers
+    }
+}
+
+/// A shared HTTP cache. One instance is meant to be kept per origin-agnostic resource thread, the
+/// way the real `net` crate keeps one `CoreResourceManager`-owned cache for all fetches.
+#[derive(Default)]
+pub struct HttpCache {
+    entries: Mutex<HashMap<CacheKey, CachedResponse>>,
+}
+
+impl HttpCache {
+    pub fn new() -> HttpCache {
+        HttpCache::default()
+    }
+
+    /// Look up a stored response for `url`, matching `request_headers` against the stored
+    /// entry's `Vary` list.
+    pub fn lookup(&self, url: &ServoUrl, request_headers: &HeaderMap) -> Option<CachedResponse> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .find(|(key, _)| {
+                key.url == *url &&
+                    key.vary.iter().all(|(name, value)| {
+                        let actual = request_headers
+                            .get(name.as_str())
+                            .and_then(|value| value.to_str().ok());
+                        actual == value.as_deref()
+                    })
+            })
+            .map(|(_, entry)| entry.clone())
+    }
+
+    /// Store `response` for `url`, keyed by the subset of `request_headers` its `Vary` header
+    /// names. A `no-store` response (<https://httpwg.org/specs/rfc9111.html#rfc.section.3>) is
+    /// never stored.
+    pub fn store(
+        &self,
+        url: ServoUrl,
+        request_headers: &HeaderMap,
+        response: Response,
+        now: SystemTime,
+    ) {
+        if !response.is_storable() {
+            return;
+        }
+
+        let key = CacheKey::new(url, &response.headers, request_headers);
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(
+            key,
+            CachedResponse {
+                response,
+                stored_at: now,
+                ranges: None,
+            },
+        );
+    }
+
+    /// Merge a `206 Partial Content` response's satisfied range into the entry for `url`,
+    /// creating one if none exists yet, per
+    /// <https://httpwg.org/specs/rfc9110.html#rfc.section.14.2>. Does nothing if `response` isn't
+    /// actually a partial-content response with a `Content-Range` header. A `no-store` response
+    /// is never merged in, same as `store` above.
+    pub fn store_partial(
+        &self,
+        url: ServoUrl,
+        request_headers: &HeaderMap,
+        response: Response,
+        now: SystemTime,
+    ) {
+        if !response.is_storable() {
+            return;
+        }
+
+        let Some(range) = response.satisfied_range() else {
+            return;
+        };
+
+        let validator = response.stored_validators();
+        let key = CacheKey::new(url, &response.headers, request_headers);
+        let mut stored_response = response.clone();
+        // The clone shares `response`'s body, so take the bytes out before emptying it.
+        let bytes = response.into_bytes();
+        *stored_response.body.lock().unwrap() = ResponseBody::Empty;
+        stored_response.cache_state = CacheState::Partial;
+
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry(key).or_insert_with(|| CachedResponse {
+            response: stored_response.clone(),
+            stored_at: now,
+            ranges: None,
+        });
+
+        let ranges = entry.ranges.get_or_insert_with(CachedRanges::default);
+        if ranges.validator != validator {
+            ranges.ranges.clear();
+        }
+        ranges.validator = validator;
+        ranges.insert(range, bytes);
+
+        entry.response = stored_response;
+        entry.stored_at = now;
+    }
+
+    /// Apply the result of revalidating `entry`: a `304 Not Modified` merges `new_headers` onto
+    /// the cached response and marks it `Validated`, per
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.4.3.4>; any other status means the
+    /// served response replaces the entry outright (handled by the caller via `store`).
+    pub fn handle_revalidation_response(
+        entry: &CachedResponse,
+        status: &HttpStatus,
+        new_headers: &HeaderMap,
+    ) -> Option<Response> {
+        if status.code() != 304 {
+            return None;
+        }
+
+        let mut response = entry.response.clone();
+        for (name, value) in new_headers.iter() {
+            response.headers.insert(name.clone(), value.clone());
+        }
+        response.cache_state = CacheState::Validated;
+
+        Some(response)
+    }
+}
@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A builder for synthetic [`Response`]s, so that fetch-pipeline behaviour (redirect handling,
+//! CORS/opaque filtering, cache-state transitions) can be exercised deterministically in tests
+//! without boxing a real HTTP client or opening a socket.
+//!
+//! This would sit behind a `testing`-only Cargo feature and be pulled in from `dev-dependencies`
+//! in a full build; this snapshot has no `Cargo.toml` to declare one in, so the module is left
+//! unconditionally compiled instead.
+
+use http::HeaderMap;
+use http::header::{HeaderName, HeaderValue};
+use servo_url::ServoUrl;
+
+use crate::ResourceTimingType;
+use crate::response::{Response, ResponseBody, ResponseInit, ResponseType};
+
+/// Assembles a canned `status_code`/headers/body and, via [`MockResponseBuilder::build`], runs it
+/// through [`Response::from_init`] and [`Response::to_filtered`] exactly as a live fetch would.
+#[derive(Debug)]
+pub struct MockResponseBuilder {
+    url: ServoUrl,
+    status_code: u16,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl MockResponseBuilder {
+    pub fn new(url: ServoUrl) -> MockResponseBuilder {
+        MockResponseBuilder {
+            url,
+            status_code: 200,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn status_code(mut self, status_code: u16) -> MockResponseBuilder {
+        self.status_code = status_code;
+        self
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> MockResponseBuilder {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn body(mut self, body: Vec<u8>) -> MockResponseBuilder {
+        self.body = body;
+        self
+    }
+
+    /// Build a real [`Response`] carrying the canned body, filtered as `response_type`.
+    ///
+    /// Passing [`ResponseType::Default`] skips filtering, since [`Response::to_filtered`] panics
+    /// on that variant.
+    pub fn build(self, response_type: ResponseType) -> Response {
+        let init = ResponseInit {
+            url: self.url,
+            headers: self.headers,
+            status_code: self.status_code,
+            referrer: None,
+            location_url: None,
+        };
+
+        let response = Response::from_init(init, ResourceTimingType::Navigation);
+        *response.body.lock().unwrap() = ResponseBody::Done(self.body);
+
+        match response_type {
+            ResponseType::Default => response,
+            _ => response.to_filtered(response_type),
+        }
+    }
+}
@@ -4,16 +4,24 @@
 
 //! The [Response](https://fetch.spec.whatwg.org/#responses) object
 //! resulting from a [fetch operation](https://fetch.spec.whatwg.org/#concept-fetch)
-use std::sync::Mutex;
+use std::collections::VecDeque;
 use std::sync::atomic::AtomicBool;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, SystemTime};
 
+use bytes::Bytes;
 use http::HeaderMap;
+use http::header::{
+    AGE, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, DATE, ETAG, EXPIRES,
+    LAST_MODIFIED,
+};
 use hyper_serde::Serde;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
 use servo_arc::Arc;
 use servo_url::ServoUrl;
 
+use crate::content_decoding::ContentDecoder;
 use crate::fetch::headers::extract_mime_type_as_mime;
 use crate::http_status::HttpStatus;
 use crate::{
@@ -42,22 +50,150 @@ pub enum TerminationReason {
 
 /// The response body can still be pushed to after fetch
 /// This provides a way to store unfinished response bodies
-#[derive(Clone, Debug, MallocSizeOf, PartialEq)]
+#[derive(Clone, Debug, MallocSizeOf)]
 pub enum ResponseBody {
     Empty, // XXXManishearth is this necessary, or is Done(vec![]) enough?
     Receiving(Vec<u8>),
+    /// A body being produced faster than (or instead of) being fully buffered up front, backed by
+    /// a bounded chunk queue: see [`StreamingBody`].
+    Streaming(#[ignore_malloc_size_of = "bounded by capacity, not heap-tracked here"] Arc<StreamingBody>),
     Done(Vec<u8>),
 }
 
+impl PartialEq for ResponseBody {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ResponseBody::Empty, ResponseBody::Empty) => true,
+            (ResponseBody::Receiving(a), ResponseBody::Receiving(b)) => a == b,
+            (ResponseBody::Done(a), ResponseBody::Done(b)) => a == b,
+            (ResponseBody::Streaming(a), ResponseBody::Streaming(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
 impl ResponseBody {
     pub fn is_done(&self) -> bool {
-        match *self {
+        match self {
             ResponseBody::Done(..) => true,
+            ResponseBody::Streaming(streaming) => streaming.is_done(),
             ResponseBody::Empty | ResponseBody::Receiving(..) => false,
         }
     }
 }
 
+/// A bounded queue of not-yet-consumed body chunks backing [`ResponseBody::Streaming`].
+///
+/// `push` blocks while the queue already holds `capacity` chunks, so a producer (the network
+/// loop pushing chunks as they arrive) applies real backpressure against a consumer that falls
+/// behind, instead of buffering an unbounded body in memory the way [`ResponseBody::Receiving`]
+/// does. `next_chunk` blocks until a chunk is available or the producer calls `finish`.
+///
+/// There's no async executor anywhere in this crate to drive a real `Stream`/`Future`-based API,
+/// so this (and `Response::body_chunks`/`Response::into_bytes` below) block the calling thread on
+/// a condition variable instead, which gives the same backpressure synchronously.
+#[derive(Debug)]
+pub struct StreamingBody {
+    state: Mutex<StreamingBodyState>,
+    not_full: Condvar,
+    not_empty: Condvar,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct StreamingBodyState {
+    chunks: VecDeque<Bytes>,
+    done: bool,
+}
+
+impl StreamingBody {
+    pub fn new(capacity: usize) -> Arc<StreamingBody> {
+        Arc::new(StreamingBody {
+            state: Mutex::new(StreamingBodyState::default()),
+            not_full: Condvar::new(),
+            not_empty: Condvar::new(),
+            capacity,
+        })
+    }
+
+    /// Queue `chunk` for consumption, blocking while the queue is already at `capacity`.
+    pub fn push(&self, chunk: Bytes) {
+        let mut state = self.state.lock().unwrap();
+        while state.chunks.len() >= self.capacity && !state.done {
+            state = self.not_full.wait(state).unwrap();
+        }
+        state.chunks.push_back(chunk);
+        self.not_empty.notify_one();
+    }
+
+    /// Signal that no more chunks will be pushed. Wakes any consumer blocked in `next_chunk` so
+    /// it can observe the drained queue and return `None`.
+    pub fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.done = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    /// Block until the next chunk is available, or return `None` once `finish` has been called
+    /// and the queue has drained.
+    pub fn next_chunk(&self) -> Option<Bytes> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(chunk) = state.chunks.pop_front() {
+                self.not_full.notify_one();
+                return Some(chunk);
+            }
+            if state.done {
+                return None;
+            }
+            state = self.not_empty.wait(state).unwrap();
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.done && state.chunks.is_empty()
+    }
+}
+
+/// An iterator over a response body's chunks as they arrive, returned by [`Response::body_chunks`].
+///
+/// A [`ResponseBody::Streaming`] body blocks between chunks exactly as `StreamingBody::next_chunk`
+/// does; an already-buffered [`ResponseBody::Receiving`]/[`ResponseBody::Done`] body yields its
+/// full contents as a single chunk, and [`ResponseBody::Empty`] yields nothing.
+pub struct BodyChunks {
+    body: Arc<Mutex<ResponseBody>>,
+    buffered_taken: bool,
+}
+
+impl Iterator for BodyChunks {
+    type Item = Bytes;
+
+    fn next(&mut self) -> Option<Bytes> {
+        let streaming = match &*self.body.lock().unwrap() {
+            ResponseBody::Streaming(streaming) => Some(Arc::clone(streaming)),
+            _ => None,
+        };
+
+        if let Some(streaming) = streaming {
+            return streaming.next_chunk();
+        }
+
+        if self.buffered_taken {
+            return None;
+        }
+        self.buffered_taken = true;
+
+        match &*self.body.lock().unwrap() {
+            ResponseBody::Receiving(bytes) | ResponseBody::Done(bytes) if !bytes.is_empty() => {
+                Some(Bytes::copy_from_slice(bytes))
+            },
+            _ => None,
+        }
+    }
+}
+
 /// [Cache state](https://fetch.spec.whatwg.org/#concept-response-cache-state)
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum CacheState {
@@ -75,6 +211,133 @@ pub enum HttpsState {
     Modern,
 }
 
+/// The subset of a [`Response`] that fetch-pipeline logic (redirect handling, CORS filtering,
+/// cache-state transitions) actually reads, implemented by `Response`, so that logic can be unit
+/// tested against a synthetic response built with
+/// [`MockResponseBuilder`](crate::mock_response::MockResponseBuilder) without a live fetch.
+pub trait HttpResponse {
+    fn status(&self) -> &HttpStatus;
+    fn headers(&self) -> &HeaderMap;
+    fn body(&self) -> Arc<Mutex<ResponseBody>>;
+    fn url(&self) -> Option<&ServoUrl>;
+}
+
+impl HttpResponse for Response {
+    fn status(&self) -> &HttpStatus {
+        &self.status
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    fn body(&self) -> Arc<Mutex<ResponseBody>> {
+        Response::body(self)
+    }
+
+    fn url(&self) -> Option<&ServoUrl> {
+        Response::url(self)
+    }
+}
+
+/// An inclusive byte range, as carried by the `Range` request header and the `Content-Range`
+/// response header. <https://httpwg.org/specs/rfc9110.html#rfc.section.14.1.2>
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn new(start: u64, end: u64) -> ByteRange {
+        ByteRange { start, end }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Always `false`: `start <= end` is an invariant of every `ByteRange`, so `len()` is never 0.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Whether `self` fully satisfies a request for `other`.
+    pub fn covers(&self, other: &ByteRange) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Whether `self` and `other` touch or overlap, and so can be coalesced into one range.
+    pub fn is_adjacent_to(&self, other: &ByteRange) -> bool {
+        self.start <= other.end.saturating_add(1) && other.start <= self.end.saturating_add(1)
+    }
+
+    pub fn union(&self, other: &ByteRange) -> ByteRange {
+        ByteRange {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+/// Parse a `Content-Range: bytes <start>-<end>/<size-or-*>` response header into the interval it
+/// satisfies, per <https://httpwg.org/specs/rfc9110.html#rfc.section.14.4>. Returns `None` for
+/// anything else, including an unsatisfied-range `Content-Range: bytes */<size>`.
+fn parse_content_range(headers: &HeaderMap) -> Option<ByteRange> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.trim().strip_prefix("bytes ")?.split('/').next()?;
+    let (start, end) = range.split_once('-')?;
+    let start = start.trim().parse().ok()?;
+    let end: u64 = end.trim().parse().ok()?;
+
+    (start <= end).then_some(ByteRange { start, end })
+}
+
+/// The subset of [`Cache-Control`](https://httpwg.org/specs/rfc9111.html#rfc.section.5.2.2) response
+/// directives this cache understands.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheControlDirectives {
+    pub no_store: bool,
+    pub no_cache: bool,
+    pub must_revalidate: bool,
+    pub private: bool,
+    pub immutable: bool,
+    pub max_age: Option<Duration>,
+}
+
+fn parse_cache_control(headers: &HeaderMap) -> CacheControlDirectives {
+    let mut directives = CacheControlDirectives::default();
+
+    let Some(value) = headers.get(CACHE_CONTROL).and_then(|value| value.to_str().ok()) else {
+        return directives;
+    };
+
+    for directive in value.split(',').map(str::trim) {
+        let mut parts = directive.splitn(2, '=');
+        match parts.next().unwrap_or("").to_ascii_lowercase().as_str() {
+            "no-store" => directives.no_store = true,
+            "no-cache" => directives.no_cache = true,
+            "must-revalidate" => directives.must_revalidate = true,
+            "private" => directives.private = true,
+            "immutable" => directives.immutable = true,
+            "max-age" => {
+                directives.max_age = parts
+                    .next()
+                    .and_then(|seconds| seconds.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs);
+            },
+            _ => {},
+        }
+    }
+
+    directives
+}
+
+fn parse_http_date(headers: &HeaderMap, name: http::header::HeaderName) -> Option<SystemTime> {
+    let value = headers.get(name)?.to_str().ok()?;
+    httpdate::parse_http_date(value).ok()
+}
+
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct ResponseInit {
     pub url: ServoUrl,
@@ -191,6 +454,10 @@ impl Response {
         self.url.as_ref()
     }
 
+    pub fn body(&self) -> Arc<Mutex<ResponseBody>> {
+        self.body.clone()
+    }
+
     pub fn is_network_error(&self) -> bool {
         matches!(self.response_type, ResponseType::Error(..))
     }
@@ -230,6 +497,177 @@ impl Response {
         Arc::clone(&self.resource_timing)
     }
 
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.5.2.2> directives on this response.
+    pub fn cache_control(&self) -> CacheControlDirectives {
+        parse_cache_control(&self.headers)
+    }
+
+    /// The `Date` header, parsed as specified by
+    /// <https://httpwg.org/specs/rfc9110.html#rfc.section.6.6.1>.
+    pub fn date(&self) -> Option<SystemTime> {
+        parse_http_date(&self.headers, DATE)
+    }
+
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.4.2.1>: how long this response may be
+    /// served from cache without being considered stale, derived from `max-age` if present,
+    /// falling back to `Expires` relative to `Date`.
+    pub fn freshness_lifetime(&self) -> Option<Duration> {
+        if let Some(max_age) = self.cache_control().max_age {
+            return Some(max_age);
+        }
+
+        let date = self.date()?;
+        let expires = parse_http_date(&self.headers, EXPIRES)?;
+        expires.duration_since(date).ok()
+    }
+
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.4.2.3>, simplified: the larger of the
+    /// sender's `Age` header (if any) and the time elapsed since this response's `Date`. Nothing
+    /// is added for the delay before the response was received.
+    pub fn current_age(&self, now: SystemTime) -> Duration {
+        let apparent_age = self
+            .date()
+            .and_then(|date| now.duration_since(date).ok())
+            .unwrap_or_default();
+
+        let age_value = self
+            .headers
+            .get(AGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+
+        apparent_age.max(age_value)
+    }
+
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.4>: whether this response may still be
+    /// served from cache without revalidation, at `now`.
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        if self.cache_control().no_cache {
+            return false;
+        }
+
+        match self.freshness_lifetime() {
+            Some(lifetime) => self.current_age(now) < lifetime,
+            None => false,
+        }
+    }
+
+    /// Whether this response is storable in a shared HTTP cache at all, per
+    /// <https://httpwg.org/specs/rfc9111.html#rfc.section.3>: a bare `no-store` response never is.
+    pub fn is_storable(&self) -> bool {
+        !self.cache_control().no_store
+    }
+
+    /// The validators <https://httpwg.org/specs/rfc9111.html#rfc.section.4.3> needs to build a
+    /// conditional request (`If-None-Match`/`If-Modified-Since`) once this entry goes stale.
+    pub fn stored_validators(&self) -> (Option<String>, Option<String>) {
+        let etag = self
+            .headers
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        let last_modified = self
+            .headers
+            .get(LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+
+        (etag, last_modified)
+    }
+
+    /// The byte interval satisfied by this response, if it's a `206 Partial Content` response
+    /// carrying a `Content-Range` header. <https://httpwg.org/specs/rfc9110.html#rfc.section.15.3.7>
+    pub fn satisfied_range(&self) -> Option<ByteRange> {
+        if self.status.code() != 206 {
+            return None;
+        }
+
+        parse_content_range(&self.headers)
+    }
+
+    /// An iterator over this response's body chunks as they arrive; see [`BodyChunks`].
+    pub fn body_chunks(&self) -> BodyChunks {
+        BodyChunks {
+            body: Arc::clone(&self.body),
+            buffered_taken: false,
+        }
+    }
+
+    /// Buffer this response's entire body into a single contiguous `Vec<u8>`, draining a
+    /// [`ResponseBody::Streaming`] body via `StreamingBody::next_chunk` if necessary.
+    pub fn into_bytes(self) -> Vec<u8> {
+        let taken = std::mem::replace(&mut *self.body.lock().unwrap(), ResponseBody::Empty);
+        match taken {
+            ResponseBody::Done(bytes) | ResponseBody::Receiving(bytes) => bytes,
+            ResponseBody::Empty => Vec::new(),
+            ResponseBody::Streaming(streaming) => {
+                let mut bytes = Vec::new();
+                while let Some(chunk) = streaming.next_chunk() {
+                    bytes.extend_from_slice(&chunk);
+                }
+                bytes
+            },
+        }
+    }
+
+    /// Decode `chunk` through `decoder`'s `Content-Encoding` stages and append the result to
+    /// this response's body, per <https://fetch.spec.whatwg.org/#content-encoding-and-decoding>.
+    /// A truncated or malformed stream replaces `self` with a network error outright, since the
+    /// body can no longer be trusted.
+    pub fn append_decoded_body_chunk(self, chunk: &[u8], decoder: &mut ContentDecoder) -> Response {
+        let decoded = match decoder.decode(chunk) {
+            Ok(decoded) => decoded,
+            Err(_) => {
+                return Response::network_error(NetworkError::Internal(
+                    "malformed Content-Encoding stream".to_owned(),
+                ));
+            },
+        };
+
+        {
+            let mut body = self.body.lock().unwrap();
+            match &mut *body {
+                ResponseBody::Receiving(buf) => buf.extend_from_slice(&decoded),
+                ResponseBody::Empty => *body = ResponseBody::Receiving(decoded),
+                ResponseBody::Done(_) => {},
+            }
+        }
+
+        self
+    }
+
+    /// Flush `decoder`'s remaining buffered output, transition the body to [`ResponseBody::Done`],
+    /// and strip the now-consumed `Content-Encoding`/`Content-Length` headers. A coding left
+    /// mid-stream when the network signals end-of-body means the body was truncated, which also
+    /// replaces `self` with a network error.
+    pub fn finish_decoded_body(self, decoder: &mut ContentDecoder) -> Response {
+        let tail = match decoder.finish() {
+            Ok(tail) => tail,
+            Err(_) => {
+                return Response::network_error(NetworkError::Internal(
+                    "truncated Content-Encoding stream".to_owned(),
+                ));
+            },
+        };
+
+        {
+            let mut body = self.body.lock().unwrap();
+            let mut bytes = match std::mem::replace(&mut *body, ResponseBody::Empty) {
+                ResponseBody::Receiving(buf) | ResponseBody::Done(buf) => buf,
+                ResponseBody::Empty => Vec::new(),
+            };
+            bytes.extend_from_slice(&tail);
+            *body = ResponseBody::Done(bytes);
+        }
+
+        let mut response = self;
+        response.headers.remove(CONTENT_ENCODING);
+        response.headers.remove(CONTENT_LENGTH);
+        response
+    }
+
     /// Convert to a filtered response, of type `filter_type`.
     /// Do not use with type Error or Default
     #[rustfmt::skip]
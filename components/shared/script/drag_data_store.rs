@@ -0,0 +1,124 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A serializable snapshot of a drag operation's data store.
+//!
+//! Servo runs script in separate processes from the compositor/constellation, so a real OS drag
+//! that crosses documents/origins cannot carry a `DataTransfer`'s contents directly: the
+//! `DataTransferItemList` backing it only exists inside the originating script thread. This
+//! module gives `dragstart` something IPC-safe to hand to the constellation, and `drop` something
+//! a (potentially different) script thread can reconstruct a fresh item list from.
+//!
+//! <https://html.spec.whatwg.org/multipage/dnd.html#the-drag-data-store>
+
+use serde::{Deserialize, Serialize};
+
+/// Mirrors the WebIDL `DataTransfer.dropEffect` enum without depending on script's codegen
+/// bindings (this crate sits below `script` in the dependency graph).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum DragDropEffect {
+    None,
+    Copy,
+    Link,
+    Move,
+}
+
+/// Mirrors the WebIDL `DataTransfer.effectAllowed` enum without depending on script's codegen
+/// bindings (this crate sits below `script` in the dependency graph).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum DragEffectAllowed {
+    None,
+    Copy,
+    CopyLink,
+    CopyMove,
+    Link,
+    LinkMove,
+    Move,
+    All,
+    Uninitialized,
+}
+
+/// A blob-backed handle to a file dragged from the OS file manager, rather than the file's bytes
+/// inline, so large drags don't copy their payload through the IPC channel.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DragDataStoreFile {
+    pub name: String,
+    pub type_: String,
+    /// Identifier `DataTransfer::to_drag_data_store` builds from the file's name (`blob:<name>`).
+    /// Nothing resolves it back into a `File`/`Blob`, so `DataTransfer::new_for_drop` leaves
+    /// file items out of the rebuilt item list.
+    pub blob_handle: String,
+}
+
+/// Whether a [`DragDataStoreEntry`] names a file or a directory.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum DragDataStoreEntryKind {
+    File,
+    Directory,
+}
+
+/// A dropped OS filesystem entry, dragged from the file manager and recursively enumerable via
+/// `webkitGetAsEntry()` on the reconstructed item. Carries only the path/kind needed to rebuild
+/// the `FileSystemEntry` tree, not its contents.
+///
+/// `children` is `None` for a file and `Some` (possibly empty) for a directory, so a dropped
+/// folder's full listing crosses the IPC boundary in one shot alongside the rest of the drag
+/// data store.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DragDataStoreEntry {
+    pub kind: DragDataStoreEntryKind,
+    pub name: String,
+    pub full_path: String,
+    pub children: Option<Vec<DragDataStoreEntry>>,
+}
+
+/// One entry of a [`DragDataStore`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum DragDataStoreItemValue {
+    String(String),
+    File(DragDataStoreFile),
+    Entry(DragDataStoreEntry),
+}
+
+/// One `(kind, type, value)` triple from the drag data store's item list.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DragDataStoreItem {
+    pub kind: String,
+    pub type_: String,
+    pub value: DragDataStoreItemValue,
+}
+
+/// The hotspot coordinates and bitmap for the drag feedback image, if one was set via
+/// `DataTransfer.setDragImage()`.
+///
+/// <https://html.spec.whatwg.org/multipage/dnd.html#drag-data-store-bitmap>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DragDataStoreBitmap {
+    /// Opaque identifier the compositor resolves into the rasterized drag feedback image.
+    pub bitmap_handle: String,
+    pub hotspot_x: i32,
+    pub hotspot_y: i32,
+}
+
+/// An IPC-serializable snapshot of a `DataTransfer`'s drag data store, sent from the originating
+/// script thread to the constellation on `dragstart`, and handed to the target document's script
+/// thread to reconstruct a `DataTransferItemList` from on `drop`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DragDataStore {
+    pub items: Vec<DragDataStoreItem>,
+    pub bitmap: Option<DragDataStoreBitmap>,
+    pub drop_effect: DragDropEffect,
+    pub effect_allowed: DragEffectAllowed,
+}
+
+impl DragDataStore {
+    pub fn new(effect_allowed: DragEffectAllowed) -> DragDataStore {
+        DragDataStore {
+            items: Vec::new(),
+            bitmap: None,
+            drop_effect: DragDropEffect::None,
+            effect_allowed,
+        }
+    }
+}
@@ -213,6 +213,10 @@ pub enum ScriptToConstellationMessage {
     GetWebGPUChan(IpcSender<Option<WebGPU>>),
     /// Notify the constellation of a pipeline's document's title.
     TitleChanged(PipelineId, String),
+    /// <https://w3c.github.io/ServiceWorker/#terminate-service-worker>: ask the SW manager to
+    /// wind down a single worker belonging to this registration, because it was replaced,
+    /// unregistered, or its idle timeout fired.
+    TerminateServiceWorker(ServiceWorkerRegistrationId, ServiceWorkerId),
     /// Notify the constellation that the size of some `<iframe>`s has changed.
     IFrameSizes(Vec<IFrameSizeMsg>),
     /// Request results from the memory reporter.
@@ -272,6 +276,12 @@ pub enum ServiceWorkerMsg {
     ForwardDOMMessage(DOMMessage, ServoUrl),
     /// <https://w3c.github.io/ServiceWorker/#schedule-job-algorithm>
     ScheduleJob(Job),
+    /// <https://w3c.github.io/ServiceWorker/#terminate-service-worker>: set the named worker's
+    /// closing flag, reject any in-flight `JobResult` promises tied to it, and drain its task
+    /// queues before tearing the worker down. The manager should only consider a subsequent
+    /// `Job` for the same scope safe to run once this has finished, so that it never races a
+    /// still-draining worker from a previous registration.
+    Terminate(ServiceWorkerId),
     /// Exit the service worker manager
     Exit,
 }
@@ -382,8 +392,24 @@ impl PartialEq for Job {
 /// Messages outgoing from the Service Worker Manager thread to constellation
 #[derive(Debug, Deserialize, Serialize)]
 pub enum SWManagerMsg {
-    /// Placeholder to keep the enum,
-    /// as it will be needed when implementing
-    /// <https://github.com/servo/servo/issues/24660>
-    PostMessageToClient,
+    /// <https://w3c.github.io/ServiceWorker/#service-worker-postmessage>: deliver a `message`
+    /// event on the `ServiceWorkerContainer` of the window client identified by `client_id`.
+    /// The constellation resolves `client_id` to its owning browsing context with
+    /// [`ScriptToConstellationMessage::GetBrowsingContextInfo`], then to a top-level
+    /// [`WebViewId`] with [`ScriptToConstellationMessage::GetTopForBrowsingContext`], in order
+    /// to find the script thread that's actually running the client and route the message to
+    /// it. If `data` carries transferred `MessagePort`s, the client-side script thread
+    /// re-entangles them through the same `NewMessagePort`/`EntanglePorts` path used for
+    /// same-thread transfers, per <https://github.com/servo/servo/issues/24660>.
+    PostMessageToClient {
+        /// The window client to deliver the message to.
+        client_id: PipelineId,
+        /// The structured-cloned message, possibly carrying transferred `MessagePort`s.
+        data: StructuredSerializedData,
+        /// The service worker that sent this message.
+        source: ServiceWorkerId,
+        /// The origin of the sending worker's registration, used to populate the delivered
+        /// `MessageEvent`'s `origin` attribute.
+        origin: ImmutableOrigin,
+    },
 }
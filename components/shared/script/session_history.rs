@@ -38,6 +38,13 @@ impl NestedHistoryId {
         static NEXT_NESTED_HISTORY_ID: AtomicUsize = AtomicUsize::new(0);
         Self(NEXT_NESTED_HISTORY_ID.fetch_add(1, Ordering::Relaxed))
     }
+
+    /// A nested history's id must equal the id of the child navigable whose session history it
+    /// holds, so unlike [`Self::next`] this doesn't mint a fresh id of its own - it wraps one
+    /// handed in by the navigable's owner (the constellation, which owns the navigable id space).
+    pub fn from_navigable_id(id: usize) -> Self {
+        Self(id)
+    }
 }
 
 impl Default for NestedHistoryId {
@@ -54,6 +61,10 @@ pub struct NestedHistory {
 }
 
 impl NestedHistory {
+    pub fn new(id: NestedHistoryId, entries: Vec<SessionHistoryEntry>) -> NestedHistory {
+        NestedHistory { id, entries }
+    }
+
     pub fn id(&self) -> usize {
         self.id.0
     }
@@ -64,7 +75,7 @@ impl NestedHistory {
 }
 
 /// Holds state inside a session history entry regarding how to present and, if necessary, recreate,
-/// a Document. 
+/// a Document.
 ///
 /// <https://html.spec.whatwg.org/multipage/#document-state-2>
 #[derive(Clone, Debug)]
@@ -78,7 +89,19 @@ pub struct DocumentState {
     pub initiator_origin: Option<MutableOrigin>,
     pub origin: ImmutableOrigin,
     pub about_base_url: Option<ServoUrl>,
-    pub request_referrer_policy: ReferrerPolicy
+    pub request_referrer_policy: ReferrerPolicy,
+    /// The document's effective domain, set by `document.domain = ...` to relax same-origin
+    /// checks between subdomains. `None` until the setter has been used.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/origin.html#concept-document-domain>
+    effective_domain: Option<String>,
+    /// Reasons, added through `add_bfcache_block_reason`, why this document could not enter, or could not
+    /// be restored from, the back/forward cache (e.g. an active unload handler, an open
+    /// connection, or a `Cache-Control: no-store` response). Surfaced to script as
+    /// `PerformanceNavigationTiming.notRestoredReasons`.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#not-restored-reasons-reasons>
+    pub bfcache_block_reasons: Vec<String>,
 }
 
 
@@ -101,10 +124,98 @@ impl DocumentState {
             origin,
             about_base_url,
             request_referrer_policy: ReferrerPolicy::default(),
+            effective_domain: None,
+            bfcache_block_reasons: vec![],
+        }
+    }
+
+    /// Record a reason this document failed to enter, or could not be restored from, the
+    /// back/forward cache. Nothing in this tree calls it, so `bfcache_block_reasons` stays
+    /// empty.
+    pub fn add_bfcache_block_reason(&mut self, reason: String) {
+        self.bfcache_block_reasons.push(reason);
+    }
+
+    /// The document's effective domain, falling back to the origin's host when it has never
+    /// been relaxed via `document.domain =`.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/origin.html#concept-document-domain>
+    pub fn effective_domain(&self) -> Option<String> {
+        self.effective_domain
+            .clone()
+            .or_else(|| self.origin.host().map(|host| host.to_string()))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/dom.html#dom-document-domain>
+    ///
+    /// Callers are responsible for the "document is sandboxed and has the sandboxed document.domain
+    /// browsing context flag set" check, since that flag lives on the `Document`, not here.
+    pub fn set_effective_domain(&mut self, new_domain: &str) -> Result<(), DocumentDomainError> {
+        // Step 4. If this's origin is an opaque origin, then throw a "SecurityError" DOMException.
+        let Some(host) = self.origin.host() else {
+            return Err(DocumentDomainError::OpaqueOrigin);
+        };
+
+        // Step 6. If newDomain is not a registrable domain suffix of and is not equal to
+        // document's origin's host, then throw a "SecurityError" DOMException.
+        let host = host.to_string();
+        if !is_registrable_domain_suffix_of_or_eq(new_domain, &host) {
+            return Err(DocumentDomainError::NotRegistrableDomainSuffix);
         }
+
+        // Step 7. Set document's origin's domain to the new domain.
+        self.effective_domain = Some(new_domain.to_owned());
+
+        Ok(())
+    }
+
+    /// Two documents are "same origin-domain" when neither has set an effective domain and they
+    /// are same origin, or when both have set the same effective domain and their schemes match.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/origin.html#same-origin-domain>
+    pub fn is_same_origin_domain(&self, other: &DocumentState) -> bool {
+        // If neither document set document.domain, fall back to same origin.
+        if self.effective_domain.is_none() && other.effective_domain.is_none() {
+            return self.origin.same_origin(&other.origin);
+        }
+
+        // Both must have explicitly set an (equal) effective domain, and schemes must match.
+        let schemes_match = self.origin.scheme() == other.origin.scheme();
+
+        schemes_match &&
+            self.effective_domain.is_some() &&
+            self.effective_domain == other.effective_domain
     }
 }
 
+/// Errors that can occur while relaxing a document's effective domain via `document.domain =`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DocumentDomainError {
+    /// The document's origin is opaque and therefore has no host to relax.
+    OpaqueOrigin,
+    /// The assigned value is neither equal to, nor a registrable domain suffix of, the current
+    /// host.
+    NotRegistrableDomainSuffix,
+}
+
+/// Whether `candidate` is a registrable domain suffix of, or equal to, `host`.
+///
+/// This is a simplified version of the spec algorithm that treats each dot-separated label as
+/// one unit, without consulting the public suffix list.
+fn is_registrable_domain_suffix_of_or_eq(candidate: &str, host: &str) -> bool {
+    if candidate.is_empty() {
+        return false;
+    }
+
+    if candidate == host {
+        return true;
+    }
+
+    host.ends_with(candidate) &&
+        host[..host.len() - candidate.len()].ends_with('.') &&
+        candidate.contains('.')
+}
+
 /// <https://html.spec.whatwg.org/multipage/#she-step>
 #[derive(Clone, Debug, Default)]
 pub enum SessionHistoryEntryStep {
@@ -164,6 +275,51 @@ impl SessionHistoryEntry {
     pub fn set_navigation_api_state(&mut self, state: StructuredSerializedData) {
         self.navigation_api_state = Some(state);
     }
+
+    pub fn url(&self) -> &ServoUrl {
+        &self.url
+    }
+
+    /// Whether `self` and `other` are same-origin, honoring the strict file-origin policy for
+    /// `file:` URLs (see [`strict_file_origin_policy_enabled`]) in place of the blanket
+    /// same-origin treatment `ImmutableOrigin` otherwise gives all `file:` URLs.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsers.html#concept-origins-same>
+    pub fn is_same_origin(&self, other: &SessionHistoryEntry) -> bool {
+        if self.url.scheme() == "file" && other.url.scheme() == "file" {
+            return file_urls_same_origin(&self.url, &other.url);
+        }
+
+        self.document_state.origin.same_origin(&other.document_state.origin)
+    }
+}
+
+/// Whether the strict file-origin policy is enabled: when set, two `file:` URLs are treated as
+/// same-origin only when they share the same directory prefix, rather than any two `file:` URLs
+/// being unconditionally same-origin.
+///
+/// Defaults to strict so local-file browsing gets directory-level isolation out of the box.
+fn strict_file_origin_policy_enabled() -> bool {
+    servo_config::pref!(network_strict_file_origin_policy)
+}
+
+/// <https://html.spec.whatwg.org/multipage/browsers.html#concept-origins-same> for `file:` URLs,
+/// optionally narrowed by [`strict_file_origin_policy_enabled`] to require a shared directory
+/// prefix rather than treating all `file:` URLs as same-origin.
+fn file_urls_same_origin(a: &ServoUrl, b: &ServoUrl) -> bool {
+    if !strict_file_origin_policy_enabled() {
+        return true;
+    }
+
+    let directory_of = |url: &ServoUrl| -> Option<String> {
+        let path = url.path();
+        path.rfind('/').map(|index| path[..index].to_owned())
+    };
+
+    match (directory_of(a), directory_of(b)) {
+        (Some(a_dir), Some(b_dir)) => a_dir == b_dir,
+        _ => false,
+    }
 }
 
 impl PartialEq for SessionHistoryEntry {